@@ -0,0 +1,193 @@
+use crate::{
+    difficulty::skill,
+    game::{
+        catch::{self, CatchObject, CatchObjectKind},
+        Gamemode, ModSet,
+    },
+    parser::beatmap::BeatmapFile,
+};
+
+const STAR_SCALING_FACTOR: f64 = 0.153;
+
+const SKILL_MULTIPLIER: f64 = 900.0;
+const STRAIN_DECAY_BASE: f64 = 0.2;
+const DECAY_WEIGHT: f64 = 0.94;
+const SECTION_LENGTH: f64 = 750.0;
+
+// positions are scaled so that a fruit is this wide relative to the catcher
+const NORMALIZED_HITOBJECT_RADIUS: f64 = 41.0;
+// players don't need to be dead centre under a fruit
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: f64 = 16.0;
+const DIRECTION_CHANGE_BONUS: f64 = 21.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CatchDifficulty {
+    pub mods: ModSet,
+    pub stars: f64,
+
+    // after the rate change, which catch scores care about
+    pub approach_rate: f64,
+
+    // fruits and droplets, which is also what accuracy is counted against besides
+    // the tiny droplets
+    pub max_combo: i32,
+    pub fruit_count: i32,
+    pub droplet_count: i32,
+    pub tiny_droplet_count: i32,
+    pub banana_count: i32,
+}
+
+#[derive(Debug)]
+struct DifficultyObject {
+    normalized_position: f64,
+    last_normalized_position: f64,
+    start_time: f64,
+    // capped at the equivalent of 375 bpm streams
+    strain_time: f64,
+    last_hyper_dash: bool,
+    last_distance_to_hyper_dash: f64,
+}
+
+pub fn calculate(beatmap: &BeatmapFile, mods: ModSet) -> CatchDifficulty {
+    calculate_objects(beatmap, &catch::convert(beatmap, mods), mods)
+}
+
+// for objects that didn't come straight from the map, `objects` should have been
+// converted with the same mods
+pub fn calculate_objects(
+    beatmap: &BeatmapFile,
+    objects: &[CatchObject],
+    mods: ModSet,
+) -> CatchDifficulty {
+    let attributes = beatmap.attributes(Gamemode::Catch, mods);
+    let clock_rate = attributes.clock_rate;
+    let count = |kind: CatchObjectKind| objects.iter().filter(|o| o.kind == kind).count() as i32;
+
+    let mut difficulty = CatchDifficulty {
+        mods,
+        stars: 0.0,
+        approach_rate: attributes.approach_rate,
+        max_combo: count(CatchObjectKind::Fruit) + count(CatchObjectKind::Droplet),
+        fruit_count: count(CatchObjectKind::Fruit),
+        droplet_count: count(CatchObjectKind::Droplet),
+        tiny_droplet_count: count(CatchObjectKind::TinyDroplet),
+        banana_count: count(CatchObjectKind::Banana),
+    };
+
+    // high circle sizes are harder to catch than the catcher's size alone suggests
+    let mut half_catcher_width = catch::catcher_width(attributes.circle_size) * 0.5;
+    half_catcher_width *= 1.0 - (attributes.circle_size - 5.5).max(0.0) * 0.0625;
+
+    let difficulty_objects = difficulty_objects(objects, clock_rate, half_catcher_width);
+    if difficulty_objects.is_empty() {
+        return difficulty;
+    }
+
+    let times: Vec<f64> = difficulty_objects.iter().map(|o| o.start_time).collect();
+    let values: Vec<f64> = movement_strains(&difficulty_objects, clock_rate)
+        .into_iter()
+        .map(|strain| strain * SKILL_MULTIPLIER)
+        .collect();
+    let peaks = skill::decaying_peaks(&times, &values, STRAIN_DECAY_BASE, SECTION_LENGTH);
+
+    difficulty.stars = skill::weighted_sum(peaks, DECAY_WEIGHT).sqrt() * STAR_SCALING_FACTOR;
+    difficulty
+}
+
+// bananas and tiny droplets don't give combo and aren't worth walking to
+fn difficulty_objects(
+    objects: &[CatchObject],
+    clock_rate: f64,
+    half_catcher_width: f64,
+) -> Vec<DifficultyObject> {
+    let scaling_factor = NORMALIZED_HITOBJECT_RADIUS / half_catcher_width;
+    let palpable: Vec<&CatchObject> = objects.iter().filter(|o| o.gives_combo()).collect();
+
+    palpable
+        .windows(2)
+        .map(|pair| {
+            let (last, current) = (pair[0], pair[1]);
+            let delta_time = (current.start_time - last.start_time) as f64 / clock_rate;
+
+            DifficultyObject {
+                normalized_position: current.x as f64 * scaling_factor,
+                last_normalized_position: last.x as f64 * scaling_factor,
+                start_time: current.start_time as f64 / clock_rate,
+                strain_time: delta_time.max(40.0),
+                last_hyper_dash: last.hyper_dash,
+                last_distance_to_hyper_dash: last.distance_to_hyper_dash as f64,
+            }
+        })
+        .collect()
+}
+
+// how far the catcher has to move and how quickly, with extra for changing direction
+// and for only just making it without a hyperdash
+fn movement_strains(objects: &[DifficultyObject], clock_rate: f64) -> Vec<f64> {
+    // the catcher moves faster with the clock rate, not just the fruits
+    let catcher_speed_multiplier = clock_rate;
+    let allowed_error = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
+
+    let mut last_player_position: Option<f64> = None;
+    let mut last_distance_moved: f64 = 0.0;
+    let mut last_strain_time: f64 = 0.0;
+
+    objects
+        .iter()
+        .map(|current| {
+            let last_position = last_player_position.unwrap_or(current.last_normalized_position);
+            let mut player_position = last_position.clamp(
+                current.normalized_position - allowed_error,
+                current.normalized_position + allowed_error,
+            );
+            let distance_moved = player_position - last_position;
+
+            let weighted_strain_time = current.strain_time + 13.0 + 3.0 / catcher_speed_multiplier;
+            let sqrt_strain = weighted_strain_time.sqrt();
+            let mut distance_addition = distance_moved.abs().powf(1.3) / 510.0;
+
+            if distance_moved.abs() > 0.1 {
+                if last_distance_moved.abs() > 0.1
+                    && distance_moved.signum() != last_distance_moved.signum()
+                {
+                    let bonus_factor = distance_moved.abs().min(50.0) / 50.0;
+                    let antiflow_factor = (last_distance_moved.abs().min(70.0) / 70.0).max(0.38);
+
+                    distance_addition += DIRECTION_CHANGE_BONUS / (last_strain_time + 16.0).sqrt()
+                        * bonus_factor
+                        * antiflow_factor
+                        * (1.0 - (weighted_strain_time / 1000.0).powi(3)).max(0.0);
+                }
+
+                // every movement is worth something, which gives streams some weight
+                distance_addition += 12.5
+                    * distance_moved.abs().min(NORMALIZED_HITOBJECT_RADIUS * 2.0)
+                    / (NORMALIZED_HITOBJECT_RADIUS * 6.0)
+                    / sqrt_strain;
+            }
+
+            // edge dashes, which are easier the more time there is to make them
+            if current.last_distance_to_hyper_dash <= 20.0 {
+                let mut edge_dash_bonus = 0.0;
+                if current.last_hyper_dash {
+                    // a hyperdash always lands right under the fruit
+                    player_position = current.normalized_position;
+                } else {
+                    edge_dash_bonus += 5.7;
+                }
+
+                distance_addition *= 1.0
+                    + edge_dash_bonus
+                        * ((20.0 - current.last_distance_to_hyper_dash) / 20.0)
+                        * ((current.strain_time * catcher_speed_multiplier).min(265.0) / 265.0)
+                            .powf(1.5);
+            }
+
+            last_player_position = Some(player_position);
+            last_distance_moved = distance_moved;
+            last_strain_time = current.strain_time;
+
+            distance_addition / weighted_strain_time
+        })
+        .collect()
+}
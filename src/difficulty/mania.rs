@@ -0,0 +1,160 @@
+use crate::{
+    difficulty::skill::{self, StrainPeaks},
+    game::{convert, mania::ManiaObject, Gamemode, ModSet},
+    parser::beatmap::BeatmapFile,
+    util,
+};
+
+const STAR_SCALING_FACTOR: f64 = 0.018;
+const DECAY_WEIGHT: f64 = 0.9;
+
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+// releasing a hold this far from another release is half as hard as releasing alone
+const RELEASE_THRESHOLD: f64 = 24.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManiaDifficulty {
+    pub mods: ModSet,
+    pub stars: f64,
+
+    // the 300 window after the rate change, in ms
+    pub great_hit_window: f64,
+    pub key_count: i32,
+    pub note_count: i32,
+    pub hold_count: i32,
+}
+
+#[derive(Debug)]
+struct DifficultyObject {
+    column: usize,
+    start_time: f64,
+    end_time: f64,
+    delta_time: f64,
+}
+
+// standard maps are converted first, with the key count the conversion picked
+pub fn calculate(beatmap: &BeatmapFile, mods: ModSet) -> ManiaDifficulty {
+    let (key_count, objects) = convert::mania_objects(beatmap, mods);
+    calculate_objects(beatmap, &objects, key_count, mods)
+}
+
+// for objects that didn't come straight from the map, with the key count they were
+// laid out for
+pub fn calculate_objects(
+    beatmap: &BeatmapFile,
+    objects: &[ManiaObject],
+    key_count: i32,
+    mods: ModSet,
+) -> ManiaDifficulty {
+    let attributes = beatmap.attributes(Gamemode::Mania, mods);
+    let clock_rate = attributes.clock_rate;
+    let hold_count = objects
+        .iter()
+        .filter(|o| matches!(o, ManiaObject::Hold(_)))
+        .count() as i32;
+
+    let mut difficulty = ManiaDifficulty {
+        mods,
+        stars: 0.0,
+        great_hit_window: (attributes.hit_window_300 / clock_rate).ceil(),
+        key_count,
+        note_count: objects.len() as i32 - hold_count,
+        hold_count,
+    };
+
+    // osu! sorts by rounded start time with its own unstable sort, which decides the
+    // order of notes at the same time. .NET rounds halves to even
+    let mut sorted = objects.to_vec();
+    let rounded = |object: &ManiaObject| object.start_time().round_ties_even() as i64;
+    util::legacy_sort_by(&mut sorted, |a, b| rounded(a).cmp(&rounded(b)));
+
+    let difficulty_objects: Vec<DifficultyObject> = sorted
+        .windows(2)
+        .map(|pair| DifficultyObject {
+            column: pair[1].column().clamp(0, key_count - 1) as usize,
+            start_time: pair[1].start_time() as f64 / clock_rate,
+            end_time: pair[1].end_time() as f64 / clock_rate,
+            delta_time: (pair[1].start_time() - pair[0].start_time()) as f64 / clock_rate,
+        })
+        .collect();
+
+    let peaks = strain_peaks(&difficulty_objects, key_count.max(1) as usize);
+    difficulty.stars = skill::weighted_sum(peaks, DECAY_WEIGHT) * STAR_SCALING_FACTOR;
+
+    difficulty
+}
+
+fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
+    value * skill::strain_decay(decay_base, delta_time)
+}
+
+// notes in the same column tire out one finger, everything else the whole hand.
+// holding something down makes every other note harder, and releasing a hold close
+// to another release is easier than releasing it on its own
+fn strain_peaks(objects: &[DifficultyObject], key_count: usize) -> Vec<f64> {
+    let mut peaks = StrainPeaks::new(skill::SECTION_LENGTH);
+
+    let mut start_times = vec![0.0; key_count];
+    let mut end_times = vec![0.0; key_count];
+    let mut individual_strains = vec![0.0; key_count];
+    let mut individual_strain = 0.0;
+    let mut overall_strain = 1.0;
+    let mut previous_start: Option<f64> = None;
+
+    for current in objects {
+        // the first object doesn't close a section, so it never needs the decay
+        let previous = previous_start.unwrap_or(current.start_time);
+        peaks.advance(current.start_time, |section_start| {
+            let delta_time = section_start - previous;
+            apply_decay(individual_strain, delta_time, INDIVIDUAL_DECAY_BASE)
+                + apply_decay(overall_strain, delta_time, OVERALL_DECAY_BASE)
+        });
+
+        let (start_time, end_time, column) = (current.start_time, current.end_time, current.column);
+
+        let mut is_overlapping = false;
+        let mut closest_end_time = (end_time - start_time).abs();
+        let mut hold_factor = 1.0;
+        let mut hold_addition = 0.0;
+
+        for other_end_time in &end_times {
+            is_overlapping |= other_end_time - start_time > 1.0 && end_time - other_end_time > 1.0;
+
+            if other_end_time - end_time > 1.0 {
+                hold_factor = 1.25;
+            }
+
+            closest_end_time = f64::min(closest_end_time, (end_time - other_end_time).abs());
+        }
+
+        if is_overlapping {
+            hold_addition = 1.0 / (1.0 + (0.5 * (RELEASE_THRESHOLD - closest_end_time)).exp());
+        }
+
+        individual_strains[column] = apply_decay(
+            individual_strains[column],
+            start_time - start_times[column],
+            INDIVIDUAL_DECAY_BASE,
+        );
+        individual_strains[column] += 2.0 * hold_factor;
+
+        // a chord is as hard as its hardest column
+        individual_strain = if current.delta_time <= 1.0 {
+            f64::max(individual_strain, individual_strains[column])
+        } else {
+            individual_strains[column]
+        };
+
+        overall_strain = apply_decay(overall_strain, current.delta_time, OVERALL_DECAY_BASE);
+        overall_strain += (1.0 + hold_addition) * hold_factor;
+
+        start_times[column] = start_time;
+        end_times[column] = end_time;
+
+        peaks.add(individual_strain + overall_strain);
+        previous_start = Some(start_time);
+    }
+
+    peaks.peaks()
+}
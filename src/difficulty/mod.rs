@@ -0,0 +1,17 @@
+// exports
+pub mod catch;
+pub mod mania;
+mod skill;
+pub mod standard;
+pub mod taiko;
+
+// maps a 0-10 setting onto the values at 0, 5 and 10, like osu!'s difficulty range
+pub(crate) fn difficulty_range(value: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if value > 5.0 {
+        mid + (max - mid) * (value - 5.0) / 5.0
+    } else if value < 5.0 {
+        mid - (mid - min) * (5.0 - value) / 5.0
+    } else {
+        mid
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+
+pub(crate) const SECTION_LENGTH: f64 = 400.0;
+
+// skills are rated by the highest strain in every section of the map
+pub(crate) struct StrainPeaks {
+    section_length: f64,
+    section_end: Option<f64>,
+    peak: f64,
+    peaks: Vec<f64>,
+}
+
+impl StrainPeaks {
+    pub(crate) fn new(section_length: f64) -> StrainPeaks {
+        StrainPeaks {
+            section_length,
+            section_end: None,
+            peak: 0.0,
+            peaks: vec![],
+        }
+    }
+
+    // closes every section that ends before `time`, new sections start out with
+    // whatever `initial` says the strain has decayed to by their start
+    pub(crate) fn advance<F: Fn(f64) -> f64>(&mut self, time: f64, initial: F) {
+        let length = self.section_length;
+        let mut section_end = match self.section_end {
+            Some(end) => end,
+            // the first object doesn't close anything
+            None => (time / length).ceil() * length,
+        };
+
+        while time > section_end {
+            self.peaks.push(self.peak);
+            self.peak = initial(section_end);
+            section_end += length;
+        }
+
+        self.section_end = Some(section_end);
+    }
+
+    pub(crate) fn add(&mut self, strain: f64) {
+        self.peak = self.peak.max(strain);
+    }
+
+    // including the section that is still open
+    pub(crate) fn peaks(&self) -> Vec<f64> {
+        let mut peaks = self.peaks.clone();
+        if self.section_end.is_some() {
+            peaks.push(self.peak);
+        }
+
+        peaks
+    }
+}
+
+pub(crate) fn strain_decay(base: f64, milliseconds: f64) -> f64 {
+    base.powf(milliseconds / 1000.0)
+}
+
+// highest strains first, each one worth `decay_weight` as much as the one before
+pub(crate) fn weighted_sum(mut strains: Vec<f64>, decay_weight: f64) -> f64 {
+    strains.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut weight = 1.0;
+    let mut sum = 0.0;
+    for strain in strains {
+        sum += strain * weight;
+        weight *= decay_weight;
+    }
+
+    sum
+}
+
+// keeps the last `capacity` values, dropping the oldest
+pub(crate) fn push_limited<T>(queue: &mut VecDeque<T>, value: T, capacity: usize) {
+    if queue.len() == capacity {
+        queue.pop_front();
+    }
+
+    queue.push_back(value);
+}
+
+// section peaks of a skill whose strain decays exponentially between objects, `values`
+// are what each object adds to the strain
+pub(crate) fn decaying_peaks(
+    times: &[f64],
+    values: &[f64],
+    decay_base: f64,
+    section_length: f64,
+) -> Vec<f64> {
+    let mut peaks = StrainPeaks::new(section_length);
+    let mut strain = 0.0;
+
+    for (index, (time, value)) in times.iter().zip(values).enumerate() {
+        let previous_time = index.checked_sub(1).map_or(*time, |i| times[i]);
+        peaks.advance(*time, |section_start| {
+            strain * strain_decay(decay_base, section_start - previous_time)
+        });
+
+        strain *= strain_decay(decay_base, time - previous_time);
+        strain += value;
+        peaks.add(strain);
+    }
+
+    peaks.peaks()
+}
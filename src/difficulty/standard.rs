@@ -0,0 +1,936 @@
+// a port of osu!'s standard difficulty calculator as of the November 2022 pp
+// deploy (aim, speed and flashlight, slider factor and speed note count). later
+// changes to osu!'s calculator aren't ported, so current stars can differ
+
+use std::f64::consts::PI;
+
+use crate::{
+    difficulty::skill::{self, StrainPeaks},
+    game::{Gamemode, ModSet, Mods},
+    parser::beatmap::{
+        objects::{HitObject, HitType, SliderObjectType},
+        BeatmapFile,
+    },
+    util::Vector2,
+};
+
+// distances are measured as if every circle had this radius
+const NORMALISED_RADIUS: f64 = 50.0;
+const MIN_DELTA_TIME: f64 = 25.0;
+
+// how far the cursor can stray from a slider's path while following it
+const MAXIMUM_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 2.4;
+const ASSUMED_SLIDER_RADIUS: f64 = NORMALISED_RADIUS * 1.8;
+
+const PLAYFIELD_HEIGHT: f32 = 384.0;
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+pub(crate) const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
+
+const SECTION_LENGTH: f64 = 400.0;
+const DECAY_WEIGHT: f64 = 0.9;
+const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+const STRAIN_DIFFICULTY_MULTIPLIER: f64 = 1.06;
+
+const AIM_MULTIPLIER: f64 = 23.55;
+const AIM_DECAY_BASE: f64 = 0.15;
+const AIM_REDUCED_SECTION_COUNT: usize = 10;
+const WIDE_ANGLE_MULTIPLIER: f64 = 1.5;
+const ACUTE_ANGLE_MULTIPLIER: f64 = 1.95;
+const SLIDER_MULTIPLIER: f64 = 1.35;
+const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
+
+const SPEED_MULTIPLIER: f64 = 1375.0;
+const SPEED_DECAY_BASE: f64 = 0.3;
+const SPEED_REDUCED_SECTION_COUNT: usize = 5;
+const SPEED_DIFFICULTY_MULTIPLIER: f64 = 1.04;
+const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+// 200 bpm 1/4ths
+const MIN_SPEED_BONUS: f64 = 75.0;
+const SPEED_BALANCING_FACTOR: f64 = 40.0;
+const HISTORY_TIME_MAX: f64 = 5000.0;
+const RHYTHM_MULTIPLIER: f64 = 0.75;
+
+const FLASHLIGHT_MULTIPLIER: f64 = 0.052;
+const FLASHLIGHT_DECAY_BASE: f64 = 0.15;
+const MAX_OPACITY_BONUS: f64 = 0.4;
+const HIDDEN_BONUS: f64 = 0.2;
+const MIN_VELOCITY: f64 = 0.5;
+const FLASHLIGHT_SLIDER_MULTIPLIER: f64 = 1.3;
+const MIN_ANGLE_MULTIPLIER: f64 = 0.2;
+
+// hidden fades objects out over this share of the preempt
+const HIDDEN_FADE_OUT_MULTIPLIER: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardDifficulty {
+    pub mods: ModSet,
+    pub stars: f64,
+
+    // skill ratings, flashlight only counts towards the stars with the mod on
+    pub aim: f64,
+    pub speed: f64,
+    pub flashlight: f64,
+    // aim without slider movement over aim with it, 1.0 for maps without sliders
+    pub slider_factor: f64,
+    // roughly how many objects are as hard to tap as the hardest one
+    pub speed_note_count: f64,
+
+    // settings as played, rate mods included
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub drain_rate: f64,
+
+    pub max_combo: i32,
+    pub circle_count: i32,
+    pub slider_count: i32,
+    pub spinner_count: i32,
+}
+
+// where the cursor can get away with being at the end of a slider
+#[derive(Debug, Clone, Copy)]
+struct LazySlider {
+    end_position: Vector2,
+    travel_distance: f64,
+    travel_time: f64,
+}
+
+// an object along with everything the skills need to know about getting to it
+#[derive(Debug)]
+struct DifficultyObject<'a> {
+    object: &'a HitObject,
+    lazy: Option<LazySlider>,
+    position: Vector2,
+    end_position: Vector2,
+
+    // all times are divided by the clock rate
+    start_time: f64,
+    delta_time: f64,
+    strain_time: f64,
+
+    lazy_jump_distance: f64,
+    minimum_jump_distance: f64,
+    minimum_jump_time: f64,
+    travel_distance: f64,
+    travel_time: f64,
+    angle: Option<f64>,
+    // the whole 300 window, both sides
+    hit_window_great: f64,
+}
+
+impl<'a> DifficultyObject<'a> {
+    fn is_slider(&self) -> bool {
+        self.lazy.is_some()
+    }
+
+    fn is_spinner(&self) -> bool {
+        is_spinner(self.object)
+    }
+}
+
+pub fn calculate(beatmap: &BeatmapFile, mods: ModSet) -> StandardDifficulty {
+    let has = |m: Mods| mods.contains(m);
+
+    // the stacks from parsing are for the unmodified approach rate
+    let restacked;
+    let beatmap = if has(Mods::HardRock) || has(Mods::Easy) {
+        restacked = beatmap.restacked(mods);
+        &restacked
+    } else {
+        beatmap
+    };
+
+    let attributes = beatmap.attributes(Gamemode::Standard, mods);
+    let clock_rate = attributes.clock_rate;
+    let preempt = attributes.preempt;
+    let hit_window_great = attributes.hit_window_300;
+
+    let objects = &beatmap.hit_objects;
+    let count = |hit_type: i32| {
+        objects
+            .iter()
+            .filter(|object| object.hit_type & hit_type != 0)
+            .count() as i32
+    };
+
+    let mut difficulty = StandardDifficulty {
+        mods,
+        stars: 0.0,
+        aim: 0.0,
+        speed: 0.0,
+        flashlight: 0.0,
+        slider_factor: 1.0,
+        speed_note_count: 0.0,
+        approach_rate: attributes.approach_rate,
+        overall_difficulty: attributes.overall_difficulty,
+        drain_rate: attributes.hp_drain,
+        max_combo: max_combo(beatmap),
+        circle_count: count(HitType::Normal as i32),
+        slider_count: count(HitType::Slider as i32),
+        spinner_count: count(HitType::Spinner as i32),
+    };
+
+    let radius = attributes.circle_radius;
+    let difficulty_objects = difficulty_objects(
+        objects,
+        radius,
+        has(Mods::HardRock),
+        clock_rate,
+        hit_window_great,
+    );
+
+    if difficulty_objects.is_empty() {
+        return difficulty;
+    }
+
+    let hidden = has(Mods::Hidden);
+    let fade_in = attributes.fade_in;
+
+    let aim = aim_value(&difficulty_objects, true);
+    let aim_no_sliders = aim_value(&difficulty_objects, false);
+    let (speed, speed_note_count) = speed_value(&difficulty_objects);
+    let flashlight = flashlight_value(&difficulty_objects, radius, preempt, fade_in, hidden);
+
+    let mut aim_rating = aim.sqrt() * DIFFICULTY_MULTIPLIER;
+    let aim_rating_no_sliders = aim_no_sliders.sqrt() * DIFFICULTY_MULTIPLIER;
+    let mut speed_rating = speed.sqrt() * DIFFICULTY_MULTIPLIER;
+    let mut flashlight_rating = flashlight.sqrt() * DIFFICULTY_MULTIPLIER;
+
+    if aim_rating > 0.0 {
+        difficulty.slider_factor = aim_rating_no_sliders / aim_rating;
+    }
+
+    if has(Mods::Relax) {
+        aim_rating *= 0.9;
+        speed_rating = 0.0;
+        flashlight_rating *= 0.7;
+    }
+
+    let base_performance =
+        |rating: f64| (5.0 * (rating / DIFFICULTY_MULTIPLIER).max(1.0) - 4.0).powi(3) / 100000.0;
+
+    let flashlight_performance = if has(Mods::Flashlight) {
+        flashlight_rating.powi(2) * 25.0
+    } else {
+        0.0
+    };
+
+    let performance = (base_performance(aim_rating).powf(1.1)
+        + base_performance(speed_rating).powf(1.1)
+        + flashlight_performance.powf(1.1))
+    .powf(1.0 / 1.1);
+
+    if performance > 0.00001 {
+        difficulty.stars = PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100000.0 / 2f64.powf(1.0 / 1.1) * performance).cbrt() + 4.0);
+    }
+
+    difficulty.aim = aim_rating;
+    difficulty.speed = speed_rating;
+    difficulty.flashlight = flashlight_rating;
+    difficulty.speed_note_count = speed_note_count;
+
+    difficulty
+}
+
+// a full combo gets one for every circle, spinner and slider head, tick, repeat and end
+pub(crate) fn max_combo(beatmap: &BeatmapFile) -> i32 {
+    beatmap
+        .hit_objects
+        .iter()
+        .map(|object| match &object.slider_objects {
+            Some(slider_objects) if object.hit_type & HitType::Slider as i32 != 0 => {
+                slider_objects.len() as i32
+            }
+            _ => 1,
+        })
+        .sum()
+}
+
+fn is_spinner(object: &HitObject) -> bool {
+    object.hit_type & HitType::Spinner as i32 != 0
+}
+
+fn difficulty_objects(
+    objects: &[HitObject],
+    radius: f64,
+    hard_rock: bool,
+    clock_rate: f64,
+    hit_window_great: f64,
+) -> Vec<DifficultyObject<'_>> {
+    // hard rock flips the map before it is stacked, stacks still lean up and left
+    let place = |object: &HitObject, position: Vector2| {
+        let position = if hard_rock {
+            Vector2::new(position.x, PLAYFIELD_HEIGHT - position.y)
+        } else {
+            position
+        };
+
+        position + object.stack_offset(radius as f32)
+    };
+
+    // distances are scaled so every circle size is compared on the same terms,
+    // with a bonus for very small circles
+    let mut scaling_factor = NORMALISED_RADIUS / radius;
+    if radius < 30.0 {
+        scaling_factor *= 1.0 + (30.0 - radius).min(5.0) / 50.0;
+    }
+
+    let mut difficulty_objects: Vec<DifficultyObject> = vec![];
+
+    for (index, object) in objects.iter().enumerate().skip(1) {
+        let last = &objects[index - 1];
+        let lazy = lazy_slider(object, &place, radius);
+
+        let start_time = object.start_time as f64 / clock_rate;
+        let delta_time = (object.start_time - last.start_time) as f64 / clock_rate;
+        let strain_time = delta_time.max(MIN_DELTA_TIME);
+
+        let mut current = DifficultyObject {
+            object,
+            lazy,
+            position: place(object, object.position),
+            end_position: place(object, object.end_position),
+            start_time,
+            delta_time,
+            strain_time,
+            lazy_jump_distance: 0.0,
+            minimum_jump_distance: 0.0,
+            minimum_jump_time: strain_time,
+            travel_distance: 0.0,
+            travel_time: 0.0,
+            angle: None,
+            hit_window_great: if is_spinner(object) {
+                0.0
+            } else {
+                2.0 * hit_window_great / clock_rate
+            },
+        };
+
+        if let Some(lazy) = lazy {
+            current.travel_distance = lazy.travel_distance;
+            current.travel_time = (lazy.travel_time / clock_rate).max(MIN_DELTA_TIME);
+        }
+
+        // nothing to aim for to or from a spinner
+        if is_spinner(object) || is_spinner(last) {
+            difficulty_objects.push(current);
+            continue;
+        }
+
+        let last_lazy = lazy_slider(last, &place, radius);
+        let last_cursor = cursor_end(last, last_lazy, &place);
+
+        current.lazy_jump_distance = (current.position.scale(scaling_factor as f32)
+            - last_cursor.scale(scaling_factor as f32))
+        .len() as f64;
+        current.minimum_jump_distance = current.lazy_jump_distance;
+
+        if let Some(last_lazy) = last_lazy {
+            let last_travel_time = (last_lazy.travel_time / clock_rate).max(MIN_DELTA_TIME);
+            current.minimum_jump_time = (strain_time - last_travel_time).max(MIN_DELTA_TIME);
+
+            // players follow through sliders that lead into the next object, so the
+            // jump can start anywhere between the lazy end and the real tail
+            let tail_jump_distance =
+                (place(last, last.end_position) - current.position).len() as f64 * scaling_factor;
+            current.minimum_jump_distance = (current.lazy_jump_distance
+                - (MAXIMUM_SLIDER_RADIUS - ASSUMED_SLIDER_RADIUS))
+                .min(tail_jump_distance - MAXIMUM_SLIDER_RADIUS)
+                .max(0.0);
+        }
+
+        if index >= 2 {
+            let last_last = &objects[index - 2];
+
+            if !is_spinner(last_last) {
+                let last_last_lazy = lazy_slider(last_last, &place, radius);
+                let last_last_cursor = cursor_end(last_last, last_last_lazy, &place);
+
+                let v1 = last_last_cursor - place(last, last.position);
+                let v2 = current.position - last_cursor;
+                let dot = v1.dot(v2) as f64;
+                let det = (v1.x * v2.y - v1.y * v2.x) as f64;
+
+                current.angle = Some(det.atan2(dot).abs());
+            }
+        }
+
+        difficulty_objects.push(current);
+    }
+
+    difficulty_objects
+}
+
+fn cursor_end<F: Fn(&HitObject, Vector2) -> Vector2>(
+    object: &HitObject,
+    lazy: Option<LazySlider>,
+    place: &F,
+) -> Vector2 {
+    match lazy {
+        Some(lazy) => lazy.end_position,
+        None => place(object, object.position),
+    }
+}
+
+// follows a slider's ticks, repeats and end the way a lazy player would, only
+// moving the cursor when a tick would otherwise leave the follow circle
+fn lazy_slider<F: Fn(&HitObject, Vector2) -> Vector2>(
+    object: &HitObject,
+    place: &F,
+    radius: f64,
+) -> Option<LazySlider> {
+    if object.hit_type & HitType::Slider as i32 == 0 {
+        return None;
+    }
+
+    let slider = object.slider_data.as_ref()?;
+    let nested = object.slider_objects.as_ref()?;
+    let last_nested = nested.last()?;
+
+    let slides = slider.slides.max(1);
+    let duration = (object.end_time - object.start_time) as f64;
+    let span_duration = duration / slides as f64;
+    let travel_time = last_nested.start_time as f64 - object.start_time as f64;
+
+    // where the body is when the last tick is judged, until something better is found
+    let mut progress = if span_duration > 0.0 {
+        travel_time / span_duration
+    } else {
+        0.0
+    };
+    progress = if progress % 2.0 >= 1.0 {
+        1.0 - progress % 1.0
+    } else {
+        progress % 1.0
+    };
+
+    let path_position = |progress: f64| {
+        let distance = progress as f32 * slider.length.max(0.0);
+        place(
+            object,
+            object.position + slider.slider_body.position_at(distance),
+        )
+    };
+
+    let mut lazy_end_position = path_position(progress);
+    let mut cursor = place(object, object.position);
+    let mut travel_distance = 0.0;
+    let scaling_factor = NORMALISED_RADIUS / radius;
+
+    for (index, nested_object) in nested.iter().enumerate().skip(1) {
+        let mut movement = place(object, nested_object.position) - cursor;
+        let mut movement_length = scaling_factor * movement.len() as f64;
+        let mut required_movement = ASSUMED_SLIDER_RADIUS;
+
+        if index == nested.len() - 1 {
+            // the end only has to be in the follow circle at some point, so take
+            // whichever of the lazy end and the real end is closer
+            let lazy_movement = lazy_end_position - cursor;
+            if lazy_movement.len() < movement.len() {
+                movement = lazy_movement;
+            }
+
+            movement_length = scaling_factor * movement.len() as f64;
+        } else if nested_object.slider_object_type as i32 == SliderObjectType::SliderRepeat as i32 {
+            // repeats are harder to cheat
+            required_movement = NORMALISED_RADIUS;
+        }
+
+        if movement_length > required_movement {
+            let scale = (movement_length - required_movement) / movement_length;
+            cursor = cursor + movement.scale(scale as f32);
+            movement_length *= scale;
+            travel_distance += movement_length;
+        }
+
+        if index == nested.len() - 1 {
+            lazy_end_position = cursor;
+        }
+    }
+
+    // bonus for repeat sliders until repeats get a proper treatment
+    let repeat_count = (slides - 1) as f64;
+    travel_distance *= (1.0 + repeat_count / 2.5).powf(1.0 / 2.5);
+
+    Some(LazySlider {
+        end_position: lazy_end_position,
+        travel_distance,
+        travel_time,
+    })
+}
+
+// the hardest sections count the most, the very hardest are toned down a little so
+// a single spike doesn't carry the whole map
+fn reduced_difficulty(peaks: Vec<f64>, reduced_section_count: usize, multiplier: f64) -> f64 {
+    let mut strains: Vec<f64> = peaks.into_iter().filter(|peak| *peak > 0.0).collect();
+    strains.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    for (index, strain) in strains.iter_mut().take(reduced_section_count).enumerate() {
+        let progress = (index as f64 / reduced_section_count as f64).clamp(0.0, 1.0);
+        let scale = (1.0 + 9.0 * progress).log10();
+        *strain *= REDUCED_STRAIN_BASELINE + (1.0 - REDUCED_STRAIN_BASELINE) * scale;
+    }
+
+    skill::weighted_sum(strains, DECAY_WEIGHT) * multiplier
+}
+
+fn aim_value(objects: &[DifficultyObject], with_sliders: bool) -> f64 {
+    let mut peaks = StrainPeaks::new(SECTION_LENGTH);
+    let mut strain = 0.0;
+
+    for (index, current) in objects.iter().enumerate() {
+        let previous_time = index
+            .checked_sub(1)
+            .map_or(current.start_time, |i| objects[i].start_time);
+        peaks.advance(current.start_time, |time| {
+            strain * skill::strain_decay(AIM_DECAY_BASE, time - previous_time)
+        });
+
+        strain *= skill::strain_decay(AIM_DECAY_BASE, current.delta_time);
+        strain += aim_strain(objects, index, with_sliders) * AIM_MULTIPLIER;
+        peaks.add(strain);
+    }
+
+    reduced_difficulty(
+        peaks.peaks(),
+        AIM_REDUCED_SECTION_COUNT,
+        STRAIN_DIFFICULTY_MULTIPLIER,
+    )
+}
+
+fn wide_angle_bonus(angle: f64) -> f64 {
+    (3.0 / 4.0 * (angle.clamp(PI / 6.0, 5.0 / 6.0 * PI) - PI / 6.0))
+        .sin()
+        .powi(2)
+}
+
+fn acute_angle_bonus(angle: f64) -> f64 {
+    1.0 - wide_angle_bonus(angle)
+}
+
+fn aim_strain(objects: &[DifficultyObject], index: usize, with_sliders: bool) -> f64 {
+    let current = &objects[index];
+    if current.is_spinner() || index <= 1 || objects[index - 1].is_spinner() {
+        return 0.0;
+    }
+
+    let last = &objects[index - 1];
+    let last_last = &objects[index - 2];
+
+    // the velocity into the object, carried through the slider before it
+    let velocity = |object: &DifficultyObject, previous: &DifficultyObject| {
+        let mut velocity = object.lazy_jump_distance / object.strain_time;
+
+        if previous.is_slider() && with_sliders {
+            let travel_velocity = previous.travel_distance / previous.travel_time;
+            let movement_velocity = object.minimum_jump_distance / object.minimum_jump_time;
+            velocity = velocity.max(movement_velocity + travel_velocity);
+        }
+
+        velocity
+    };
+
+    let mut current_velocity = velocity(current, last);
+    let mut previous_velocity = velocity(last, last_last);
+
+    let mut wide_bonus = 0.0;
+    let mut acute_bonus = 0.0;
+    let mut velocity_change_bonus = 0.0;
+    let mut slider_bonus = 0.0;
+
+    let mut strain = current_velocity;
+
+    // angles only matter while the rhythm stays the same
+    if current.strain_time.max(last.strain_time) < 1.25 * current.strain_time.min(last.strain_time)
+    {
+        if let (Some(current_angle), Some(last_angle), Some(last_last_angle)) =
+            (current.angle, last.angle, last_last.angle)
+        {
+            let angle_bonus = current_velocity.min(previous_velocity);
+
+            wide_bonus = wide_angle_bonus(current_angle);
+            acute_bonus = acute_angle_bonus(current_angle);
+
+            // only wiggles faster than 300 bpm 1/2s are worth anything
+            if current.strain_time > 100.0 {
+                acute_bonus = 0.0;
+            } else {
+                acute_bonus *= acute_angle_bonus(last_angle)
+                    * angle_bonus.min(125.0 / current.strain_time)
+                    * (PI / 2.0 * ((100.0 - current.strain_time) / 25.0).min(1.0))
+                        .sin()
+                        .powi(2)
+                    * (PI / 2.0 * (current.lazy_jump_distance.clamp(50.0, 100.0) - 50.0) / 50.0)
+                        .sin()
+                        .powi(2);
+            }
+
+            // repeated angles are easier
+            wide_bonus *=
+                angle_bonus * (1.0 - wide_bonus.min(wide_angle_bonus(last_angle).powi(3)));
+            acute_bonus *=
+                0.5 + 0.5 * (1.0 - acute_bonus.min(acute_angle_bonus(last_last_angle).powi(3)));
+        }
+    }
+
+    if previous_velocity.max(current_velocity) != 0.0 {
+        // changes are judged on the average velocity over the whole object
+        previous_velocity =
+            (last.lazy_jump_distance + last_last.travel_distance) / last.strain_time;
+        current_velocity =
+            (current.lazy_jump_distance + last.travel_distance) / current.strain_time;
+
+        let difference = (previous_velocity - current_velocity).abs();
+        let distance_ratio = (PI / 2.0 * difference / previous_velocity.max(current_velocity))
+            .sin()
+            .powi(2);
+        let overlap_velocity_buff =
+            (125.0 / current.strain_time.min(last.strain_time)).min(difference);
+
+        velocity_change_bonus = overlap_velocity_buff * distance_ratio;
+
+        // rhythm changes make it easier to adjust
+        velocity_change_bonus *= (current.strain_time.min(last.strain_time)
+            / current.strain_time.max(last.strain_time))
+        .powi(2);
+    }
+
+    if last.is_slider() {
+        slider_bonus = last.travel_distance / last.travel_time;
+    }
+
+    strain += (acute_bonus * ACUTE_ANGLE_MULTIPLIER).max(
+        wide_bonus * WIDE_ANGLE_MULTIPLIER + velocity_change_bonus * VELOCITY_CHANGE_MULTIPLIER,
+    );
+
+    if with_sliders {
+        strain += slider_bonus * SLIDER_MULTIPLIER;
+    }
+
+    strain
+}
+
+// returns the difficulty and how many notes are about as hard as the hardest
+fn speed_value(objects: &[DifficultyObject]) -> (f64, f64) {
+    let mut peaks = StrainPeaks::new(SECTION_LENGTH);
+    let mut strain = 0.0;
+    let mut rhythm = 0.0;
+    let mut object_strains = vec![];
+
+    for (index, current) in objects.iter().enumerate() {
+        let previous_time = index
+            .checked_sub(1)
+            .map_or(current.start_time, |i| objects[i].start_time);
+        peaks.advance(current.start_time, |time| {
+            strain * rhythm * skill::strain_decay(SPEED_DECAY_BASE, time - previous_time)
+        });
+
+        strain *= skill::strain_decay(SPEED_DECAY_BASE, current.strain_time);
+        strain += speed_strain(objects, index) * SPEED_MULTIPLIER;
+        rhythm = rhythm_complexity(objects, index);
+
+        let total = strain * rhythm;
+        object_strains.push(total);
+        peaks.add(total);
+    }
+
+    let max_strain = object_strains.iter().cloned().fold(0.0, f64::max);
+    let note_count = if max_strain > 0.0 {
+        object_strains
+            .iter()
+            .map(|strain| 1.0 / (1.0 + (-(strain / max_strain * 12.0 - 6.0)).exp()))
+            .sum()
+    } else {
+        0.0
+    };
+
+    let difficulty = reduced_difficulty(
+        peaks.peaks(),
+        SPEED_REDUCED_SECTION_COUNT,
+        SPEED_DIFFICULTY_MULTIPLIER,
+    );
+
+    (difficulty, note_count)
+}
+
+fn speed_strain(objects: &[DifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.is_spinner() {
+        return 0.0;
+    }
+
+    let mut strain_time = current.strain_time;
+
+    // doubles that can be tapped as one are nerfed
+    let mut doubletapness = 1.0;
+    if let Some(next) = objects.get(index + 1) {
+        let current_delta = current.delta_time.max(1.0);
+        let next_delta = next.delta_time.max(1.0);
+        let difference = (next_delta - current_delta).abs();
+        let speed_ratio = current_delta / current_delta.max(difference);
+        let window_ratio = (current_delta / current.hit_window_great).min(1.0).powi(2);
+
+        doubletapness = speed_ratio.powf(1.0 - window_ratio);
+    }
+
+    // anything faster than the 300 window can be mashed
+    strain_time /= (strain_time / current.hit_window_great / 0.93).clamp(0.92, 1.0);
+
+    let mut speed_bonus = 1.0;
+    if strain_time < MIN_SPEED_BONUS {
+        speed_bonus += 0.75 * ((MIN_SPEED_BONUS - strain_time) / SPEED_BALANCING_FACTOR).powi(2);
+    }
+
+    let travel_distance = index
+        .checked_sub(1)
+        .map_or(0.0, |i| objects[i].travel_distance);
+    let distance = SINGLE_SPACING_THRESHOLD.min(travel_distance + current.minimum_jump_distance);
+
+    (speed_bonus + speed_bonus * (distance / SINGLE_SPACING_THRESHOLD).powf(3.5)) * doubletapness
+        / strain_time
+}
+
+// rewards changes in rhythm, 1.0 for a steady one
+fn rhythm_complexity(objects: &[DifficultyObject], index: usize) -> f64 {
+    let current = &objects[index];
+    if current.is_spinner() {
+        return 0.0;
+    }
+
+    // `previous(0)` is the object right before this one
+    let previous = |i: usize| &objects[index - i - 1];
+
+    let mut previous_island_size = 0;
+    let mut complexity_sum = 0.0;
+    let mut island_size = 1;
+    let mut start_ratio = 0.0;
+    let mut first_delta_switch = false;
+
+    let historical_note_count = index.min(32);
+
+    let mut rhythm_start = 0;
+    while rhythm_start + 2 < historical_note_count
+        && current.start_time - previous(rhythm_start).start_time < HISTORY_TIME_MAX
+    {
+        rhythm_start += 1;
+    }
+
+    for i in (1..=rhythm_start).rev() {
+        let current_object = previous(i - 1);
+        let previous_object = previous(i);
+        let last_object = previous(i + 1);
+
+        // older notes count for less, by time or by count
+        let historical_decay = ((HISTORY_TIME_MAX
+            - (current.start_time - current_object.start_time))
+            / HISTORY_TIME_MAX)
+            .min((historical_note_count - i) as f64 / historical_note_count as f64);
+
+        let current_delta = current_object.strain_time;
+        let previous_delta = previous_object.strain_time;
+        let last_delta = last_object.strain_time;
+
+        let current_ratio = 1.0
+            + 6.0
+                * (PI / (previous_delta.min(current_delta) / previous_delta.max(current_delta)))
+                    .sin()
+                    .powi(2)
+                    .min(0.5);
+
+        let window = current_object.hit_window_great * 0.3;
+        let window_penalty =
+            (((previous_delta - current_delta).abs() - window).max(0.0) / window).min(1.0);
+
+        let mut effective_ratio = window_penalty * current_ratio;
+
+        if first_delta_switch {
+            if !(previous_delta > 1.25 * current_delta || previous_delta * 1.25 < current_delta) {
+                // the island is still going
+                if island_size < 7 {
+                    island_size += 1;
+                }
+            } else {
+                // speeding up into or out of a slider has a lenient window
+                if current_object.is_slider() {
+                    effective_ratio *= 0.125;
+                }
+                if previous_object.is_slider() {
+                    effective_ratio *= 0.25;
+                }
+                // triplet into triplet and the like
+                if previous_island_size == island_size {
+                    effective_ratio *= 0.25;
+                }
+                if previous_island_size % 2 == island_size % 2 {
+                    effective_ratio *= 0.5;
+                }
+                // 1/1 to 1/2 to 1/4 isn't much of a change
+                if last_delta > previous_delta + 10.0 && previous_delta > current_delta + 10.0 {
+                    effective_ratio *= 0.125;
+                }
+
+                complexity_sum += (effective_ratio * start_ratio).sqrt()
+                    * historical_decay
+                    * (4.0 + island_size as f64).sqrt()
+                    / 2.0
+                    * (4.0 + previous_island_size as f64).sqrt()
+                    / 2.0;
+
+                start_ratio = effective_ratio;
+                previous_island_size = island_size;
+
+                // slowing down ends the count
+                if previous_delta * 1.25 < current_delta {
+                    first_delta_switch = false;
+                }
+
+                island_size = 1;
+            }
+        } else if previous_delta > 1.25 * current_delta {
+            // speeding up starts a new island
+            first_delta_switch = true;
+            start_ratio = effective_ratio;
+            island_size = 1;
+        }
+    }
+
+    (4.0 + complexity_sum * RHYTHM_MULTIPLIER).sqrt() / 2.0
+}
+
+fn flashlight_value(
+    objects: &[DifficultyObject],
+    radius: f64,
+    preempt: f64,
+    fade_in: f64,
+    hidden: bool,
+) -> f64 {
+    let mut peaks = StrainPeaks::new(SECTION_LENGTH);
+    let mut strain = 0.0;
+
+    for (index, current) in objects.iter().enumerate() {
+        let previous_time = index
+            .checked_sub(1)
+            .map_or(current.start_time, |i| objects[i].start_time);
+        peaks.advance(current.start_time, |time| {
+            strain * skill::strain_decay(FLASHLIGHT_DECAY_BASE, time - previous_time)
+        });
+
+        strain *= skill::strain_decay(FLASHLIGHT_DECAY_BASE, current.delta_time);
+        strain += flashlight_strain(objects, index, radius, preempt, fade_in, hidden)
+            * FLASHLIGHT_MULTIPLIER;
+        peaks.add(strain);
+    }
+
+    peaks.peaks().iter().sum::<f64>() * STRAIN_DIFFICULTY_MULTIPLIER
+}
+
+// how visible an object is at `time`, without the rate change
+fn opacity_at(object: &HitObject, time: f64, preempt: f64, fade_in: f64, hidden: bool) -> f64 {
+    let start_time = object.start_time as f64;
+    if time > start_time {
+        return 0.0;
+    }
+
+    let fade_in_start = start_time - preempt;
+    let opacity = ((time - fade_in_start) / fade_in).clamp(0.0, 1.0);
+
+    if hidden {
+        let fade_out_start = fade_in_start + fade_in;
+        let fade_out_duration = preempt * HIDDEN_FADE_OUT_MULTIPLIER;
+
+        opacity.min(1.0 - ((time - fade_out_start) / fade_out_duration).clamp(0.0, 1.0))
+    } else {
+        opacity
+    }
+}
+
+fn flashlight_strain(
+    objects: &[DifficultyObject],
+    index: usize,
+    radius: f64,
+    preempt: f64,
+    fade_in: f64,
+    hidden: bool,
+) -> f64 {
+    let current = &objects[index];
+    if current.is_spinner() {
+        return 0.0;
+    }
+
+    let scaling_factor = 52.0 / radius;
+    let mut small_distance_nerf = 1.0;
+    let mut cumulative_strain_time = 0.0;
+    let mut result = 0.0;
+    let mut angle_repeat_count = 0.0;
+    let mut last = current;
+
+    // looking back in time from the current object
+    for i in 0..index.min(10) {
+        let previous = &objects[index - i - 1];
+
+        if !previous.is_spinner() {
+            let jump_distance = (current.position - previous.end_position).len() as f64;
+            cumulative_strain_time += last.strain_time;
+
+            // objects that are already inside the flashlight circle are easy
+            if i == 0 {
+                small_distance_nerf = (jump_distance / 75.0).min(1.0);
+            }
+
+            // only the first object of a stack counts
+            let stack_nerf = ((previous.lazy_jump_distance / scaling_factor) / 25.0).min(1.0);
+            let opacity_bonus = 1.0
+                + MAX_OPACITY_BONUS
+                    * (1.0
+                        - opacity_at(
+                            current.object,
+                            previous.object.start_time as f64,
+                            preempt,
+                            fade_in,
+                            hidden,
+                        ));
+
+            result += stack_nerf * opacity_bonus * scaling_factor * jump_distance
+                / cumulative_strain_time;
+
+            if let (Some(previous_angle), Some(current_angle)) = (previous.angle, current.angle) {
+                // further back counts less
+                if (previous_angle - current_angle).abs() < 0.02 {
+                    angle_repeat_count += (1.0 - 0.1 * i as f64).max(0.0);
+                }
+            }
+        }
+
+        last = previous;
+    }
+
+    result = (small_distance_nerf * result).powi(2);
+
+    // no approach circles to read from
+    if hidden {
+        result *= 1.0 + HIDDEN_BONUS;
+    }
+
+    // repeated angles are easier to memorise
+    result *= MIN_ANGLE_MULTIPLIER + (1.0 - MIN_ANGLE_MULTIPLIER) / (angle_repeat_count + 1.0);
+
+    if let (Some(lazy), Some(slider)) = (current.lazy, current.object.slider_data.as_ref()) {
+        let pixel_travel_distance = lazy.travel_distance / scaling_factor;
+
+        // faster and longer sliders need more memorising, repeats need less
+        let mut slider_bonus = (pixel_travel_distance / current.travel_time - MIN_VELOCITY)
+            .max(0.0)
+            .sqrt()
+            * pixel_travel_distance;
+
+        if slider.slides > 1 {
+            slider_bonus /= slider.slides as f64;
+        }
+
+        result += slider_bonus * FLASHLIGHT_SLIDER_MULTIPLIER;
+    }
+
+    result
+}
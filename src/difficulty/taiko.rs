@@ -0,0 +1,469 @@
+use std::collections::VecDeque;
+
+use crate::{
+    difficulty::skill::{self, push_limited},
+    game::{
+        convert,
+        taiko::{TaikoObject, TaikoObjectKind},
+        Gamemode, ModSet,
+    },
+    parser::beatmap::BeatmapFile,
+};
+
+const COLOUR_SKILL_MULTIPLIER: f64 = 0.01;
+const RHYTHM_SKILL_MULTIPLIER: f64 = 0.014;
+const STAMINA_SKILL_MULTIPLIER: f64 = 0.02;
+
+const DECAY_WEIGHT: f64 = 0.9;
+
+const COLOUR_DECAY_BASE: f64 = 0.4;
+const MONO_HISTORY_LENGTH: usize = 5;
+
+const RHYTHM_MULTIPLIER: f64 = 10.0;
+const RHYTHM_STRAIN_DECAY: f64 = 0.96;
+const RHYTHM_HISTORY_LENGTH: usize = 8;
+
+const STAMINA_DECAY_BASE: f64 = 0.4;
+const NOTE_PAIR_HISTORY_LENGTH: usize = 2;
+
+// the same pattern repeated this many times can be rolled or tapped with one hand
+const ROLL_MIN_REPETITIONS: usize = 12;
+const TL_TAP_MIN_REPETITIONS: i32 = 16;
+
+// ratios between consecutive gaps and how hard each one is to read
+const COMMON_RHYTHMS: [(f64, f64); 9] = [
+    (1.0, 0.0),
+    (2.0, 0.3),
+    (1.0 / 2.0, 0.5),
+    (3.0, 0.3),
+    (1.0 / 3.0, 0.35),
+    (3.0 / 2.0, 0.6),
+    (2.0 / 3.0, 0.4),
+    (5.0 / 4.0, 0.5),
+    (4.0 / 5.0, 0.7),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaikoDifficulty {
+    pub mods: ModSet,
+    pub stars: f64,
+
+    pub stamina: f64,
+    pub rhythm: f64,
+    pub colour: f64,
+
+    // the 300 window after the rate change, in ms
+    pub great_hit_window: f64,
+    pub max_combo: i32,
+}
+
+#[derive(Debug)]
+struct DifficultyObject {
+    // index into the converted objects
+    index: usize,
+    kind: TaikoObjectKind,
+    last_kind: TaikoObjectKind,
+    start_time: f64,
+    delta_time: f64,
+    // index into `COMMON_RHYTHMS`
+    rhythm: usize,
+    stamina_cheese: bool,
+}
+
+impl DifficultyObject {
+    fn is_hit(&self) -> bool {
+        is_hit(self.kind)
+    }
+}
+
+fn is_hit(kind: TaikoObjectKind) -> bool {
+    kind == TaikoObjectKind::Don || kind == TaikoObjectKind::Kat
+}
+
+// standard maps are converted first
+pub fn calculate(beatmap: &BeatmapFile, mods: ModSet) -> TaikoDifficulty {
+    calculate_objects(beatmap, &convert::taiko_objects(beatmap), mods)
+}
+
+// for objects that didn't come straight from the map, like converts
+pub fn calculate_objects(
+    beatmap: &BeatmapFile,
+    objects: &[TaikoObject],
+    mods: ModSet,
+) -> TaikoDifficulty {
+    let attributes = beatmap.attributes(Gamemode::Taiko, mods);
+    let clock_rate = attributes.clock_rate;
+
+    let mut difficulty = TaikoDifficulty {
+        mods,
+        stars: 0.0,
+        stamina: 0.0,
+        rhythm: 0.0,
+        colour: 0.0,
+        great_hit_window: attributes.hit_window_300 / clock_rate,
+        max_combo: objects.iter().filter(|object| object.is_hit()).count() as i32,
+    };
+
+    let mut difficulty_objects = difficulty_objects(objects, clock_rate);
+    if difficulty_objects.is_empty() {
+        return difficulty;
+    }
+
+    mark_cheese(&mut difficulty_objects);
+
+    let times: Vec<f64> = difficulty_objects.iter().map(|o| o.start_time).collect();
+    let peaks = |values: Vec<f64>, decay_base: f64| {
+        skill::decaying_peaks(&times, &values, decay_base, skill::SECTION_LENGTH)
+    };
+
+    let colour = peaks(colour_strains(&difficulty_objects), COLOUR_DECAY_BASE);
+    // rhythm keeps its own strain, the skill itself doesn't carry anything over
+    let rhythm = peaks(rhythm_strains(&difficulty_objects), 0.0);
+    let stamina_right = peaks(stamina_strains(&difficulty_objects, 0), STAMINA_DECAY_BASE);
+    let stamina_left = peaks(stamina_strains(&difficulty_objects, 1), STAMINA_DECAY_BASE);
+
+    let colour_rating = skill::weighted_sum(colour.clone(), DECAY_WEIGHT) * COLOUR_SKILL_MULTIPLIER;
+    let rhythm_rating = skill::weighted_sum(rhythm.clone(), DECAY_WEIGHT) * RHYTHM_SKILL_MULTIPLIER;
+    let mut stamina_rating = (skill::weighted_sum(stamina_right.clone(), DECAY_WEIGHT)
+        + skill::weighted_sum(stamina_left.clone(), DECAY_WEIGHT))
+        * STAMINA_SKILL_MULTIPLIER;
+
+    // maps that are all stamina and no colour are mostly rolled
+    let stamina_penalty = if colour_rating <= 0.0 {
+        0.79 - 0.25
+    } else {
+        0.79 - (stamina_rating / colour_rating - 12.0).atan() / std::f64::consts::PI / 2.0
+    };
+    stamina_rating *= stamina_penalty;
+
+    // the skills peaking in the same sections is harder than them peaking apart
+    let combined_peaks: Vec<f64> = (0..colour.len())
+        .map(|i| {
+            norm(
+                2.0,
+                &[
+                    colour[i] * COLOUR_SKILL_MULTIPLIER,
+                    rhythm[i] * RHYTHM_SKILL_MULTIPLIER,
+                    (stamina_right[i] + stamina_left[i])
+                        * STAMINA_SKILL_MULTIPLIER
+                        * stamina_penalty,
+                ],
+            )
+        })
+        .filter(|peak| *peak > 0.0)
+        .collect();
+    let combined_rating = skill::weighted_sum(combined_peaks, DECAY_WEIGHT);
+    let separated_rating = norm(1.5, &[colour_rating, rhythm_rating, stamina_rating]);
+
+    let stars = 1.4 * separated_rating + 0.5 * combined_rating;
+    difficulty.stars = if stars < 0.0 {
+        stars
+    } else {
+        10.43 * (stars / 8.0 + 1.0).ln()
+    };
+
+    difficulty.colour = colour_rating;
+    difficulty.rhythm = rhythm_rating;
+    difficulty.stamina = stamina_rating;
+
+    difficulty
+}
+
+fn norm(p: f64, values: &[f64]) -> f64 {
+    values
+        .iter()
+        .map(|value| value.powf(p))
+        .sum::<f64>()
+        .powf(1.0 / p)
+}
+
+// the first two objects only set up the rhythm of the third
+fn difficulty_objects(objects: &[TaikoObject], clock_rate: f64) -> Vec<DifficultyObject> {
+    let mut difficulty_objects = vec![];
+
+    for index in 2..objects.len() {
+        let (current, last, last_last) =
+            (&objects[index], &objects[index - 1], &objects[index - 2]);
+
+        let delta_time = (current.start_time - last.start_time) as f64 / clock_rate;
+        let previous_length = (last.start_time - last_last.start_time) as f64 / clock_rate;
+        let ratio = delta_time / previous_length;
+
+        let rhythm = (0..COMMON_RHYTHMS.len())
+            .min_by(|a, b| {
+                let distance = |i: &usize| (COMMON_RHYTHMS[*i].0 - ratio).abs();
+                distance(a)
+                    .partial_cmp(&distance(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0);
+
+        difficulty_objects.push(DifficultyObject {
+            index,
+            kind: current.kind,
+            last_kind: last.kind,
+            start_time: current.start_time as f64 / clock_rate,
+            delta_time,
+            rhythm,
+            stamina_cheese: false,
+        });
+    }
+
+    difficulty_objects
+}
+
+// rolls and tl-taps let one hand do the work of two, stamina doesn't count them fully
+fn mark_cheese(objects: &mut [DifficultyObject]) {
+    let mark = |objects: &mut [DifficultyObject], start: usize, end: usize| {
+        for object in &mut objects[start..=end] {
+            object.stamina_cheese = true;
+        }
+    };
+
+    // kddk kddk ... and kdk kdk ...
+    for pattern_length in 3..=4 {
+        let mut history: VecDeque<usize> = VecDeque::new();
+        let mut index_before_last_repeat: isize = -1;
+        let mut last_mark_end = 0;
+
+        for i in 0..objects.len() {
+            push_limited(&mut history, i, 2 * pattern_length);
+            if history.len() < 2 * pattern_length {
+                continue;
+            }
+
+            let repeats = (0..pattern_length)
+                .all(|j| rim(&objects[history[j]]) == rim(&objects[history[j + pattern_length]]));
+
+            if !repeats {
+                index_before_last_repeat = i as isize - history.len() as isize + 1;
+                continue;
+            }
+
+            let repeated_length = i as isize - index_before_last_repeat;
+            if repeated_length < ROLL_MIN_REPETITIONS as isize {
+                continue;
+            }
+
+            let start = last_mark_end.max((i as isize - repeated_length + 1).max(0) as usize);
+            mark(objects, start, i);
+            last_mark_end = i;
+        }
+    }
+
+    // every other note the same colour
+    for (parity, rim_kind) in [(0, true), (1, true), (0, false), (1, false)].iter() {
+        let mut tl_length = -2;
+        let mut last_mark_end = 0;
+
+        for i in (*parity..objects.len()).step_by(2) {
+            if rim(&objects[i]) == Some(*rim_kind) {
+                tl_length += 2;
+            } else {
+                tl_length = -2;
+            }
+
+            if tl_length < TL_TAP_MIN_REPETITIONS {
+                continue;
+            }
+
+            let start = last_mark_end.max(i.saturating_sub(tl_length as usize));
+            mark(objects, start, i);
+            last_mark_end = i;
+        }
+    }
+}
+
+// None for drum rolls and swells
+fn rim(object: &DifficultyObject) -> Option<bool> {
+    match object.kind {
+        TaikoObjectKind::Don => Some(false),
+        TaikoObjectKind::Kat => Some(true),
+        _ => None,
+    }
+}
+
+fn colour_strains(objects: &[DifficultyObject]) -> Vec<f64> {
+    let mut mono_history: VecDeque<i32> = VecDeque::new();
+    let mut previous_rim: Option<bool> = None;
+    let mut mono_length = 0;
+
+    objects
+        .iter()
+        .map(|current| {
+            // changing to or from a drum roll or swell isn't a colour change, and
+            // neither is anything a second apart
+            if !(is_hit(current.last_kind) && current.is_hit() && current.delta_time < 1000.0) {
+                mono_history.clear();
+                mono_length = if current.is_hit() { 1 } else { 0 };
+                previous_rim = rim(current);
+                return 0.0;
+            }
+
+            let mut strain = 0.0;
+            if previous_rim.is_some() && rim(current) != previous_rim {
+                // it takes two streaks to tell whether a change is hard, and an even
+                // number of notes over the last two changes back on the same hand
+                strain = 1.0;
+                if mono_history.len() < 2
+                    || (mono_history.back().copied().unwrap_or(0) + mono_length) % 2 == 0
+                {
+                    strain = 0.0;
+                }
+
+                push_limited(&mut mono_history, mono_length, MONO_HISTORY_LENGTH);
+                strain *= mono_repetition_penalty(&mono_history);
+                mono_length = 1;
+            } else {
+                mono_length += 1;
+            }
+
+            previous_rim = rim(current);
+            strain
+        })
+        .collect()
+}
+
+// the last two streak lengths showing up again earlier on
+fn mono_repetition_penalty(history: &VecDeque<i32>) -> f64 {
+    const COMPARED: usize = 2;
+
+    if history.len() <= COMPARED {
+        return 1.0;
+    }
+
+    for start in (0..history.len() - COMPARED).rev() {
+        let same =
+            (0..COMPARED).all(|i| history[start + i] == history[history.len() - COMPARED + i]);
+        if !same {
+            continue;
+        }
+
+        let notes_since: i32 = history.iter().skip(start).sum();
+        return repetition_penalty(notes_since as f64);
+    }
+
+    1.0
+}
+
+fn repetition_penalty(notes_since: f64) -> f64 {
+    (0.032 * notes_since).min(1.0)
+}
+
+fn rhythm_strains(objects: &[DifficultyObject]) -> Vec<f64> {
+    let mut history: VecDeque<&DifficultyObject> = VecDeque::new();
+    let mut strain = 0.0;
+    let mut notes_since_change = 0;
+
+    objects
+        .iter()
+        .map(|current| {
+            // drum rolls and swells have no rhythm to read
+            if !current.is_hit() {
+                strain = 0.0;
+                notes_since_change = 0;
+                return 0.0;
+            }
+
+            strain *= RHYTHM_STRAIN_DECAY;
+            notes_since_change += 1;
+
+            let difficulty = COMMON_RHYTHMS[current.rhythm].1;
+            if difficulty == 0.0 {
+                return 0.0;
+            }
+
+            push_limited(&mut history, current, RHYTHM_HISTORY_LENGTH);
+
+            let mut object_strain = difficulty * rhythm_repetition_penalty(&history, current.index);
+
+            // short and long patterns are both easier
+            let pattern_length = notes_since_change as f64;
+            object_strain *= (0.15 * pattern_length)
+                .min(1.0)
+                .min((2.5 - 0.15 * pattern_length).clamp(0.0, 1.0));
+
+            // slow changes are easy, and very slow ones start over
+            let speed_penalty = if current.delta_time < 80.0 {
+                1.0
+            } else if current.delta_time < 210.0 {
+                (1.4 - 0.005 * current.delta_time).max(0.0)
+            } else {
+                strain = 0.0;
+                0.0
+            };
+            object_strain *= speed_penalty;
+
+            notes_since_change = 0;
+            strain += object_strain;
+            strain * RHYTHM_MULTIPLIER
+        })
+        .collect()
+}
+
+fn rhythm_repetition_penalty(history: &VecDeque<&DifficultyObject>, index: usize) -> f64 {
+    let mut penalty = 1.0;
+
+    for compared in 2..=RHYTHM_HISTORY_LENGTH / 2 {
+        if history.len() <= compared {
+            continue;
+        }
+
+        for start in (0..history.len() - compared).rev() {
+            let same = (0..compared)
+                .all(|i| history[start + i].rhythm == history[history.len() - compared + i].rhythm);
+
+            if same {
+                penalty *= repetition_penalty((index - history[start].index) as f64);
+                break;
+            }
+        }
+    }
+
+    penalty
+}
+
+// every other note is hit by the same hand
+fn stamina_strains(objects: &[DifficultyObject], hand: usize) -> Vec<f64> {
+    let mut note_pair_durations: VecDeque<f64> = VecDeque::new();
+    let mut offhand_duration = f64::MAX;
+
+    objects
+        .iter()
+        .map(|current| {
+            if !current.is_hit() {
+                return 0.0;
+            }
+
+            if current.index % 2 != hand {
+                offhand_duration = current.delta_time;
+                return 0.0;
+            }
+
+            let note_pair_duration = current.delta_time + offhand_duration;
+            push_limited(
+                &mut note_pair_durations,
+                note_pair_duration,
+                NOTE_PAIR_HISTORY_LENGTH,
+            );
+
+            let shortest = note_pair_durations.iter().cloned().fold(f64::MAX, f64::min);
+            let mut strain = 1.0;
+            if shortest < 200.0 {
+                strain += (200.0 - shortest).powi(2) / 100000.0;
+            }
+
+            if current.stamina_cheese {
+                strain *= if note_pair_duration > 125.0 {
+                    1.0
+                } else if note_pair_duration < 100.0 {
+                    0.6
+                } else {
+                    0.6 + (note_pair_duration - 100.0) * 0.016
+                };
+            }
+
+            strain
+        })
+        .collect()
+}
@@ -0,0 +1,357 @@
+use crate::{
+    game::{random::LegacyRandom, Gamemode, ModSet, Mods},
+    parser::beatmap::{
+        objects::{HitObject, HitType, SliderObjectType},
+        BeatmapFile,
+    },
+};
+
+pub const PLAYFIELD_WIDTH: f32 = 512.0;
+
+const RNG_SEED: i32 = 1337;
+
+const BASE_CATCHER_SIZE: f64 = 106.75;
+// only this much of the catcher's plate actually catches
+const ALLOWED_CATCH_RANGE: f64 = 0.8;
+
+// tiny droplets are spread at least this far apart, in ms
+const TINY_DROPLET_THRESHOLD: i32 = 80;
+const TINY_DROPLET_MAX_SPACING: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CatchObjectKind {
+    Fruit,
+    Droplet,
+    TinyDroplet,
+    Banana,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CatchObject {
+    pub kind: CatchObjectKind,
+    pub start_time: f32,
+    pub x: f32,
+    // walking to the next fruit or droplet isn't fast enough, the catcher dashes there
+    pub hyper_dash: bool,
+    // how much further the catcher could still have walked, zero for hyperdashes
+    pub distance_to_hyper_dash: f32,
+}
+
+impl CatchObject {
+    fn new(kind: CatchObjectKind, start_time: f32, x: f32) -> CatchObject {
+        CatchObject {
+            kind,
+            start_time,
+            x: x.clamp(0.0, PLAYFIELD_WIDTH),
+            hyper_dash: false,
+            distance_to_hyper_dash: 0.0,
+        }
+    }
+
+    // fruits and droplets, missing anything else doesn't break combo
+    pub fn gives_combo(&self) -> bool {
+        self.kind == CatchObjectKind::Fruit || self.kind == CatchObjectKind::Droplet
+    }
+}
+
+// width of the part of the catcher that catches, smaller with higher circle size
+pub fn catcher_width(circle_size: f64) -> f64 {
+    (1.0 - 0.7 * (circle_size - 5.0) / 5.0) * BASE_CATCHER_SIZE * ALLOWED_CATCH_RANGE
+}
+
+// circles are fruits, sliders are juice streams of fruits, droplets and tiny droplets
+// and spinners are banana showers. hard rock moves fruits around and the circle size
+// the mods leave decides which jumps are hyperdashes
+pub fn convert(beatmap: &BeatmapFile, mods: ModSet) -> Vec<CatchObject> {
+    let hard_rock = mods.contains(Mods::HardRock);
+    let mut rng = LegacyRandom::new(RNG_SEED);
+    let mut objects = vec![];
+
+    // where the last fruit ended up, for hard rock's offsets
+    let mut last_position: Option<f32> = None;
+    let mut last_start_time = 0.0;
+
+    for object in &beatmap.hit_objects {
+        if object.hit_type & HitType::Slider as i32 != 0 {
+            let stream = juice_stream(object);
+
+            // osu!stable takes the last control point and the start time instead of
+            // where and when the stream really ends
+            last_position = Some(
+                object
+                    .slider_data
+                    .as_ref()
+                    .and_then(|slider| slider.base_points.last())
+                    .map_or(object.x, |point| point.x),
+            );
+            last_start_time = object.start_time;
+
+            for mut nested in stream {
+                match nested.kind {
+                    CatchObjectKind::TinyDroplet => {
+                        let offset = rng.next_range(-20, 20) as f32;
+                        nested.x = (nested.x + offset).clamp(0.0, PLAYFIELD_WIDTH);
+                    }
+                    // osu!stable picked a random droplet rotation
+                    CatchObjectKind::Droplet => {
+                        rng.next();
+                    }
+                    _ => (),
+                }
+
+                objects.push(nested);
+            }
+        } else if object.hit_type & HitType::Spinner as i32 != 0 {
+            for time in banana_times(object.start_time, object.end_time) {
+                let x = rng.next_range(0, PLAYFIELD_WIDTH as i32) as f32;
+                objects.push(CatchObject::new(CatchObjectKind::Banana, time, x));
+
+                // osu!stable picked a banana type, rotation and colour
+                rng.next();
+                rng.next();
+                rng.next();
+            }
+        } else {
+            let mut x = object.x;
+            if hard_rock {
+                x = hard_rock_offset(
+                    x,
+                    object.start_time,
+                    &mut last_position,
+                    &mut last_start_time,
+                    &mut rng,
+                );
+            }
+
+            objects.push(CatchObject::new(
+                CatchObjectKind::Fruit,
+                object.start_time,
+                x,
+            ));
+        }
+    }
+
+    let circle_size = beatmap.attributes(Gamemode::Catch, mods).circle_size;
+    apply_hyper_dashes(&mut objects, catcher_width(circle_size));
+
+    objects
+}
+
+// fruits on the head, repeats and tail, droplets on ticks and tiny droplets filling
+// the gaps longer than 80ms between them
+fn juice_stream(object: &HitObject) -> Vec<CatchObject> {
+    let (slider, slider_objects) = match (&object.slider_data, &object.slider_objects) {
+        (Some(slider), Some(slider_objects)) => (slider, slider_objects),
+        _ => {
+            return vec![CatchObject::new(
+                CatchObjectKind::Fruit,
+                object.start_time,
+                object.x,
+            )]
+        }
+    };
+
+    let span_count = slider.slides.max(1);
+    let span_duration = (object.end_time - object.start_time) / span_count as f32;
+    let x_at =
+        |progress: f32| object.x + slider.slider_body.position_at(progress * slider.length).x;
+
+    // how far along the path the stream is at a given time
+    let progress_at = |time: f32| {
+        if span_duration <= 0.0 {
+            return 0.0;
+        }
+
+        let spans = ((time - object.start_time) / span_duration).max(0.0);
+        let span = spans.floor().min((span_count - 1) as f32);
+        let progress = (spans - span).clamp(0.0, 1.0);
+
+        if span as i32 % 2 == 1 {
+            1.0 - progress
+        } else {
+            progress
+        }
+    };
+
+    // the legacy last tick doesn't make anything but still splits tiny droplets
+    let mut events: Vec<(f32, Option<CatchObjectKind>)> = slider_objects
+        .iter()
+        .map(|nested| {
+            let kind = match nested.slider_object_type {
+                SliderObjectType::SliderHead | SliderObjectType::SliderRepeat => {
+                    Some(CatchObjectKind::Fruit)
+                }
+                SliderObjectType::SliderTick => Some(CatchObjectKind::Droplet),
+                SliderObjectType::SliderEnd => None,
+            };
+
+            (nested.start_time, kind)
+        })
+        .collect();
+    events.push((object.end_time, Some(CatchObjectKind::Fruit)));
+
+    let mut objects = vec![];
+    let mut last_event: Option<f32> = None;
+
+    for (time, kind) in events {
+        if let Some(last_time) = last_event {
+            let since_last = time as i32 - last_time as i32;
+
+            if since_last > TINY_DROPLET_THRESHOLD {
+                let mut spacing = since_last as f64;
+                while spacing > TINY_DROPLET_MAX_SPACING {
+                    spacing /= 2.0;
+                }
+
+                let (last_progress, progress) = (progress_at(last_time), progress_at(time));
+                let mut t = spacing;
+                while t < since_last as f64 {
+                    let fraction = (t / since_last as f64) as f32;
+                    objects.push(CatchObject::new(
+                        CatchObjectKind::TinyDroplet,
+                        last_time + t as f32,
+                        x_at(last_progress + fraction * (progress - last_progress)),
+                    ));
+
+                    t += spacing;
+                }
+            }
+        }
+
+        last_event = Some(time);
+        if let Some(kind) = kind {
+            objects.push(CatchObject::new(kind, time, x_at(progress_at(time))));
+        }
+    }
+
+    objects
+}
+
+// bananas every 100ms or less, halving the spinner's length until they fit
+fn banana_times(start_time: f32, end_time: f32) -> Vec<f32> {
+    let mut spacing = (end_time - start_time) as f64;
+    while spacing > 100.0 {
+        spacing /= 2.0;
+    }
+
+    if spacing <= 0.0 {
+        return vec![];
+    }
+
+    let mut times = vec![];
+    let mut time = start_time as f64;
+    while time <= end_time as f64 {
+        times.push(time as f32);
+        time += spacing;
+    }
+
+    times
+}
+
+// hard rock stretches short jumps and nudges fruits that sit on top of each other
+fn hard_rock_offset(
+    x: f32,
+    start_time: f32,
+    last_position: &mut Option<f32>,
+    last_start_time: &mut f32,
+    rng: &mut LegacyRandom,
+) -> f32 {
+    let mut position = x;
+
+    let last = match *last_position {
+        Some(last) => last,
+        None => {
+            *last_position = Some(position);
+            *last_start_time = start_time;
+            return position;
+        }
+    };
+
+    let position_diff = position - last;
+    let time_diff = (start_time - *last_start_time) as i32;
+
+    if time_diff > 1000 {
+        *last_position = Some(position);
+        *last_start_time = start_time;
+        return position;
+    }
+
+    // stacked fruits move, but they don't count as where the last fruit was
+    if position_diff == 0.0 {
+        let right = rng.next_bool();
+        let offset =
+            (rng.next_double_range(0.0, (time_diff as f64 / 4.0).max(0.0)) as i32).min(20) as f32;
+
+        if right {
+            if position + offset <= PLAYFIELD_WIDTH {
+                position += offset;
+            } else {
+                position -= offset;
+            }
+        } else if position - offset >= 0.0 {
+            position -= offset;
+        } else {
+            position += offset;
+        }
+
+        return position;
+    }
+
+    // osu!stable divides the time as an integer here
+    if position_diff.abs() < (time_diff / 3) as f32 {
+        if position_diff > 0.0 {
+            if position + position_diff < PLAYFIELD_WIDTH {
+                position += position_diff;
+            }
+        } else if position + position_diff > 0.0 {
+            position += position_diff;
+        }
+    }
+
+    *last_position = Some(position);
+    *last_start_time = start_time;
+    position
+}
+
+// osu!stable tests hyperdashes against the whole catcher rather than the part that
+// catches, so this does too
+fn apply_hyper_dashes(objects: &mut [CatchObject], catcher_width: f64) {
+    let half_catcher_width = catcher_width / 2.0 / ALLOWED_CATCH_RANGE;
+
+    let indices: Vec<usize> = (0..objects.len())
+        .filter(|&i| objects[i].gives_combo())
+        .collect();
+
+    let mut last_direction = 0;
+    let mut last_excess = half_catcher_width;
+
+    for pair in indices.windows(2) {
+        let (current, next) = (objects[pair[0]], objects[pair[1]]);
+
+        let direction = if next.x > current.x { 1 } else { -1 };
+        // a quarter of a frame of leeway. osu! drops the fractions of the times first,
+        // which droplets have
+        let time_to_next =
+            (next.start_time as i32 - current.start_time as i32) as f64 - 1000.0 / 60.0 / 4.0;
+        let distance_to_next = (next.x - current.x).abs() as f64
+            - if last_direction == direction {
+                last_excess
+            } else {
+                half_catcher_width
+            };
+        let distance_to_hyper_dash = (time_to_next - distance_to_next) as f32;
+
+        let current = &mut objects[pair[0]];
+        if distance_to_hyper_dash < 0.0 {
+            current.hyper_dash = true;
+            current.distance_to_hyper_dash = 0.0;
+            last_excess = half_catcher_width;
+        } else {
+            current.hyper_dash = false;
+            current.distance_to_hyper_dash = distance_to_hyper_dash;
+            last_excess = (distance_to_hyper_dash as f64).clamp(0.0, half_catcher_width);
+        }
+
+        last_direction = direction;
+    }
+}
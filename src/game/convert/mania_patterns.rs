@@ -0,0 +1,1071 @@
+use std::{cmp::Ordering, ops::Range};
+
+use crate::{
+    game::{
+        convert::beat_lengths,
+        mania::{ManiaHold, ManiaNote, ManiaObject},
+        random::LegacyRandom,
+        ModSet, Mods,
+    },
+    parser::beatmap::{
+        objects::{HitObject, HitSound, HitType, SliderData},
+        BeatmapFile,
+    },
+    util::Vector2,
+};
+
+// how many of the latest notes the density is taken over
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+// hints for how the next pattern should be laid out, these are the values osu!stable
+// uses
+#[derive(Debug, Clone, Copy)]
+enum PatternType {
+    ForceStack = 1 << 0,
+    ForceNotStack = 1 << 1,
+    KeepSingle = 1 << 2,
+    LowProbability = 1 << 3,
+    Gathered = 1 << 7,
+    Mirror = 1 << 8,
+    Reverse = 1 << 9,
+    Cycle = 1 << 10,
+    Stair = 1 << 11,
+}
+
+// the objects a single standard object turned into, the next pattern is laid out
+// around the columns this one filled
+#[derive(Debug, Clone, Default)]
+struct Pattern {
+    objects: Vec<ManiaObject>,
+}
+
+impl Pattern {
+    fn has_column(&self, column: i32) -> bool {
+        self.objects.iter().any(|object| object.column() == column)
+    }
+
+    fn column_count(&self) -> i32 {
+        let mut columns: Vec<i32> = self.objects.iter().map(|object| object.column()).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns.len() as i32
+    }
+
+    fn append(&mut self, other: &Pattern) {
+        self.objects.extend_from_slice(&other.objects);
+    }
+}
+
+// osu!stable's slider timing in whole milliseconds, every span is as long as the first
+struct SliderTiming {
+    start_time: i32,
+    end_time: i32,
+    segment_duration: i32,
+    spans: i32,
+}
+
+// lays a standard map out over the key count osu!stable picks for it, or the one the
+// mods ask for. the layout is random but seeded by the map's difficulty settings, so
+// the same map always converts the same way
+pub(super) fn convert(beatmap: &BeatmapFile, mods: ModSet) -> (i32, Vec<ManiaObject>) {
+    let mut total_columns = mods.key_count().unwrap_or_else(|| key_count(beatmap));
+    if mods.contains(Mods::ManiaCoOp) {
+        total_columns *= 2;
+    }
+
+    let difficulty = &beatmap.difficulty;
+    let seed = (difficulty.hp_drain + difficulty.circle_size).round_ties_even() as i32 * 20
+        + (difficulty.overall_difficulty as f64 * 41.2) as i32
+        + difficulty.approach_rate.round_ties_even() as i32;
+
+    let mut random = LegacyRandom::new(seed);
+    let conversion_difficulty = conversion_difficulty(beatmap);
+
+    let mut objects = vec![];
+    let mut last_pattern = Pattern::default();
+    let mut last_time = 0.0;
+    let mut last_position = Vector2::new(0.0, 0.0);
+    let mut note_times: Vec<f64> = vec![];
+    let mut density = i32::MAX as f64;
+
+    let mut compute_density = |time: f64| {
+        if note_times.len() == MAX_NOTES_FOR_DENSITY {
+            note_times.remove(0);
+        }
+
+        note_times.push(time);
+        if note_times.len() >= 2 {
+            density = (note_times[note_times.len() - 1] - note_times[0]) / note_times.len() as f64;
+        }
+
+        density
+    };
+
+    for object in &beatmap.hit_objects {
+        let generator = Generator {
+            beatmap,
+            object,
+            random: &mut random,
+            previous: &last_pattern,
+            total_columns,
+            random_start: if total_columns == 8 { 1 } else { 0 },
+            conversion_difficulty,
+            convert_type: 0,
+        };
+
+        let patterns = match &object.slider_data {
+            Some(slider_data) if object.hit_type & HitType::Slider as i32 != 0 => {
+                let timing = slider_timing(beatmap, object, slider_data);
+                for i in 0..=timing.spans {
+                    let time = object.start_time as f64 + (timing.segment_duration * i) as f64;
+                    last_time = time;
+                    last_position = object.position;
+                    compute_density(time);
+                }
+
+                generator.slider(slider_data, &timing)
+            }
+            _ if object.hit_type & HitType::Spinner as i32 != 0 => {
+                let pattern = generator.spinner();
+                last_time = object.end_time as f64;
+                last_position = Vector2::new(256.0, 192.0);
+                compute_density(object.end_time as f64);
+
+                // spinners don't change what the next object is laid out around
+                objects.extend(pattern.objects);
+                continue;
+            }
+            _ => {
+                let density = compute_density(object.start_time as f64);
+                let pattern = generator.circle(last_time, last_position, density);
+                last_time = object.start_time as f64;
+                last_position = object.position;
+
+                vec![pattern]
+            }
+        };
+
+        for pattern in patterns {
+            objects.extend_from_slice(&pattern.objects);
+            last_pattern = pattern;
+        }
+    }
+
+    objects.sort_by(|a, b| {
+        a.start_time()
+            .partial_cmp(&b.start_time())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    (total_columns, objects)
+}
+
+// maps with few sliders and spinners get 7 keys, the rest go by their overall
+// difficulty. osu!stable rounds like .NET does, halves go to the even number
+fn key_count(beatmap: &BeatmapFile) -> i32 {
+    let circle_size = beatmap.difficulty.circle_size.round_ties_even();
+    let overall_difficulty = beatmap.difficulty.overall_difficulty.round_ties_even();
+
+    let with_duration = beatmap
+        .hit_objects
+        .iter()
+        .filter(|o| o.hit_type & (HitType::Slider as i32 | HitType::Spinner as i32) != 0)
+        .count();
+    let percent_with_duration = with_duration as f32 / beatmap.hit_objects.len().max(1) as f32;
+
+    if percent_with_duration < 0.2 {
+        7
+    } else if percent_with_duration < 0.3 || circle_size >= 5.0 {
+        if overall_difficulty > 5.0 {
+            7
+        } else {
+            6
+        }
+    } else if percent_with_duration > 0.6 {
+        if overall_difficulty > 4.0 {
+            5
+        } else {
+            4
+        }
+    } else {
+        (overall_difficulty as i32 + 1).clamp(4, 7)
+    }
+}
+
+// 0-12, how busy the map is for its drain time. decides how likely chords are
+fn conversion_difficulty(beatmap: &BeatmapFile) -> f64 {
+    let objects = &beatmap.hit_objects;
+    let (first, last) = match (objects.first(), objects.last()) {
+        (Some(first), Some(last)) => (first.start_time as f64, last.start_time as f64),
+        _ => (0.0, 0.0),
+    };
+
+    let break_time: f64 = beatmap.breaks().iter().map(|b| b.duration() as f64).sum();
+    let drain_time = match ((last - first - break_time) / 1000.0) as i32 {
+        0 => 10000,
+        drain_time => drain_time,
+    };
+
+    let difficulty = &beatmap.difficulty;
+    let drain_and_approach =
+        (difficulty.hp_drain + difficulty.approach_rate.clamp(4.0, 7.0)) as f64 / 1.5;
+    let value =
+        (drain_and_approach + objects.len() as f64 / drain_time as f64 * 9.0) / 38.0 * 5.0 / 1.15;
+
+    value.min(12.0)
+}
+
+fn slider_timing(
+    beatmap: &BeatmapFile,
+    object: &HitObject,
+    slider_data: &SliderData,
+) -> SliderTiming {
+    let spans = slider_data.slides.max(1);
+    let (_, beat_length) = beat_lengths(beatmap, object.start_time);
+
+    let start_time = (object.start_time as f64).round_ties_even() as i32;
+    let end_time = (start_time as f64
+        + slider_data.length as f64 * beat_length * spans as f64 * 0.01
+            / beatmap.difficulty.slider_multiplier as f64)
+        .floor() as i32;
+
+    SliderTiming {
+        start_time,
+        end_time,
+        segment_duration: (end_time - start_time) / spans,
+        spans,
+    }
+}
+
+fn has_sound(hit_sound: i32, sound: HitSound) -> bool {
+    hit_sound & sound as i32 != 0
+}
+
+fn kiai(beatmap: &BeatmapFile, time: f32) -> bool {
+    !beatmap.timing_points.is_empty() && beatmap.get_timing_point(time).effects & 1 != 0
+}
+
+fn note(column: i32, start_time: f32) -> ManiaObject {
+    ManiaObject::Note(ManiaNote { column, start_time })
+}
+
+fn hold(column: i32, start_time: f32, end_time: f32) -> ManiaObject {
+    ManiaObject::Hold(ManiaHold {
+        column,
+        start_time,
+        end_time,
+    })
+}
+
+// the state every pattern of one standard object is generated with, the random
+// generator is shared by the whole map
+struct Generator<'a> {
+    beatmap: &'a BeatmapFile,
+    object: &'a HitObject,
+    random: &'a mut LegacyRandom,
+    previous: &'a Pattern,
+    total_columns: i32,
+    // 8 keys keep the first column for special notes
+    random_start: i32,
+    conversion_difficulty: f64,
+    convert_type: i32,
+}
+
+impl Generator<'_> {
+    fn has(&self, pattern_type: PatternType) -> bool {
+        self.convert_type & pattern_type as i32 != 0
+    }
+
+    fn columns(&self) -> Range<i32> {
+        self.random_start..self.total_columns
+    }
+
+    fn column(&self, x: f32, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            return ((x / (512.0 / 7.0)).floor() as i32).clamp(0, 6) + 1;
+        }
+
+        let width = 512.0 / self.total_columns as f32;
+        ((x / width).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    fn random_column(&mut self, columns: Range<i32>) -> i32 {
+        self.random.next_range(columns.start, columns.end)
+    }
+
+    // 1-6, each probability is for at least that many notes
+    fn random_note_count(&mut self, p2: f64, p3: f64, p4: f64, p5: f64, p6: f64) -> i32 {
+        let value = self.random.next_double();
+
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    // `initial` if it's free, otherwise the next free column either at random or to the
+    // right for gathered patterns. a column is free if it isn't `excluded` and none of
+    // `patterns` have an object in it
+    fn find_available_column(
+        &mut self,
+        initial: i32,
+        columns: Range<i32>,
+        gathered: bool,
+        excluded: Option<i32>,
+        patterns: &[&Pattern],
+    ) -> i32 {
+        let is_valid = |column: i32| {
+            excluded != Some(column) && !patterns.iter().any(|p| p.has_column(column))
+        };
+
+        // osu!stable gives up here, the initial column is as good as any
+        if is_valid(initial) || !columns.clone().any(is_valid) {
+            return initial;
+        }
+
+        let mut column = initial;
+        loop {
+            column = if gathered {
+                if column + 1 == self.total_columns {
+                    self.random_start
+                } else {
+                    column + 1
+                }
+            } else {
+                self.random_column(columns.clone())
+            };
+
+            if is_valid(column) {
+                return column;
+            }
+        }
+    }
+
+    // circles become single notes or chords depending on how close they are to the
+    // last object in time and space
+    fn circle(mut self, previous_time: f64, previous_position: Vector2, density: f64) -> Pattern {
+        let object = self.object;
+        let (beat_length, _) = beat_lengths(self.beatmap, object.start_time);
+        let position_separation = object.position.distance(previous_position);
+        let time_separation = object.start_time as f64 - previous_time;
+
+        let force_not_stack = PatternType::ForceNotStack as i32;
+        let keep_single = PatternType::KeepSingle as i32;
+        let low_probability = PatternType::LowProbability as i32;
+
+        self.convert_type |= if time_separation <= 80.0 {
+            force_not_stack | keep_single
+        } else if time_separation <= 95.0 {
+            // stairs only ever go up, osu!stable never flips their direction
+            force_not_stack | keep_single | PatternType::Stair as i32
+        } else if time_separation <= 105.0 {
+            force_not_stack | low_probability
+        } else if time_separation <= 125.0 {
+            force_not_stack
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            PatternType::Cycle as i32 | keep_single
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            PatternType::ForceStack as i32 | low_probability
+        } else if position_separation < 20.0 && density >= beat_length / 2.5 {
+            PatternType::Reverse as i32 | low_probability
+        } else if density < beat_length / 2.5 || kiai(self.beatmap, object.start_time) {
+            0
+        } else {
+            low_probability
+        };
+
+        if !self.has(PatternType::KeepSingle) {
+            if has_sound(object.hit_sound, HitSound::Finish) && self.total_columns != 8 {
+                self.convert_type |= PatternType::Mirror as i32;
+            } else if has_sound(object.hit_sound, HitSound::Clap) {
+                self.convert_type |= PatternType::Gathered as i32;
+            }
+        }
+
+        self.circle_pattern()
+    }
+
+    fn circle_pattern(&mut self) -> Pattern {
+        if self.total_columns == 1 {
+            return self.circle_notes(&[0]);
+        }
+
+        let previous = self.previous;
+        let previous_count = previous.objects.len();
+        let last_column = previous.objects.first().map_or(0, |o| o.column());
+
+        if self.has(PatternType::Reverse) && previous_count > 0 {
+            let columns: Vec<i32> = self
+                .columns()
+                .filter(|&column| previous.has_column(column))
+                .map(|column| self.random_start + self.total_columns - column - 1)
+                .collect();
+            return self.circle_notes(&columns);
+        }
+
+        // the special key and the centre column don't cycle
+        if self.has(PatternType::Cycle)
+            && previous_count == 1
+            && (self.total_columns != 8 || last_column != 0)
+            && (self.total_columns % 2 == 0 || last_column != self.total_columns / 2)
+        {
+            let column = self.random_start + self.total_columns - last_column - 1;
+            return self.circle_notes(&[column]);
+        }
+
+        if self.has(PatternType::ForceStack) && previous_count > 0 {
+            let columns: Vec<i32> = self
+                .columns()
+                .filter(|&column| previous.has_column(column))
+                .collect();
+            return self.circle_notes(&columns);
+        }
+
+        if previous_count == 1 && self.has(PatternType::Stair) {
+            let mut column = last_column + 1;
+            if column == self.total_columns {
+                column = self.random_start;
+            }
+
+            return self.circle_notes(&[column]);
+        }
+
+        if self.has(PatternType::KeepSingle) {
+            return self.random_notes(1);
+        }
+
+        let low_probability = self.has(PatternType::LowProbability);
+
+        if self.has(PatternType::Mirror) {
+            return if self.conversion_difficulty > 6.5 {
+                self.random_pattern_mirrored(0.12, 0.38, 0.12)
+            } else if self.conversion_difficulty > 4.0 {
+                self.random_pattern_mirrored(0.12, 0.17, 0.0)
+            } else {
+                self.random_pattern_mirrored(0.12, 0.0, 0.0)
+            };
+        }
+
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.random_pattern(0.78, 0.42, 0.0, 0.0)
+            } else {
+                self.random_pattern(1.0, 0.62, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.random_pattern(0.35, 0.08, 0.0, 0.0)
+            } else {
+                self.random_pattern(0.52, 0.15, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.0 {
+            if low_probability {
+                self.random_pattern(0.18, 0.0, 0.0, 0.0)
+            } else {
+                self.random_pattern(0.45, 0.0, 0.0, 0.0)
+            }
+        } else {
+            self.random_pattern(0.0, 0.0, 0.0, 0.0)
+        }
+    }
+
+    fn circle_notes(&self, columns: &[i32]) -> Pattern {
+        Pattern {
+            objects: columns
+                .iter()
+                .map(|&column| note(column, self.object.start_time))
+                .collect(),
+        }
+    }
+
+    // notes in different columns, starting under the circle
+    fn random_notes(&mut self, mut note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+        let allow_stacking = !self.has(PatternType::ForceNotStack);
+        let gathered = self.has(PatternType::Gathered);
+
+        if !allow_stacking {
+            note_count =
+                note_count.min(self.total_columns - self.random_start - previous.column_count());
+        }
+
+        let mut column = self.column(self.object.x, true);
+        for _ in 0..note_count {
+            column = if allow_stacking {
+                self.find_available_column(column, self.columns(), gathered, None, &[&pattern])
+            } else {
+                self.find_available_column(
+                    column,
+                    self.columns(),
+                    gathered,
+                    None,
+                    &[&pattern, previous],
+                )
+            };
+
+            pattern.objects.push(note(column, self.object.start_time));
+        }
+
+        pattern
+    }
+
+    // a clap together with a finish also fills the special key
+    fn has_special_column(&self) -> bool {
+        has_sound(self.object.hit_sound, HitSound::Clap)
+            && has_sound(self.object.hit_sound, HitSound::Finish)
+    }
+
+    fn random_pattern(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> Pattern {
+        match self.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.23);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+                p5 = 0.0;
+            }
+            5 => {
+                p3 = p3.min(0.15);
+                p4 = p4.min(0.03);
+                p5 = 0.0;
+            }
+            _ => {}
+        }
+
+        if has_sound(self.object.hit_sound, HitSound::Clap) {
+            p2 = 1.0;
+        }
+
+        let note_count = self.random_note_count(p2, p3, p4, p5, 0.0);
+        let mut pattern = self.random_notes(note_count);
+
+        if self.random_start > 0 && self.has_special_column() {
+            pattern.objects.push(note(0, self.object.start_time));
+        }
+
+        pattern
+    }
+
+    // notes mirrored around the centre, with one in the centre itself sometimes
+    fn random_pattern_mirrored(&mut self, mut centre: f64, mut p2: f64, mut p3: f64) -> Pattern {
+        if self.has(PatternType::ForceNotStack) {
+            return self.random_pattern(0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        // osu!stable works with inverse probabilities, so doubling them happens on 1 - p
+        match self.total_columns {
+            2 => {
+                centre = 0.0;
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            3 => {
+                centre = centre.min(0.03);
+                p2 = 0.0;
+                p3 = 0.0;
+            }
+            4 => {
+                centre = 0.0;
+                p2 = 1.0 - f64::max((1.0 - p2) * 2.0, 0.8);
+                p3 = 0.0;
+            }
+            5 => {
+                centre = centre.min(0.03);
+                p3 = 0.0;
+            }
+            6 => {
+                centre = 0.0;
+                p2 = 1.0 - f64::max((1.0 - p2) * 2.0, 0.5);
+                p3 = 1.0 - f64::max((1.0 - p3) * 2.0, 0.85);
+            }
+            _ => {}
+        }
+
+        let p2 = p2.clamp(0.0, 1.0);
+        let p3 = p3.clamp(0.0, 1.0);
+
+        let centre_value = self.random.next_double();
+        let note_count = self.random_note_count(p2, p3, 0.0, 0.0, 0.0);
+        let add_to_centre =
+            self.total_columns % 2 != 0 && note_count != 3 && centre_value > 1.0 - centre;
+
+        let mut pattern = Pattern::default();
+        let column_limit = (self.total_columns - self.total_columns % 2) / 2;
+        let mut column = self.random_column(self.random_start..column_limit);
+
+        for _ in 0..note_count {
+            column = self.find_available_column(
+                column,
+                self.random_start..column_limit,
+                false,
+                None,
+                &[&pattern],
+            );
+
+            let mirrored = self.random_start + self.total_columns - column - 1;
+            pattern.objects.push(note(column, self.object.start_time));
+            pattern.objects.push(note(mirrored, self.object.start_time));
+        }
+
+        if add_to_centre {
+            pattern
+                .objects
+                .push(note(self.total_columns / 2, self.object.start_time));
+        }
+
+        if self.random_start > 0 && self.has_special_column() {
+            pattern.objects.push(note(0, self.object.start_time));
+        }
+
+        pattern
+    }
+
+    // spinners become a hold, or a note if they're shorter than 100ms
+    fn spinner(mut self) -> Pattern {
+        let object = self.object;
+        let end_time = object.end_time as i32;
+        let is_hold = end_time as f64 - object.start_time as f64 >= 100.0;
+
+        if self.previous.column_count() != self.total_columns {
+            self.convert_type |= PatternType::ForceNotStack as i32;
+        }
+
+        let column = if self.total_columns == 8
+            && has_sound(object.hit_sound, HitSound::Finish)
+            && (end_time as f64 - object.start_time as f64) < 1000.0
+        {
+            0
+        } else {
+            let columns = if self.total_columns == 8 {
+                self.columns()
+            } else {
+                0..self.total_columns
+            };
+
+            let column = self.random_column(columns.clone());
+            if self.has(PatternType::ForceNotStack) {
+                let previous = self.previous;
+                self.find_available_column(column, columns, false, None, &[previous])
+            } else {
+                column
+            }
+        };
+
+        let object = if is_hold {
+            hold(column, object.start_time, end_time as f32)
+        } else {
+            note(column, object.start_time)
+        };
+
+        Pattern {
+            objects: vec![object],
+        }
+    }
+
+    // sliders become holds, or notes on every span for sliders that repeat quickly.
+    // anything ending with the slider is split off, since only that is still there
+    // for the next object to be laid out around
+    fn slider(mut self, slider_data: &SliderData, timing: &SliderTiming) -> Vec<Pattern> {
+        if !kiai(self.beatmap, self.object.start_time) {
+            self.convert_type = PatternType::LowProbability as i32;
+        }
+
+        let pattern = self.slider_pattern(slider_data, timing);
+        if pattern.objects.len() == 1 {
+            return vec![pattern];
+        }
+
+        let (end_time, intermediate): (Vec<ManiaObject>, Vec<ManiaObject>) = pattern
+            .objects
+            .into_iter()
+            .partition(|o| o.end_time().round_ties_even() as i32 == timing.end_time);
+
+        vec![
+            Pattern {
+                objects: intermediate,
+            },
+            Pattern { objects: end_time },
+        ]
+    }
+
+    fn slider_pattern(&mut self, slider_data: &SliderData, timing: &SliderTiming) -> Pattern {
+        let start_time = timing.start_time;
+        let segment_duration = timing.segment_duration;
+
+        if self.total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_slider_object(&mut pattern, 0, start_time, timing.end_time);
+            return pattern;
+        }
+
+        if timing.spans > 1 {
+            if segment_duration <= 90 {
+                return self.random_hold_notes(timing, 1);
+            }
+
+            if segment_duration <= 120 {
+                self.convert_type |= PatternType::ForceNotStack as i32;
+                return self.slider_random_notes(timing, timing.spans + 1);
+            }
+
+            if segment_duration <= 160 {
+                return self.stair(timing);
+            }
+
+            if segment_duration <= 200 && self.conversion_difficulty > 3.0 {
+                return self.random_multiple_notes(timing);
+            }
+
+            if timing.end_time - start_time >= 4000 {
+                return self.n_random_notes(slider_data, timing, 0.23, 0.0, 0.0);
+            }
+
+            if segment_duration > 400 && timing.spans < self.total_columns - 1 - self.random_start {
+                return self.tiled_hold_notes(timing);
+            }
+
+            return self.hold_and_normal_notes(slider_data, timing);
+        }
+
+        if segment_duration <= 110 {
+            if self.previous.column_count() < self.total_columns {
+                self.convert_type |= PatternType::ForceNotStack as i32;
+            } else {
+                self.convert_type &= !(PatternType::ForceNotStack as i32);
+            }
+
+            let note_count = if segment_duration < 80 { 1 } else { 2 };
+            return self.slider_random_notes(timing, note_count);
+        }
+
+        let low_probability = self.has(PatternType::LowProbability);
+
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.n_random_notes(slider_data, timing, 0.78, 0.3, 0.0)
+            } else {
+                self.n_random_notes(slider_data, timing, 0.85, 0.36, 0.03)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.n_random_notes(slider_data, timing, 0.43, 0.08, 0.0)
+            } else {
+                self.n_random_notes(slider_data, timing, 0.56, 0.18, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.5 {
+            if low_probability {
+                self.n_random_notes(slider_data, timing, 0.3, 0.0, 0.0)
+            } else {
+                self.n_random_notes(slider_data, timing, 0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            self.n_random_notes(slider_data, timing, 0.17, 0.0, 0.0)
+        } else {
+            self.n_random_notes(slider_data, timing, 0.27, 0.0, 0.0)
+        }
+    }
+
+    // a note when the times match, otherwise a hold
+    fn add_slider_object(
+        &self,
+        pattern: &mut Pattern,
+        column: i32,
+        start_time: i32,
+        end_time: i32,
+    ) {
+        let object = if start_time == end_time {
+            note(column, start_time as f32)
+        } else {
+            hold(column, start_time as f32, end_time as f32)
+        };
+
+        pattern.objects.push(object);
+    }
+
+    // the hitsound of the slider's edge at `time`
+    fn edge_sound(&self, slider_data: &SliderData, timing: &SliderTiming, time: i32) -> i32 {
+        let index = match timing.segment_duration {
+            0 => 0,
+            segment_duration => (time - timing.start_time) / segment_duration,
+        };
+
+        slider_data
+            .edge_sounds
+            .get(index.max(0) as usize)
+            .copied()
+            .unwrap_or(self.object.hit_sound)
+    }
+
+    // holds over the whole slider, avoiding the previous pattern while there's room
+    fn random_hold_notes(&mut self, timing: &SliderTiming, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let usable_columns = self.total_columns - self.random_start - previous.column_count();
+        let mut column = self.random_column(self.columns());
+
+        for _ in 0..usable_columns.min(note_count) {
+            column = self.find_available_column(
+                column,
+                self.columns(),
+                false,
+                None,
+                &[&pattern, previous],
+            );
+            self.add_slider_object(&mut pattern, column, timing.start_time, timing.end_time);
+        }
+
+        for _ in 0..note_count - usable_columns {
+            column = self.find_available_column(column, self.columns(), false, None, &[&pattern]);
+            self.add_slider_object(&mut pattern, column, timing.start_time, timing.end_time);
+        }
+
+        pattern
+    }
+
+    // a note on every span, never twice in a row in the same column
+    fn slider_random_notes(&mut self, timing: &SliderTiming, note_count: i32) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let mut column = self.column(self.object.x, true);
+        if self.has(PatternType::ForceNotStack) && previous.column_count() < self.total_columns {
+            column = self.find_available_column(column, self.columns(), false, None, &[previous]);
+        }
+
+        let mut last_column = column;
+        let mut start_time = timing.start_time;
+
+        for _ in 0..note_count {
+            self.add_slider_object(&mut pattern, column, start_time, start_time);
+            column =
+                self.find_available_column(column, self.columns(), false, Some(last_column), &[]);
+            last_column = column;
+            start_time += timing.segment_duration;
+        }
+
+        pattern
+    }
+
+    // a note on every span, walking across the columns and turning at the edges
+    fn stair(&mut self, timing: &SliderTiming) -> Pattern {
+        let mut pattern = Pattern::default();
+        let mut column = self.column(self.object.x, true);
+        let mut increasing = self.random.next_double() > 0.5;
+        let mut start_time = timing.start_time;
+
+        for _ in 0..=timing.spans {
+            self.add_slider_object(&mut pattern, column, start_time, start_time);
+            start_time += timing.segment_duration;
+
+            if increasing {
+                if column >= self.total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= self.random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+
+        pattern
+    }
+
+    // one or two notes on every span
+    fn random_multiple_notes(&mut self, timing: &SliderTiming) -> Pattern {
+        let mut pattern = Pattern::default();
+        let legacy = (4..=8).contains(&self.total_columns);
+        let interval = self
+            .random
+            .next_range(1, self.total_columns - if legacy { 1 } else { 0 });
+
+        let mut column = self.column(self.object.x, true);
+        let mut start_time = timing.start_time;
+
+        for _ in 0..=timing.spans {
+            self.add_slider_object(&mut pattern, column, start_time, start_time);
+
+            column += interval;
+            if column >= self.total_columns - self.random_start {
+                column =
+                    column - self.total_columns - self.random_start + if legacy { 1 } else { 0 };
+            }
+            column += self.random_start;
+
+            // no long runs of doubles on 2 keys
+            if self.total_columns > 2 {
+                self.add_slider_object(&mut pattern, column, start_time, start_time);
+            }
+
+            column = self.random_column(self.columns());
+            start_time += timing.segment_duration;
+        }
+
+        pattern
+    }
+
+    // a random number of holds over the whole slider, sliders starting with a clap or
+    // finish always get at least two
+    fn n_random_notes(
+        &mut self,
+        slider_data: &SliderData,
+        timing: &SliderTiming,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+    ) -> Pattern {
+        match self.total_columns {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+            }
+            4 => {
+                p2 = p2.min(0.3);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+            }
+            5 => {
+                p2 = p2.min(0.34);
+                p3 = p3.min(0.1);
+                p4 = p4.min(0.03);
+            }
+            _ => {}
+        }
+
+        let is_double = |hit_sound: i32| {
+            has_sound(hit_sound, HitSound::Clap) || has_sound(hit_sound, HitSound::Finish)
+        };
+
+        if !self.has(PatternType::LowProbability)
+            && (is_double(self.object.hit_sound)
+                || is_double(self.edge_sound(slider_data, timing, timing.start_time)))
+        {
+            p2 = 1.0;
+        }
+
+        let note_count = self.random_note_count(p2, p3, p4, 0.0, 0.0);
+        self.random_hold_notes(timing, note_count)
+    }
+
+    // holds starting one span after another, all ending together
+    fn tiled_hold_notes(&mut self, timing: &SliderTiming) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+        let column_repeat = timing.spans.min(self.total_columns);
+
+        // not always the slider's end time, the segments are rounded down
+        let end_time = timing.start_time + timing.segment_duration * timing.spans;
+        let mut start_time = timing.start_time;
+
+        let mut column = self.column(self.object.x, true);
+        if self.has(PatternType::ForceNotStack) && previous.column_count() < self.total_columns {
+            column = self.find_available_column(column, self.columns(), false, None, &[previous]);
+        }
+
+        for _ in 0..column_repeat {
+            column = self.find_available_column(column, self.columns(), false, None, &[&pattern]);
+            self.add_slider_object(&mut pattern, column, start_time, end_time);
+            start_time += timing.segment_duration;
+        }
+
+        pattern
+    }
+
+    // a hold under the slider with notes beside it on every span
+    fn hold_and_normal_notes(
+        &mut self,
+        slider_data: &SliderData,
+        timing: &SliderTiming,
+    ) -> Pattern {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let mut hold_column = self.column(self.object.x, true);
+        if self.has(PatternType::ForceNotStack) && previous.column_count() < self.total_columns {
+            hold_column =
+                self.find_available_column(hold_column, self.columns(), false, None, &[previous]);
+        }
+
+        self.add_slider_object(
+            &mut pattern,
+            hold_column,
+            timing.start_time,
+            timing.end_time,
+        );
+
+        let mut column = self.random_column(self.columns());
+        let note_count = if self.conversion_difficulty > 6.5 {
+            self.random_note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 4.0 {
+            let p2 = if self.total_columns < 6 { 0.12 } else { 0.45 };
+            self.random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 2.5 {
+            let p2 = if self.total_columns < 6 { 0.0 } else { 0.24 };
+            self.random_note_count(p2, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let note_count = note_count.min(self.total_columns - 1);
+
+        // the head only gets notes beside it if it has a hitsound of its own
+        let head_sound = self.edge_sound(slider_data, timing, timing.start_time);
+        let ignore_head = !(has_sound(head_sound, HitSound::Whistle)
+            || has_sound(head_sound, HitSound::Finish)
+            || has_sound(head_sound, HitSound::Clap));
+
+        let mut start_time = timing.start_time;
+        for _ in 0..=timing.spans {
+            let mut row = Pattern::default();
+
+            if !(ignore_head && start_time == timing.start_time) {
+                for _ in 0..note_count {
+                    column = self.find_available_column(
+                        column,
+                        self.columns(),
+                        false,
+                        Some(hold_column),
+                        &[&row],
+                    );
+                    self.add_slider_object(&mut row, column, start_time, start_time);
+                }
+            }
+
+            pattern.append(&row);
+            start_time += timing.segment_duration;
+        }
+
+        pattern
+    }
+}
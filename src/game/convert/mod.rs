@@ -0,0 +1,79 @@
+use crate::{
+    game::{
+        catch::{self, CatchObject},
+        mania::{self, ManiaObject},
+        taiko::{self, TaikoObject},
+        Gamemode, ModSet,
+    },
+    parser::beatmap::{objects::TimingPointType, BeatmapFile},
+};
+
+// exports
+mod mania_patterns;
+pub(crate) mod taiko_objects;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertedObjects {
+    Taiko(Vec<TaikoObject>),
+    Catch(Vec<CatchObject>),
+    Mania {
+        key_count: i32,
+        objects: Vec<ManiaObject>,
+    },
+}
+
+// the objects `beatmap` is played with in `mode`. standard maps convert to every other
+// mode and maps of other modes only to their own, standard itself has nothing to
+// convert to. mods matter for catch's positions and mania's key count
+pub fn convert(beatmap: &BeatmapFile, mode: Gamemode, mods: ModSet) -> Option<ConvertedObjects> {
+    let native = beatmap.gamemode as i32 == mode as i32;
+    if !native && beatmap.gamemode as i32 != Gamemode::Standard as i32 {
+        return None;
+    }
+
+    match mode {
+        Gamemode::Standard => None,
+        Gamemode::Taiko => Some(ConvertedObjects::Taiko(taiko::convert(beatmap))),
+        Gamemode::Catch => Some(ConvertedObjects::Catch(catch::convert(beatmap, mods))),
+        Gamemode::Mania if native => Some(ConvertedObjects::Mania {
+            key_count: mania::key_count(beatmap),
+            objects: mania::convert(beatmap),
+        }),
+        Gamemode::Mania => {
+            let (key_count, objects) = mania_patterns::convert(beatmap, mods);
+            Some(ConvertedObjects::Mania { key_count, objects })
+        }
+    }
+}
+
+pub(crate) fn taiko_objects(beatmap: &BeatmapFile) -> Vec<TaikoObject> {
+    match convert(beatmap, Gamemode::Taiko, ModSet::default()) {
+        Some(ConvertedObjects::Taiko(objects)) => objects,
+        _ => taiko::convert(beatmap),
+    }
+}
+
+pub(crate) fn mania_objects(beatmap: &BeatmapFile, mods: ModSet) -> (i32, Vec<ManiaObject>) {
+    match convert(beatmap, Gamemode::Mania, mods) {
+        Some(ConvertedObjects::Mania { key_count, objects }) => (key_count, objects),
+        _ => (mania::key_count(beatmap), mania::convert(beatmap)),
+    }
+}
+
+// the beat length at `time` and the same beat length slowed down by the slider
+// velocity, which osu!stable takes from the inherited point as a bpm multiplier
+fn beat_lengths(beatmap: &BeatmapFile, time: f32) -> (f64, f64) {
+    if beatmap.uninherited_points.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let beat_length = beatmap.get_uninherited_timing_point(time).beat_length as f64;
+    let point = beatmap.get_timing_point(time);
+
+    let bpm_multiplier = match point.point_type {
+        TimingPointType::Inherited => (-point.beat_length as f64).clamp(10.0, 10000.0) / 100.0,
+        TimingPointType::Uninherited => 1.0,
+    };
+
+    (beat_length, beat_length * bpm_multiplier)
+}
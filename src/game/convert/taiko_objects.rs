@@ -0,0 +1,107 @@
+use crate::{
+    game::{
+        convert::beat_lengths,
+        taiko::{self, TaikoObject, TaikoObjectKind},
+    },
+    parser::beatmap::{
+        objects::{HitObject, HitSound, HitType},
+        BeatmapFile,
+    },
+};
+
+// taiko scrolls 1.4 times faster than standard sliders move
+const VELOCITY_MULTIPLIER: f64 = 1.4;
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+
+// circles are notes and spinners are swells, on taiko maps and converts alike.
+// sliders that are short and slow enough become a note on every tick, with the
+// hitsounds of the slider's edges in turn, and every other slider is a drum roll
+// lasting as long as the slider would take at taiko's velocity. objects at the same
+// time merge into a big note
+pub(crate) fn convert(beatmap: &BeatmapFile) -> Vec<TaikoObject> {
+    let mut objects: Vec<TaikoObject> = vec![];
+
+    for object in &beatmap.hit_objects {
+        let converted = if object.hit_type & HitType::Slider as i32 != 0 {
+            slider(beatmap, object)
+        } else {
+            vec![taiko::convert_object(beatmap, object)]
+        };
+
+        for converted in converted {
+            match objects.last_mut() {
+                Some(last) if last.start_time == converted.start_time => {
+                    if last.kind != TaikoObjectKind::Swell {
+                        last.big = true;
+                    }
+                }
+                _ => objects.push(converted),
+            }
+        }
+    }
+
+    objects
+}
+
+// osu!stable's arithmetic is kept as-is, the truncation to whole milliseconds
+// decides which sliders turn into notes
+fn slider(beatmap: &BeatmapFile, object: &HitObject) -> Vec<TaikoObject> {
+    let slider_data = match &object.slider_data {
+        Some(slider_data) => slider_data,
+        None => return vec![taiko::convert_object(beatmap, object)],
+    };
+
+    let difficulty = &beatmap.difficulty;
+    let spans = slider_data.slides.max(1);
+    let distance = slider_data.length as f64 * spans as f64 * VELOCITY_MULTIPLIER;
+
+    let (timing_beat_length, mut beat_length) = beat_lengths(beatmap, object.start_time);
+    let slider_tickrate = difficulty.slider_tickrate as f64;
+    let scoring_point_distance = BASE_SCORING_DISTANCE
+        * (difficulty.slider_multiplier as f64 * VELOCITY_MULTIPLIER)
+        / slider_tickrate;
+    let taiko_velocity = scoring_point_distance * slider_tickrate;
+    let taiko_duration = (distance / taiko_velocity * beat_length) as i32;
+
+    let osu_velocity = taiko_velocity * (1000.0 / beat_length);
+
+    // older maps used the slowed down beat length for ticks too
+    if beatmap.format_version >= 8 {
+        beat_length = timing_beat_length;
+    }
+
+    let tick_spacing = f64::min(
+        beat_length / slider_tickrate,
+        taiko_duration as f64 / spans as f64,
+    );
+
+    let big = object.hit_sound & HitSound::Finish as i32 != 0;
+    if !(tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_length) {
+        let start_time = object.start_time;
+        return vec![taiko::drum_roll(
+            beatmap,
+            start_time,
+            start_time + taiko_duration as f32,
+            big,
+        )];
+    }
+
+    let edge_sounds = if slider_data.edge_sounds.is_empty() {
+        vec![object.hit_sound]
+    } else {
+        slider_data.edge_sounds.clone()
+    };
+
+    let mut notes = vec![];
+    let end = object.start_time as f64 + taiko_duration as f64 + tick_spacing / 8.0;
+    let mut time = object.start_time as f64;
+
+    while time <= end {
+        let hit_sound = edge_sounds[notes.len() % edge_sounds.len()];
+        notes.push(taiko::note(hit_sound, time as f32));
+
+        time += tick_spacing;
+    }
+
+    notes
+}
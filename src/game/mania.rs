@@ -0,0 +1,85 @@
+use crate::parser::beatmap::{objects::HitType, BeatmapFile};
+
+// the playfield every column is laid out over, whatever the key count
+const PLAYFIELD_WIDTH: f32 = 512.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManiaNote {
+    pub column: i32,
+    pub start_time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManiaHold {
+    pub column: i32,
+    pub start_time: f32,
+    pub end_time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ManiaObject {
+    Note(ManiaNote),
+    Hold(ManiaHold),
+}
+
+impl ManiaObject {
+    pub fn column(&self) -> i32 {
+        match self {
+            ManiaObject::Note(note) => note.column,
+            ManiaObject::Hold(hold) => hold.column,
+        }
+    }
+
+    pub fn start_time(&self) -> f32 {
+        match self {
+            ManiaObject::Note(note) => note.start_time,
+            ManiaObject::Hold(hold) => hold.start_time,
+        }
+    }
+
+    // the start time for notes
+    pub fn end_time(&self) -> f32 {
+        match self {
+            ManiaObject::Note(note) => note.start_time,
+            ManiaObject::Hold(hold) => hold.end_time,
+        }
+    }
+}
+
+// the circle size of a mania map is its key count
+pub fn key_count(beatmap: &BeatmapFile) -> i32 {
+    (beatmap.difficulty.circle_size.round() as i32).max(1)
+}
+
+// which of `key_count` columns an x position falls in
+pub fn column(x: f32, key_count: i32) -> i32 {
+    let width = PLAYFIELD_WIDTH / key_count as f32;
+    ((x / width).floor() as i32).clamp(0, key_count - 1)
+}
+
+// objects of a map made for mania, holds are the objects with an end time and
+// everything else is a note
+pub fn convert(beatmap: &BeatmapFile) -> Vec<ManiaObject> {
+    let key_count = key_count(beatmap);
+
+    beatmap
+        .hit_objects
+        .iter()
+        .map(|object| {
+            let column = column(object.x, key_count);
+
+            if object.hit_type & HitType::Hold as i32 != 0 {
+                ManiaObject::Hold(ManiaHold {
+                    column,
+                    start_time: object.start_time,
+                    end_time: object.end_time,
+                })
+            } else {
+                ManiaObject::Note(ManiaNote {
+                    column,
+                    start_time: object.start_time,
+                })
+            }
+        })
+        .collect()
+}
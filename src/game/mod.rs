@@ -1,10 +1,16 @@
+pub mod catch;
+pub mod convert;
+pub mod mania;
 pub mod score;
+pub mod taiko;
 
-use std::{convert::TryFrom, str::FromStr};
+mod random;
+
+use std::{convert::TryFrom, fmt, ops::BitOr, str::FromStr};
 
 /// GAME MODE DATA ///
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Gamemode {
     Standard,
     Taiko,
@@ -45,7 +51,7 @@ impl FromStr for Gamemode {
 }
 
 /// MOD DATA ///
-
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mods {
     NoMod = 0,
     NoFail = 1 << 0,
@@ -79,35 +85,338 @@ pub enum Mods {
     Mania2K = 1 << 28,
 }
 
+// every mod in the order of its bit, which is the order acronyms are written in
+const ALL_MODS: [Mods; 29] = [
+    Mods::NoFail,
+    Mods::Easy,
+    Mods::TouchDevice,
+    Mods::Hidden,
+    Mods::HardRock,
+    Mods::SuddenDeath,
+    Mods::DoubleTime,
+    Mods::Relax,
+    Mods::HalfTime,
+    Mods::Nightcore,
+    Mods::Flashlight,
+    Mods::Autoplay,
+    Mods::SpunOut,
+    Mods::Relax2,
+    Mods::Perfect,
+    Mods::Mania4K,
+    Mods::Mania5K,
+    Mods::Mania6K,
+    Mods::Mania7K,
+    Mods::Mania8K,
+    Mods::FadeIn,
+    Mods::Random,
+    Mods::Cinema,
+    Mods::Target,
+    Mods::Mania9K,
+    Mods::ManiaCoOp,
+    Mods::Mania1K,
+    Mods::Mania3K,
+    Mods::Mania2K,
+];
+
+// mods that can't be played together
+const INCOMPATIBLE_MODS: [(Mods, Mods); 12] = [
+    (Mods::Easy, Mods::HardRock),
+    (Mods::DoubleTime, Mods::HalfTime),
+    (Mods::NoFail, Mods::SuddenDeath),
+    (Mods::NoFail, Mods::Relax),
+    (Mods::NoFail, Mods::Relax2),
+    (Mods::SuddenDeath, Mods::Relax),
+    (Mods::SuddenDeath, Mods::Relax2),
+    (Mods::Relax, Mods::Relax2),
+    (Mods::Relax, Mods::Autoplay),
+    (Mods::Autoplay, Mods::Relax2),
+    (Mods::SpunOut, Mods::Relax2),
+    (Mods::Hidden, Mods::FadeIn),
+];
+
+// the mania key mods and the key count each one asks for
+const KEY_MODS: [(Mods, i32); 9] = [
+    (Mods::Mania1K, 1),
+    (Mods::Mania2K, 2),
+    (Mods::Mania3K, 3),
+    (Mods::Mania4K, 4),
+    (Mods::Mania5K, 5),
+    (Mods::Mania6K, 6),
+    (Mods::Mania7K, 7),
+    (Mods::Mania8K, 8),
+    (Mods::Mania9K, 9),
+];
+
 impl Mods {
-    pub fn from_str(string: String) -> Mods {
-        match string.as_str() {
-            // Difficulty Reduction Mods
-            "NF" => Mods::NoFail,
-            "HT" => Mods::HalfTime,
-            "EZ" => Mods::Easy,
-
-            // Perfectionist Mods
-            "SD" => Mods::SuddenDeath,
-            "PF" => Mods::Perfect,
-
-            // Difficulty Increase Mods
-            "HR" => Mods::HardRock,
-            "HD" => Mods::Hidden,
-            "DT" => Mods::DoubleTime,
-            "NC" => Mods::Nightcore,
-            "FL" => Mods::Flashlight,
-
-            // Extraneous Mods
-            "RX" => Mods::Relax,
-            "AP" => Mods::Relax2,
-            "SO" => Mods::SpunOut,
-            "AT" => Mods::Autoplay,
-            "TD" => Mods::TouchDevice, // RIP TD Players.
-
-            // No Mod
-            "NM" => Mods::NoMod,
-            _ => Mods::NoMod, // Default, if none available.
+    pub fn acronym(&self) -> &'static str {
+        match self {
+            Mods::NoMod => "NM",
+            Mods::NoFail => "NF",
+            Mods::Easy => "EZ",
+            Mods::TouchDevice => "TD",
+            Mods::Hidden => "HD",
+            Mods::HardRock => "HR",
+            Mods::SuddenDeath => "SD",
+            Mods::DoubleTime => "DT",
+            Mods::Relax => "RX",
+            Mods::HalfTime => "HT",
+            Mods::Nightcore => "NC",
+            Mods::Flashlight => "FL",
+            Mods::Autoplay => "AT",
+            Mods::SpunOut => "SO",
+            Mods::Relax2 => "AP",
+            Mods::Perfect => "PF",
+            Mods::Mania4K => "4K",
+            Mods::Mania5K => "5K",
+            Mods::Mania6K => "6K",
+            Mods::Mania7K => "7K",
+            Mods::Mania8K => "8K",
+            Mods::FadeIn => "FI",
+            Mods::Random => "RD",
+            Mods::Cinema => "CN",
+            Mods::Target => "TP",
+            Mods::Mania9K => "9K",
+            Mods::ManiaCoOp => "CO",
+            Mods::Mania1K => "1K",
+            Mods::Mania3K => "3K",
+            Mods::Mania2K => "2K",
+        }
+    }
+
+    // the mod this one can't be played without, nightcore is double time with a
+    // different sound and perfect is a stricter sudden death
+    pub fn implied(&self) -> Option<Mods> {
+        match self {
+            Mods::Nightcore => Some(Mods::DoubleTime),
+            Mods::Perfect => Some(Mods::SuddenDeath),
+            _ => None,
+        }
+    }
+
+    // what a play's score is multiplied by in osu!stable, relax and autopilot don't
+    // give any score
+    pub fn score_multiplier(&self, mode: Gamemode) -> f64 {
+        match (self, mode) {
+            (Mods::NoFail, _) | (Mods::Easy, _) => 0.5,
+            (Mods::HalfTime, Gamemode::Mania) => 0.5,
+            (Mods::HalfTime, _) => 0.3,
+            (Mods::Relax, _) | (Mods::Relax2, _) => 0.0,
+            (Mods::SpunOut, _) => 0.9,
+            // the difficulty increases only count in mania if they're ranked
+            (_, Gamemode::Mania) => 1.0,
+            (Mods::HardRock, Gamemode::Catch) => 1.12,
+            (Mods::DoubleTime, Gamemode::Catch) | (Mods::Nightcore, Gamemode::Catch) => 1.06,
+            (Mods::Hidden, _) | (Mods::HardRock, _) => 1.06,
+            (Mods::DoubleTime, _) | (Mods::Nightcore, _) | (Mods::Flashlight, _) => 1.12,
+            _ => 1.0,
         }
     }
 }
+
+impl FromStr for Mods {
+    type Err = ();
+
+    // a single acronym, "NM" for no mod
+    fn from_str(input: &str) -> Result<Mods, Self::Err> {
+        let input = input.to_ascii_uppercase();
+        if input == Mods::NoMod.acronym() {
+            return Ok(Mods::NoMod);
+        }
+
+        ALL_MODS
+            .iter()
+            .find(|m| m.acronym() == input)
+            .copied()
+            .ok_or(())
+    }
+}
+
+// an acronym in a mod string that isn't a mod
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownMod(pub String);
+
+impl fmt::Display for UnknownMod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown mod \"{}\"", self.0)
+    }
+}
+
+// a set of mods, stored as osu!'s bitflags. mods that imply another always carry it
+// with them, like they do in osu!'s own mod integers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModSet {
+    bits: i64,
+}
+
+impl ModSet {
+    // bits osu! doesn't know are kept so the integer can be written back as it was
+    pub fn from_bits(bits: i64) -> ModSet {
+        let mut mods = ModSet { bits };
+        for m in mods.iter().collect::<Vec<Mods>>() {
+            mods.insert(m);
+        }
+
+        mods
+    }
+
+    pub fn bits(&self) -> i64 {
+        self.bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(&self, m: Mods) -> bool {
+        m as i64 != 0 && self.bits & m as i64 == m as i64
+    }
+
+    pub fn insert(&mut self, m: Mods) {
+        self.bits |= m as i64;
+        if let Some(implied) = m.implied() {
+            self.bits |= implied as i64;
+        }
+    }
+
+    // taking away a mod also takes away anything implying it
+    pub fn remove(&mut self, m: Mods) {
+        self.bits &= !(m as i64);
+        for other in ALL_MODS.iter().filter(|other| other.implied() == Some(m)) {
+            self.bits &= !(*other as i64);
+        }
+    }
+
+    // in the order of their bits
+    pub fn iter(&self) -> impl Iterator<Item = Mods> + '_ {
+        ALL_MODS.iter().copied().filter(move |m| self.contains(*m))
+    }
+
+    // pairs of mods in the set that can't be played together, more than one key mod
+    // counts too
+    pub fn incompatible(&self) -> Vec<(Mods, Mods)> {
+        let mut pairs: Vec<(Mods, Mods)> = INCOMPATIBLE_MODS
+            .iter()
+            .copied()
+            .filter(|(a, b)| self.contains(*a) && self.contains(*b))
+            .collect();
+
+        let keys: Vec<Mods> = KEY_MODS
+            .iter()
+            .map(|(m, _)| *m)
+            .filter(|m| self.contains(*m))
+            .collect();
+        for (i, a) in keys.iter().enumerate() {
+            for b in &keys[i + 1..] {
+                pairs.push((*a, *b));
+            }
+        }
+
+        pairs
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.incompatible().is_empty()
+    }
+
+    // the product of every mod's multiplier, implied mods don't count twice
+    pub fn score_multiplier(&self, mode: Gamemode) -> f64 {
+        self.shown().map(|m| m.score_multiplier(mode)).product()
+    }
+
+    // how much faster the song plays
+    pub fn clock_rate(&self) -> f64 {
+        if self.contains(Mods::DoubleTime) {
+            1.5
+        } else if self.contains(Mods::HalfTime) {
+            0.75
+        } else {
+            1.0
+        }
+    }
+
+    // the key count the key mods ask for
+    pub fn key_count(&self) -> Option<i32> {
+        KEY_MODS
+            .iter()
+            .find(|(m, _)| self.contains(*m))
+            .map(|(_, count)| *count)
+    }
+
+    // the mods that are written out, leaving out the ones implied by another
+    fn shown(&self) -> impl Iterator<Item = Mods> + '_ {
+        self.iter()
+            .filter(move |m| !self.iter().any(|other| other.implied() == Some(*m)))
+    }
+}
+
+impl From<Mods> for ModSet {
+    fn from(m: Mods) -> ModSet {
+        let mut mods = ModSet::default();
+        mods.insert(m);
+        mods
+    }
+}
+
+impl BitOr for ModSet {
+    type Output = ModSet;
+
+    fn bitor(self, other: ModSet) -> ModSet {
+        ModSet::from_bits(self.bits | other.bits)
+    }
+}
+
+impl BitOr<Mods> for ModSet {
+    type Output = ModSet;
+
+    fn bitor(self, other: Mods) -> ModSet {
+        self | ModSet::from(other)
+    }
+}
+
+impl BitOr for Mods {
+    type Output = ModSet;
+
+    fn bitor(self, other: Mods) -> ModSet {
+        ModSet::from(self) | other
+    }
+}
+
+impl FromStr for ModSet {
+    type Err = UnknownMod;
+
+    // acronyms written together like "HDDTHR" or split up like "+HD,+DT" or "+HD+DT",
+    // in any case
+    fn from_str(input: &str) -> Result<ModSet, Self::Err> {
+        let mut mods = ModSet::default();
+
+        for part in input.split(|c: char| c == ',' || c == '|' || c == '+' || c.is_whitespace()) {
+            let chars: Vec<char> = part.chars().collect();
+
+            for acronym in chars.chunks(2) {
+                let acronym: String = acronym.iter().collect();
+                match acronym.parse::<Mods>() {
+                    Ok(m) if m != Mods::NoMod => mods.insert(m),
+                    Ok(_) => {}
+                    Err(_) => return Err(UnknownMod(acronym)),
+                }
+            }
+        }
+
+        Ok(mods)
+    }
+}
+
+impl fmt::Display for ModSet {
+    // "NM" when there are no mods
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.shown().next().is_none() {
+            return write!(f, "{}", Mods::NoMod.acronym());
+        }
+
+        for m in self.shown() {
+            write!(f, "{}", m.acronym())?;
+        }
+
+        Ok(())
+    }
+}
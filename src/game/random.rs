@@ -0,0 +1,65 @@
+// the xorshift generator osu!stable uses for anything random in converted maps, the
+// same seed has to give the same sequence for positions to line up with the game
+pub(crate) struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+    // booleans come one bit at a time out of a single number
+    bit_buffer: u32,
+    bit_index: u32,
+}
+
+const INT_TO_REAL: f64 = 1.0 / (i32::MAX as f64 + 1.0);
+
+impl LegacyRandom {
+    pub(crate) fn new(seed: i32) -> LegacyRandom {
+        LegacyRandom {
+            x: seed as u32,
+            y: 842502087,
+            z: 3579807591,
+            w: 273326509,
+            bit_buffer: 0,
+            bit_index: 32,
+        }
+    }
+
+    pub(crate) fn next_uint(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    // 0 to i32::MAX
+    pub(crate) fn next(&mut self) -> i32 {
+        (self.next_uint() & 0x7FFFFFFF) as i32
+    }
+
+    pub(crate) fn next_double(&mut self) -> f64 {
+        INT_TO_REAL * self.next() as f64
+    }
+
+    // a new number every 32 calls, the bits are handed out lowest first
+    pub(crate) fn next_bool(&mut self) -> bool {
+        if self.bit_index == 32 {
+            self.bit_buffer = self.next_uint();
+            self.bit_index = 0;
+        }
+
+        let bit = (self.bit_buffer >> self.bit_index) & 1;
+        self.bit_index += 1;
+        bit == 1
+    }
+
+    // lower inclusive, upper exclusive
+    pub(crate) fn next_range(&mut self, lower: i32, upper: i32) -> i32 {
+        (lower as f64 + self.next_double() * (upper - lower) as f64) as i32
+    }
+
+    pub(crate) fn next_double_range(&mut self, lower: f64, upper: f64) -> f64 {
+        lower + self.next_double() * (upper - lower)
+    }
+}
@@ -1,11 +1,16 @@
+use std::fmt;
+
+use crate::game::{Gamemode, ModSet, Mods};
+
 /// SCORE DATA ///
+#[derive(Debug, Clone, PartialEq)]
 pub struct Score {
     // General Score Data
     pub score_id: i64,
     //pub mode: Gamemode,
     pub user_id: i64,
     pub beatmap_id: i32,
-    pub mods: i64,
+    pub mods: ModSet,
 
     // Score
     pub score: i32,
@@ -15,11 +20,121 @@ pub struct Score {
     pub count_50: i32,
     pub count_geki: i32,
     pub count_katu: i32,
+    pub count_miss: i32,
+}
+
+// the grades of a passed play as osu!stable names them, X is SS and the H grades are
+// the silver ones hidden and flashlight give
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    XH,
+    SH,
+    X,
+    S,
+    A,
+    B,
+    C,
+    D,
 }
 
-// for score calculation
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Score {
+    // the grade osu!stable gives the play, standard and taiko go by the share of 300s
+    // and the rest by accuracy
+    pub fn grade(&self, gamemode: Gamemode) -> Grade {
+        let silver = self.mods.contains(Mods::Hidden) || self.mods.contains(Mods::Flashlight);
+        let (x, s) = if silver {
+            (Grade::XH, Grade::SH)
+        } else {
+            (Grade::X, Grade::S)
+        };
+
+        match gamemode {
+            Gamemode::Standard | Gamemode::Taiko => {
+                let total = self.count_300 + self.count_100 + self.count_50 + self.count_miss;
+                let ratio_300 = self.count_300 as f64 / total.max(1) as f64;
+                let ratio_50 = self.count_50 as f64 / total.max(1) as f64;
+                let full_combo = self.count_miss == 0;
+
+                if ratio_300 == 1.0 {
+                    x
+                } else if ratio_300 > 0.9 && ratio_50 <= 0.01 && full_combo {
+                    s
+                } else if (ratio_300 > 0.8 && full_combo) || ratio_300 > 0.9 {
+                    Grade::A
+                } else if (ratio_300 > 0.7 && full_combo) || ratio_300 > 0.8 {
+                    Grade::B
+                } else if ratio_300 > 0.6 {
+                    Grade::C
+                } else {
+                    Grade::D
+                }
+            }
+            Gamemode::Catch => {
+                let caught = self.count_300 + self.count_100 + self.count_50;
+                let accuracy =
+                    caught as f64 / (caught + self.count_katu + self.count_miss).max(1) as f64;
+                grade_by_accuracy(accuracy, x, s, [0.98, 0.94, 0.9, 0.85])
+            }
+            Gamemode::Mania => {
+                let total = self.count_300
+                    + self.count_100
+                    + self.count_50
+                    + self.count_geki
+                    + self.count_katu
+                    + self.count_miss;
+                let points = (self.count_300 + self.count_geki) * 300
+                    + self.count_katu * 200
+                    + self.count_100 * 100
+                    + self.count_50 * 50;
+                let accuracy = points as f64 / (total.max(1) * 300) as f64;
+                grade_by_accuracy(accuracy, x, s, [0.95, 0.9, 0.8, 0.7])
+            }
+        }
+    }
+}
+
+// `thresholds` are the accuracies an S, A, B and C need to be beaten
+fn grade_by_accuracy(accuracy: f64, x: Grade, s: Grade, thresholds: [f64; 4]) -> Grade {
+    if accuracy == 1.0 {
+        x
+    } else if accuracy > thresholds[0] {
+        s
+    } else if accuracy > thresholds[1] {
+        Grade::A
+    } else if accuracy > thresholds[2] {
+        Grade::B
+    } else if accuracy > thresholds[3] {
+        Grade::C
+    } else {
+        Grade::D
+    }
+}
+
+// for score calculation, "what if" plays where the hit counts follow from the accuracy
+#[derive(Debug, Clone, PartialEq)]
 pub struct PartialScore {
     //pub mode: Gamemode,
-    pub mods: i64,
-    pub max_combo: i32,
+    pub mods: ModSet,
+    // None for a full combo
+    pub max_combo: Option<i32>,
+    // percentage, 0-100
+    pub accuracy: f64,
+    pub count_miss: i32,
+}
+
+impl PartialScore {
+    pub fn full_combo(mods: ModSet, accuracy: f64) -> PartialScore {
+        PartialScore {
+            mods,
+            max_combo: None,
+            accuracy,
+            count_miss: 0,
+        }
+    }
 }
@@ -0,0 +1,125 @@
+use crate::{
+    difficulty::difficulty_range,
+    game::convert::taiko_objects,
+    parser::beatmap::{
+        objects::{HitObject, HitSound, HitType},
+        BeatmapFile,
+    },
+};
+
+// swells need this many more hits per second than a spinner needs spins
+const SWELL_HIT_MULTIPLIER: f64 = 1.65;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaikoObjectKind {
+    Don,
+    Kat,
+    DrumRoll,
+    Swell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaikoObject {
+    pub kind: TaikoObjectKind,
+    pub start_time: f32,
+    pub end_time: f32,
+    // big notes are meant to be hit with both hands
+    pub big: bool,
+    // ticks of a drum roll or hits a swell needs, zero for dons and kats
+    pub ticks: i32,
+}
+
+impl TaikoObject {
+    // dons and kats, the only objects that give combo
+    pub fn is_hit(&self) -> bool {
+        self.kind == TaikoObjectKind::Don || self.kind == TaikoObjectKind::Kat
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.end_time - self.start_time
+    }
+}
+
+// objects of a map made for taiko or converted from standard, osu! treats both the
+// same. circles are dons unless they whistle or clap, sliders are drum rolls or a run
+// of notes and spinners are swells. finishes make notes big
+pub fn convert(beatmap: &BeatmapFile) -> Vec<TaikoObject> {
+    taiko_objects::convert(beatmap)
+}
+
+pub(crate) fn convert_object(beatmap: &BeatmapFile, object: &HitObject) -> TaikoObject {
+    let big = object.hit_sound & HitSound::Finish as i32 != 0;
+
+    if object.hit_type & HitType::Slider as i32 != 0 {
+        drum_roll(beatmap, object.start_time, object.end_time, big)
+    } else if object.hit_type & HitType::Spinner as i32 != 0 {
+        swell(beatmap, object.start_time, object.end_time)
+    } else {
+        note(object.hit_sound, object.start_time)
+    }
+}
+
+pub(crate) fn note(hit_sound: i32, time: f32) -> TaikoObject {
+    let rim = hit_sound & (HitSound::Whistle as i32 | HitSound::Clap as i32) != 0;
+
+    TaikoObject {
+        kind: if rim {
+            TaikoObjectKind::Kat
+        } else {
+            TaikoObjectKind::Don
+        },
+        start_time: time,
+        end_time: time,
+        big: hit_sound & HitSound::Finish as i32 != 0,
+        ticks: 0,
+    }
+}
+
+// a tick every 1/4 beat, 1/3 on maps with a tick rate of 3
+pub(crate) fn drum_roll(
+    beatmap: &BeatmapFile,
+    start_time: f32,
+    end_time: f32,
+    big: bool,
+) -> TaikoObject {
+    let tick_rate = if beatmap.difficulty.slider_tickrate == 3.0 {
+        3.0
+    } else {
+        4.0
+    };
+
+    let mut ticks = 0;
+    if !beatmap.uninherited_points.is_empty() {
+        let tick_spacing = beatmap.get_uninherited_timing_point(start_time).beat_length / tick_rate;
+
+        if tick_spacing > 0.0 {
+            let mut time = start_time;
+            while time < end_time + tick_spacing / 2.0 {
+                ticks += 1;
+                time += tick_spacing;
+            }
+        }
+    }
+
+    TaikoObject {
+        kind: TaikoObjectKind::DrumRoll,
+        start_time,
+        end_time,
+        big,
+        ticks,
+    }
+}
+
+pub(crate) fn swell(beatmap: &BeatmapFile, start_time: f32, end_time: f32) -> TaikoObject {
+    let od = beatmap.difficulty.overall_difficulty as f64;
+    let hits_per_second = difficulty_range(od, 3.0, 5.0, 7.5) * SWELL_HIT_MULTIPLIER;
+    let duration = (end_time - start_time) as f64;
+
+    TaikoObject {
+        kind: TaikoObjectKind::Swell,
+        start_time,
+        end_time,
+        big: false,
+        ticks: ((duration / 1000.0 * hits_per_second) as i32).max(1),
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 
+pub mod difficulty;
 pub mod game;
 pub mod parser;
+pub mod performance;
+pub mod replay;
 pub mod request;
 pub mod util;
 pub mod constants;
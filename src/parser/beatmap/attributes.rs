@@ -0,0 +1,159 @@
+use crate::{
+    difficulty::difficulty_range,
+    game::{Gamemode, ModSet, Mods},
+    parser::beatmap::{BeatmapFile, DifficultyMetadata},
+};
+
+// the map's difficulty settings as they are played with a set of mods. the settings
+// are scaled by hard rock and easy, approach rate and overall difficulty are also
+// what they feel like after the rate change. the millisecond values are in the map's
+// own time like object times are, divide them by the clock rate for real time.
+// judgements a ruleset doesn't have get a window of 0, catch has none at all
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyAttributes {
+    pub approach_rate: f64,
+    pub overall_difficulty: f64,
+    pub circle_size: f64,
+    pub hp_drain: f64,
+    pub clock_rate: f64,
+
+    // how long before its time an object appears, and how long it takes to fade in
+    pub preempt: f64,
+    pub fade_in: f64,
+
+    // how far off a hit can be on either side
+    pub hit_window_300: f64,
+    pub hit_window_100: f64,
+    pub hit_window_50: f64,
+
+    pub circle_radius: f64,
+    pub spins_per_second: f64,
+}
+
+impl DifficultyAttributes {
+    // the full spins a spinner lasting `duration` ms needs to be cleared
+    pub fn required_spins(&self, duration: f64) -> i32 {
+        (duration / 1000.0 * self.spins_per_second).floor() as i32
+    }
+}
+
+impl DifficultyMetadata {
+    // the attributes for a map made for `mode`
+    pub fn attributes(&self, mode: Gamemode, mods: ModSet) -> DifficultyAttributes {
+        attributes(self, mode, false, mods)
+    }
+}
+
+impl BeatmapFile {
+    // the attributes when played in `mode`, converts to mania have their own windows
+    pub fn attributes(&self, mode: Gamemode, mods: ModSet) -> DifficultyAttributes {
+        let convert = self.gamemode as i32 != mode as i32;
+        attributes(&self.difficulty, mode, convert, mods)
+    }
+}
+
+// hard rock scales everything by 1.4 up to 10 except circle size, which only goes up
+// by 1.3. easy halves everything
+pub(super) fn adjust(value: f32, hard_rock: f64, mods: ModSet) -> f64 {
+    let value = value as f64;
+    if mods.contains(Mods::HardRock) {
+        (value * hard_rock).min(10.0)
+    } else if mods.contains(Mods::Easy) {
+        value * 0.5
+    } else {
+        value
+    }
+}
+
+fn attributes(
+    difficulty: &DifficultyMetadata,
+    mode: Gamemode,
+    convert: bool,
+    mods: ModSet,
+) -> DifficultyAttributes {
+    let clock_rate = mods.clock_rate();
+
+    // a mania map's circle size is its key count
+    let circle_size = if mode == Gamemode::Mania {
+        difficulty.circle_size as f64
+    } else {
+        adjust(difficulty.circle_size, 1.3, mods)
+    };
+    let approach_rate = adjust(difficulty.approach_rate, 1.4, mods);
+    let overall_difficulty = adjust(difficulty.overall_difficulty, 1.4, mods);
+
+    let preempt = difficulty_range(approach_rate, 1800.0, 1200.0, 450.0);
+    let scaled_preempt = preempt / clock_rate;
+    let effective_approach_rate = if scaled_preempt > 1200.0 {
+        (1800.0 - scaled_preempt) / 120.0
+    } else {
+        (1200.0 - scaled_preempt) / 150.0 + 5.0
+    };
+
+    let (windows, effective_overall_difficulty) = match mode {
+        Gamemode::Standard => {
+            let windows = [
+                difficulty_range(overall_difficulty, 80.0, 50.0, 20.0),
+                difficulty_range(overall_difficulty, 140.0, 100.0, 60.0),
+                difficulty_range(overall_difficulty, 200.0, 150.0, 100.0),
+            ];
+            (windows, (80.0 - windows[0] / clock_rate) / 6.0)
+        }
+        Gamemode::Taiko => {
+            let windows = [
+                difficulty_range(overall_difficulty, 50.0, 35.0, 20.0),
+                difficulty_range(overall_difficulty, 120.0, 80.0, 50.0),
+                0.0,
+            ];
+            (windows, (50.0 - windows[0] / clock_rate) / 3.0)
+        }
+        Gamemode::Catch => ([0.0; 3], overall_difficulty),
+        Gamemode::Mania => {
+            let windows = mania_windows(difficulty.overall_difficulty as f64, convert, mods);
+            (windows, (64.0 - windows[0] / clock_rate) / 3.0)
+        }
+    };
+
+    DifficultyAttributes {
+        approach_rate: effective_approach_rate,
+        overall_difficulty: effective_overall_difficulty,
+        circle_size,
+        hp_drain: adjust(difficulty.hp_drain, 1.4, mods),
+        clock_rate,
+        preempt,
+        fade_in: 400.0 * (preempt / 450.0).min(1.0),
+        hit_window_300: windows[0],
+        hit_window_100: windows[1],
+        hit_window_50: windows[2],
+        circle_radius: 54.4 - 4.48 * circle_size,
+        spins_per_second: difficulty_range(overall_difficulty, 3.0, 5.0, 7.5),
+    }
+}
+
+// mania's windows shrink by 3ms per overall difficulty and hard rock and easy scale
+// the windows by 1.4 instead of changing it. converts only have two sets of windows,
+// one above OD4 and one for the rest
+fn mania_windows(overall_difficulty: f64, convert: bool, mods: ModSet) -> [f64; 3] {
+    let windows = if !convert {
+        let overall_difficulty = overall_difficulty.clamp(0.0, 10.0);
+        [
+            64.0 - 3.0 * overall_difficulty,
+            127.0 - 3.0 * overall_difficulty,
+            151.0 - 3.0 * overall_difficulty,
+        ]
+    } else if overall_difficulty.round() > 4.0 {
+        [34.0, 97.0, 121.0]
+    } else {
+        [47.0, 97.0, 121.0]
+    };
+
+    windows.map(|window| {
+        if mods.contains(Mods::HardRock) {
+            window / 1.4
+        } else if mods.contains(Mods::Easy) {
+            window * 1.4
+        } else {
+            window
+        }
+    })
+}
@@ -0,0 +1,314 @@
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::parser::beatmap::{
+    error::ParseError,
+    writer::{pair_separator, SECTIONS},
+    BeatmapFile,
+};
+
+// a parsed beatmap that remembers the file it came from, lines that were not
+// changed through `beatmap` are written back exactly as they were read
+#[derive(Debug)]
+pub struct BeatmapDocument {
+    pub beatmap: BeatmapFile,
+
+    bom: bool,
+    format_version: i32,
+    lines: Vec<DocumentLine>,
+
+    // what the writer produced for each section when the file was read
+    pairs: HashMap<String, HashMap<String, String>>,
+    items: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug)]
+struct DocumentLine {
+    content: String,
+    ending: String,
+    section: String,
+    kind: LineKind,
+}
+
+#[derive(Debug)]
+enum LineKind {
+    Version,
+    // comments, blank lines, section headers and anything the parser skips
+    Verbatim,
+    Pair { key: String, value_start: usize },
+    Item(usize),
+}
+
+impl BeatmapDocument {
+    pub fn from_file(path: &str) -> BeatmapDocument {
+        BeatmapDocument::try_from_file(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_pathbuf(path: PathBuf) -> BeatmapDocument {
+        BeatmapDocument::try_from_pathbuf(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(map_string: &str) -> BeatmapDocument {
+        BeatmapDocument::try_from_str(map_string).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_file(path: &str) -> Result<BeatmapDocument, ParseError> {
+        let map_string = fs::read_to_string(path).map_err(|e| ParseError::io(e, path))?;
+        BeatmapDocument::try_from_str(&map_string)
+    }
+
+    pub fn try_from_pathbuf(path: PathBuf) -> Result<BeatmapDocument, ParseError> {
+        let map_string =
+            fs::read_to_string(&path).map_err(|e| ParseError::io(e, &path.to_string_lossy()))?;
+        BeatmapDocument::try_from_str(&map_string)
+    }
+
+    pub fn try_from_str(map_string: &str) -> Result<BeatmapDocument, ParseError> {
+        let beatmap = BeatmapFile::try_from_str(map_string)?;
+        let kvp_regex = Regex::new(r"(\w+)\s*:\s*(.*)").unwrap();
+
+        let mut pairs = HashMap::new();
+        let mut items = HashMap::new();
+
+        for section in &SECTIONS {
+            let section_pairs: HashMap<String, String> =
+                beatmap.section_pairs(section).into_iter().collect();
+
+            pairs.insert(section.to_string(), section_pairs);
+            items.insert(section.to_string(), beatmap.section_items(section));
+        }
+
+        let stripped = map_string.trim_start_matches('\u{feff}');
+        let mut lines = vec![];
+        let mut section = "";
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+
+        for raw in stripped.split_inclusive('\n') {
+            let content = raw.trim_end_matches(&['\r', '\n'][..]);
+            let ending = &raw[content.len()..];
+            let s = content.trim_end();
+
+            let kind = if s.is_empty() || s.starts_with("//") {
+                LineKind::Verbatim
+            } else if s.starts_with("osu file format v") {
+                LineKind::Version
+            } else if s.starts_with('[') && s.ends_with(']') {
+                section = s[1..s.len() - 1].trim();
+                LineKind::Verbatim
+            } else {
+                match section {
+                    "Events" | "TimingPoints" | "HitObjects" => {
+                        let count = counts.entry(section).or_insert(0);
+                        *count += 1;
+                        LineKind::Item(*count - 1)
+                    }
+                    _ => match (kvp_regex.captures(s), pairs.get(section)) {
+                        (Some(cap), Some(known)) if known.contains_key(&cap[1]) => LineKind::Pair {
+                            key: cap[1].to_string(),
+                            value_start: cap.get(2).map_or(s.len(), |m| m.start()),
+                        },
+                        _ => LineKind::Verbatim,
+                    },
+                }
+            };
+
+            lines.push(DocumentLine {
+                content: content.to_string(),
+                ending: ending.to_string(),
+                section: section.to_string(),
+                kind,
+            });
+        }
+
+        Ok(BeatmapDocument {
+            format_version: beatmap.format_version,
+            beatmap,
+            bom: map_string.starts_with('\u{feff}'),
+            lines,
+            pairs,
+            items,
+        })
+    }
+
+    pub fn to_osu_string(&self) -> String {
+        let mut buffer = vec![];
+        self.write_to(&mut buffer)
+            .expect("writing to a Vec<u8> cannot fail");
+
+        String::from_utf8(buffer).expect("beatmap output is always valid utf-8")
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let newline = self
+            .lines
+            .first()
+            .map(|line| line.ending.as_str())
+            .filter(|ending| !ending.is_empty())
+            .unwrap_or("\n");
+
+        // new keys and objects go after the last line with content in their section
+        let mut anchors: HashMap<&str, usize> = HashMap::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            let s = line.content.trim();
+            if !s.is_empty() && !s.starts_with("//") {
+                anchors.insert(&line.section, index);
+            }
+        }
+
+        // what each unchanged item looked like in the file
+        let mut originals: HashMap<&str, HashMap<&str, VecDeque<&str>>> = HashMap::new();
+        for line in &self.lines {
+            if let LineKind::Item(index) = line.kind {
+                if let Some(rendered) = self.items[&line.section].get(index) {
+                    originals
+                        .entry(&line.section)
+                        .or_default()
+                        .entry(rendered)
+                        .or_default()
+                        .push_back(&line.content);
+                }
+            }
+        }
+
+        let mut current_pairs = HashMap::new();
+        let mut current_items = HashMap::new();
+        for section in &SECTIONS {
+            let pairs: HashMap<String, String> =
+                self.beatmap.section_pairs(section).into_iter().collect();
+
+            current_pairs.insert(*section, pairs);
+            current_items.insert(*section, self.beatmap.section_items(section));
+        }
+
+        if self.bom {
+            write!(writer, "\u{feff}")?;
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let section = line.section.as_str();
+
+            match &line.kind {
+                LineKind::Version => {
+                    if self.beatmap.format_version == self.format_version {
+                        write!(writer, "{}{}", line.content, line.ending)?;
+                    } else {
+                        let version = format!("osu file format v{}", self.beatmap.format_version);
+                        write!(writer, "{}{}", version, line.ending)?;
+                    }
+                }
+
+                LineKind::Verbatim => write!(writer, "{}{}", line.content, line.ending)?,
+
+                LineKind::Pair { key, value_start } => {
+                    match current_pairs.get(section).and_then(|pairs| pairs.get(key)) {
+                        // the value is no longer written at all
+                        None => {}
+                        Some(value) if Some(value) == self.pairs[section].get(key) => {
+                            write!(writer, "{}{}", line.content, line.ending)?
+                        }
+                        Some(value) => write!(
+                            writer,
+                            "{}{}{}",
+                            &line.content[..*value_start],
+                            value,
+                            line.ending
+                        )?,
+                    }
+                }
+
+                LineKind::Item(item) => {
+                    if let Some(rendered) = current_items[section].get(*item) {
+                        let original = originals
+                            .get_mut(section)
+                            .and_then(|lines| lines.get_mut(rendered.as_str()))
+                            .and_then(|lines| lines.pop_front());
+
+                        match original {
+                            Some(content) => write!(writer, "{}{}", content, line.ending)?,
+                            None => write!(writer, "{}{}", rendered, line.ending)?,
+                        }
+                    }
+                }
+            }
+
+            if anchors.get(section) == Some(&index) {
+                let added = self.added_lines(section);
+
+                if !added.is_empty() && line.ending.is_empty() {
+                    write!(writer, "{}", newline)?;
+                }
+
+                for added_line in added {
+                    write!(writer, "{}{}", added_line, newline)?;
+                }
+            }
+        }
+
+        // sections that weren't in the file at all
+        for section in &SECTIONS {
+            if anchors.contains_key(section) {
+                continue;
+            }
+
+            let added = self.added_lines(section);
+            if added.is_empty() {
+                continue;
+            }
+
+            if self.lines.last().is_some_and(|line| line.ending.is_empty()) {
+                write!(writer, "{}", newline)?;
+            }
+
+            write!(writer, "{}[{}]{}", newline, section, newline)?;
+            for added_line in added {
+                write!(writer, "{}{}", added_line, newline)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    // keys and objects the file has no line for yet
+    fn added_lines(&self, section: &str) -> Vec<String> {
+        let mut in_file = HashSet::new();
+        let mut item_count = 0;
+
+        for line in self.lines.iter().filter(|line| line.section == section) {
+            match &line.kind {
+                LineKind::Pair { key, .. } => {
+                    in_file.insert(key.as_str());
+                }
+                LineKind::Item(_) => item_count += 1,
+                _ => {}
+            }
+        }
+
+        let separator = pair_separator(section);
+        let original = self.pairs.get(section);
+        let mut added: Vec<String> = self
+            .beatmap
+            .section_pairs(section)
+            .into_iter()
+            .filter(|(key, value)| {
+                !in_file.contains(key.as_str())
+                    && original.and_then(|pairs| pairs.get(key)) != Some(value)
+            })
+            .map(|(key, value)| format!("{}{}{}", key, separator, value))
+            .collect();
+
+        added.extend(
+            self.beatmap
+                .section_items(section)
+                .into_iter()
+                .skip(item_count),
+        );
+
+        added
+    }
+}
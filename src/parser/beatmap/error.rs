@@ -0,0 +1,167 @@
+use std::{error::Error, fmt, io, str::FromStr};
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    InvalidValue,
+    MissingValue,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub section: String,
+    pub field: String,
+    pub text: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Io(err) => write!(f, "could not read `{}`: {}", self.text, err),
+            ParseErrorKind::InvalidValue => write!(
+                f,
+                "line {} [{}]: invalid value for {}: `{}`",
+                self.line, self.section, self.field, self.text
+            ),
+            ParseErrorKind::MissingValue => write!(
+                f,
+                "line {} [{}]: missing value for {}: `{}`",
+                self.line, self.section, self.field, self.text
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ParseError {
+    pub fn io(err: io::Error, path: &str) -> Self {
+        ParseError {
+            kind: ParseErrorKind::Io(err),
+            line: 0,
+            section: "".to_string(),
+            field: "".to_string(),
+            text: path.to_string(),
+        }
+    }
+
+    // what lenient parsing does with this error instead of bailing out
+    pub fn into_warning(self) -> ParseWarning {
+        let kind = match self.section.as_str() {
+            "Events" | "TimingPoints" | "HitObjects" => ParseWarningKind::DroppedLine,
+            _ => ParseWarningKind::DefaultedValue,
+        };
+
+        ParseWarning {
+            kind,
+            line: self.line,
+            section: self.section,
+            field: self.field,
+            text: self.text,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseWarningKind {
+    DroppedLine,
+    DefaultedValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub line: usize,
+    pub section: String,
+    pub field: String,
+    pub text: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseWarningKind::DroppedLine => write!(
+                f,
+                "line {} [{}]: dropped line, bad {}: `{}`",
+                self.line, self.section, self.field, self.text
+            ),
+            ParseWarningKind::DefaultedValue => write!(
+                f,
+                "line {} [{}]: defaulted {}: `{}`",
+                self.line, self.section, self.field, self.text
+            ),
+        }
+    }
+}
+
+// position of the line currently being parsed, used to build errors
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LineContext<'a> {
+    pub number: usize,
+    pub section: &'a str,
+    pub text: &'a str,
+}
+
+impl<'a> LineContext<'a> {
+    pub fn error(&self, kind: ParseErrorKind, field: &str, text: &str) -> ParseError {
+        ParseError {
+            kind,
+            line: self.number,
+            section: self.section.to_string(),
+            field: field.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    pub fn warning(&self, kind: ParseWarningKind, field: &str, text: &str) -> ParseWarning {
+        ParseWarning {
+            kind,
+            line: self.number,
+            section: self.section.to_string(),
+            field: field.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    // "NaN" and "inf" parse as floats, but no field can hold them and they break the
+    // arithmetic done on the map later
+    pub fn parse<T: FromStr>(&self, field: &str, text: &str) -> Result<T, ParseError> {
+        let trimmed = text.trim();
+        if matches!(trimmed.parse::<f64>(), Ok(value) if !value.is_finite()) {
+            return Err(self.error(ParseErrorKind::InvalidValue, field, text));
+        }
+
+        trimmed
+            .parse()
+            .map_err(|_| self.error(ParseErrorKind::InvalidValue, field, text))
+    }
+
+    pub fn parse_bool(&self, field: &str, text: &str) -> Result<bool, ParseError> {
+        match text.trim() {
+            "1" => Ok(true),
+            "0" => Ok(false),
+            _ => Err(self.error(ParseErrorKind::InvalidValue, field, text)),
+        }
+    }
+
+    pub fn value<'b>(
+        &self,
+        values: &[&'b str],
+        index: usize,
+        field: &str,
+    ) -> Result<&'b str, ParseError> {
+        values
+            .get(index)
+            .copied()
+            .ok_or_else(|| self.error(ParseErrorKind::MissingValue, field, self.text))
+    }
+}
@@ -0,0 +1,78 @@
+use super::error::{LineContext, ParseError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Background {
+        filename: String,
+        x_offset: i32,
+        y_offset: i32,
+    },
+    Video {
+        start_time: f32,
+        filename: String,
+        x_offset: i32,
+        y_offset: i32,
+    },
+    Break(BreakPeriod),
+    // storyboard declarations and commands, kept verbatim
+    Raw(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreakPeriod {
+    pub start_time: f32,
+    pub end_time: f32,
+}
+
+impl BreakPeriod {
+    pub fn duration(&self) -> f32 {
+        self.end_time - self.start_time
+    }
+
+    pub fn contains(&self, time: f32) -> bool {
+        time >= self.start_time && time <= self.end_time
+    }
+}
+
+impl Event {
+    pub(crate) fn parse(line: &LineContext, time_offset: f32) -> Result<Event, ParseError> {
+        // storyboard commands are indented
+        if line.text.starts_with(' ') || line.text.starts_with('_') {
+            return Ok(Event::Raw(line.text.to_string()));
+        }
+
+        let values: Vec<&str> = line.text.split(',').map(|s| s.trim()).collect();
+        let offset = |index: usize, field: &str| -> Result<i32, ParseError> {
+            match values.get(index) {
+                Some(value) => line.parse(field, value),
+                None => Ok(0),
+            }
+        };
+
+        match values[0] {
+            "0" | "Background" => Ok(Event::Background {
+                filename: unquote(line.value(&values, 2, "filename")?),
+                x_offset: offset(3, "xOffset")?,
+                y_offset: offset(4, "yOffset")?,
+            }),
+            "1" | "Video" => Ok(Event::Video {
+                start_time: line.parse::<f32>("startTime", line.value(&values, 1, "startTime")?)?
+                    + time_offset,
+                filename: unquote(line.value(&values, 2, "filename")?),
+                x_offset: offset(3, "xOffset")?,
+                y_offset: offset(4, "yOffset")?,
+            }),
+            "2" | "Break" => Ok(Event::Break(BreakPeriod {
+                start_time: line.parse::<f32>("startTime", line.value(&values, 1, "startTime")?)?
+                    + time_offset,
+                end_time: line.parse::<f32>("endTime", line.value(&values, 2, "endTime")?)?
+                    + time_offset,
+            })),
+            _ => Ok(Event::Raw(line.text.to_string())),
+        }
+    }
+}
+
+pub(crate) fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
@@ -1,22 +1,48 @@
 use regex::Regex;
-use std::{fs, path::PathBuf};
+use std::{cmp::Ordering, fs, path::PathBuf};
 
 use crate::{
     constants,
     game::Gamemode,
     parser::beatmap::objects::{
-        CurveType, HitObject, HitObjectExtra, HitSample, HitType, InheritedTimingPoint, SliderData,
-        SliderObject, SliderObjectType, TimingPoint, TimingPointType, UninheritedTimingPoint,
+        Countdown, CurveType, HitObject, HitObjectExtra, HitSample, HitType, InheritedTimingPoint,
+        OverlayPosition, SampleSet, SliderData, SliderObject, SliderObjectType, TimingPoint,
+        TimingPointType, UninheritedTimingPoint,
     },
-    util::Vector2,
+    util::{Colour, Vector2},
 };
 
-use self::objects::SliderBody;
+use self::{
+    error::{LineContext, ParseError, ParseErrorKind, ParseWarning, ParseWarningKind},
+    events::{BreakPeriod, Event},
+    objects::SliderBody,
+};
 
 // exports
+pub mod attributes;
+pub mod document;
+pub mod error;
+pub mod events;
+mod mods;
 pub mod objects;
-
-#[derive(Debug)]
+mod stacking;
+mod writer;
+
+// osu! has room for this many combo colours, Combo1 to Combo8
+const MAX_COMBO_COLOURS: usize = 8;
+
+const TIMING_POINT_FIELDS: [&str; 8] = [
+    "time",
+    "beatLength",
+    "meter",
+    "sampleSet",
+    "sampleIndex",
+    "volume",
+    "uninherited",
+    "effects",
+];
+
+#[derive(Debug, Clone)]
 pub struct BeatmapFile {
     // internal metadata
     pub format_version: i32,
@@ -26,6 +52,8 @@ pub struct BeatmapFile {
     pub title_unicode: String,
     pub artist: String,
     pub artist_unicode: String,
+    pub creator: String,
+    pub source: String,
     pub gamemode: Gamemode,
 
     // timings
@@ -39,26 +67,66 @@ pub struct BeatmapFile {
 
     // objects
     pub hit_objects: Vec<HitObject>,
+    pub events: Vec<Event>,
 
     // general metadata
+    pub general: GeneralMetadata,
     pub audio: AudioMetadata,
     pub difficulty: DifficultyMetadata,
     pub metadata: Metadata,
+    pub editor: EditorSettings,
+    pub colours: Colours,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Metadata {
     pub tags: Vec<String>,
     pub preview_time: i32,
+    pub beatmap_id: Option<i64>,
+    pub beatmapset_id: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneralMetadata {
+    pub countdown: Countdown,
+    pub countdown_offset: i32,
+    pub letterbox_in_breaks: bool,
+    pub story_fire_in_front: bool,
+    pub use_skin_sprites: bool,
+    pub always_show_playfield: bool,
+    pub overlay_position: OverlayPosition,
+    pub skin_preference: String,
+    pub epilepsy_warning: bool,
+    pub special_style: bool,
+    pub widescreen_storyboard: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AudioMetadata {
     pub filename: String,
-    pub lead_in: i32
+    pub lead_in: i32,
+    pub hash: String,
+    pub sample_set: SampleSet,
+    pub samples_match_playback_rate: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditorSettings {
+    pub bookmarks: Vec<i32>,
+    pub distance_spacing: f32,
+    pub beat_divisor: i32,
+    pub grid_size: i32,
+    pub timeline_zoom: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Colours {
+    pub combo_colours: Vec<Colour>,
+    pub slider_track_override: Option<Colour>,
+    pub slider_border: Option<Colour>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DifficultyMetadata {
     pub hp_drain: f32,
     pub circle_size: f32,
@@ -79,6 +147,8 @@ impl Default for BeatmapFile {
             title_unicode: "".to_string(),
             artist: "".to_string(),
             artist_unicode: "".to_string(),
+            creator: "".to_string(),
+            source: "".to_string(),
             gamemode: Gamemode::Standard,
 
             // difficulty metadata
@@ -87,6 +157,7 @@ impl Default for BeatmapFile {
 
             // objects
             hit_objects: vec![],
+            events: vec![],
 
             // timings
             timing_points: vec![],
@@ -94,9 +165,25 @@ impl Default for BeatmapFile {
             inherited_points: vec![],
 
             // general metadata
+            general: GeneralMetadata {
+                countdown: Countdown::Normal,
+                countdown_offset: 0,
+                letterbox_in_breaks: false,
+                story_fire_in_front: true,
+                use_skin_sprites: false,
+                always_show_playfield: false,
+                overlay_position: OverlayPosition::NoChange,
+                skin_preference: "".to_string(),
+                epilepsy_warning: false,
+                special_style: false,
+                widescreen_storyboard: false,
+            },
             audio: AudioMetadata {
                 filename: "".to_string(),
-                lead_in: 0
+                lead_in: 0,
+                hash: "".to_string(),
+                sample_set: SampleSet::Normal,
+                samples_match_playback_rate: false,
             },
             difficulty: DifficultyMetadata {
                 hp_drain: 0.0,
@@ -109,6 +196,20 @@ impl Default for BeatmapFile {
             metadata: Metadata {
                 tags: vec![],
                 preview_time: 0,
+                beatmap_id: None,
+                beatmapset_id: None,
+            },
+            editor: EditorSettings {
+                bookmarks: vec![],
+                distance_spacing: 1.0,
+                beat_divisor: 4,
+                grid_size: 4,
+                timeline_zoom: 1.0,
+            },
+            colours: Colours {
+                combo_colours: vec![],
+                slider_track_override: None,
+                slider_border: None,
             },
         }
     }
@@ -116,1057 +217,964 @@ impl Default for BeatmapFile {
 
 impl BeatmapFile {
     pub fn from_file(path: &str) -> BeatmapFile {
-        BeatmapFile::from_str(&fs::read_to_string(&path).unwrap())
+        BeatmapFile::try_from_file(path).unwrap_or_else(|e| panic!("{}", e))
     }
 
     pub fn from_pathbuf(path: PathBuf) -> BeatmapFile {
-        BeatmapFile::from_str(&fs::read_to_string(path).unwrap())
+        BeatmapFile::try_from_pathbuf(path).unwrap_or_else(|e| panic!("{}", e))
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(map_string: &str) -> BeatmapFile {
+        BeatmapFile::try_from_str(map_string).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_file(path: &str) -> Result<BeatmapFile, ParseError> {
+        let map_string = fs::read_to_string(path).map_err(|e| ParseError::io(e, path))?;
+        BeatmapFile::try_from_str(&map_string)
+    }
+
+    pub fn try_from_pathbuf(path: PathBuf) -> Result<BeatmapFile, ParseError> {
+        let map_string =
+            fs::read_to_string(&path).map_err(|e| ParseError::io(e, &path.to_string_lossy()))?;
+        BeatmapFile::try_from_str(&map_string)
+    }
+
+    pub fn try_from_str(map_string: &str) -> Result<BeatmapFile, ParseError> {
+        BeatmapFile::parse(map_string, false).map(|(beatmap, _)| beatmap)
+    }
+
+    // skips or defaults anything that fails to parse, reporting it as a warning
+    pub fn from_str_lenient(map_string: &str) -> (BeatmapFile, Vec<ParseWarning>) {
+        match BeatmapFile::parse(map_string, true) {
+            Ok(result) => result,
+            Err(err) => (BeatmapFile::default(), vec![err.into_warning()]),
+        }
+    }
+
+    fn parse(
+        map_string: &str,
+        lenient: bool,
+    ) -> Result<(BeatmapFile, Vec<ParseWarning>), ParseError> {
         // begin parse
-        let lines = map_string.lines();
+        let lines = map_string.trim_start_matches('\u{feff}').lines();
         let mut section = "";
 
         // regex
-        let section_regex = Regex::new(r"\[(\w+)\]").unwrap();
-        let kvp_regex = Regex::new(r"(\w+):\s*(.*)").unwrap();
+        let kvp_regex = Regex::new(r"(\w+)\s*:\s*(.*)").unwrap();
 
         // empty bm
         let mut beatmap = BeatmapFile::default();
-        let mut timing_point = UninheritedTimingPoint {
-            time: 0.0,
-            beat_length: 0.0,
-            time_signature: 4,
-        };
+        let mut warnings = vec![];
 
         // iterate through
-        for s in lines {
-            if s.starts_with("//") {
+        for (index, s) in lines.enumerate() {
+            let s = s.trim_end();
+
+            if s.is_empty() || s.starts_with("//") {
                 // ignore comments
                 continue;
             }
 
-            if s.starts_with("osu file format v") {
-                // version
-                beatmap.format_version = s[17..].parse().unwrap();
-            }
+            let line = LineContext {
+                number: index + 1,
+                section,
+                text: s,
+            };
 
-            if section_regex.is_match(&s) {
-                section = &s[1..&s.len() - 1];
+            let result = if let Some(version) = s.strip_prefix("osu file format v") {
+                // version
+                line.parse("format_version", version)
+                    .map(|version| beatmap.format_version = version)
+            } else if s.starts_with('[') && s.ends_with(']') {
+                section = s[1..s.len() - 1].trim();
                 continue;
-            }
-
-            match section {
-                "General" => {
-                    // general section
-                    for cap in kvp_regex.captures_iter(&s) {
-                        // read value
-                        let value = &cap[2];
-                        match &cap[1] {
-                            "AudioFilename" => beatmap.audio.filename = value.clone().to_string(),
-                            "AudioLeadIn" => beatmap.audio.lead_in = value.clone().parse().unwrap_or(0),
-                            "PreviewTime" => {
-                                beatmap.metadata.preview_time = value.clone().parse().unwrap()
-                            }
-                            "Mode" => beatmap.gamemode = value.clone().parse().unwrap(),
-                            "StackLeniency" => beatmap.stack_leniency = value.clone().parse().unwrap(),
-                            _ => continue,
+            } else {
+                match section {
+                    "General" | "Editor" | "Difficulty" | "Metadata" | "Colours" => {
+                        match kvp_regex.captures(s) {
+                            Some(cap) => beatmap.parse_pair(&line, &cap[1], cap[2].trim()),
+                            None => Ok(()),
                         }
                     }
+                    "Events" => beatmap.parse_event(&line),
+                    "TimingPoints" => beatmap.parse_timing_point(&line, &mut warnings),
+                    "HitObjects" => beatmap.parse_hit_object(&line, &mut warnings),
+                    _ => continue,
                 }
+            };
 
-                "Difficulty" => {
-                    // difficulty section
-                    for cap in kvp_regex.captures_iter(&s) {
-                        // read value
-                        let value = &cap[2];
-                        match &cap[1] {
-                            "HPDrainRate" => {
-                                beatmap.difficulty.hp_drain = value.clone().parse().unwrap()
-                            }
-                            "CircleSize" => {
-                                beatmap.difficulty.circle_size = value.clone().parse().unwrap()
-                            }
-                            "OverallDifficulty" => {
-                                beatmap.difficulty.overall_difficulty =
-                                    value.clone().parse().unwrap()
-                            }
-                            "ApproachRate" => {
-                                beatmap.difficulty.approach_rate = value.clone().parse().unwrap()
-                            }
-                            "SliderMultiplier" => {
-                                beatmap.difficulty.slider_multiplier =
-                                    value.clone().parse().unwrap()
-                            }
-                            "SliderTickRate" => {
-                                beatmap.difficulty.slider_tickrate = value.clone().parse().unwrap()
-                            }
-                            _ => continue,
-                        }
-                    }
+            if let Err(err) = result {
+                if !lenient {
+                    return Err(err);
                 }
 
-                "Metadata" => {
-                    for cap in kvp_regex.captures_iter(&s) {
-                        // read value
-                        let value = &cap[2];
-                        match &cap[1] {
-                            "Title" => beatmap.title = value.clone().to_string(),
-                            "TitleUnicode" => beatmap.title_unicode = value.clone().to_string(),
-
-                            "Artist" => beatmap.artist = value.clone().to_string(),
-                            "ArtistUnicode" => beatmap.artist_unicode = value.clone().to_string(),
-
-                            "Version" => beatmap.difficulty_name = value.clone().to_string(),
-                            "Tags" => {
-                                beatmap.metadata.tags = value
-                                    .clone()
-                                    .split_whitespace()
-                                    .map(|s| s.to_string())
-                                    .collect()
-                            }
-                            _ => continue,
-                        }
-                    }
-                }
+                warnings.push(err.into_warning());
+            }
+        }
 
-                "TimingPoints" => {
-                    let values: Vec<String> = s.clone().split(",").map(|s| s.to_string()).collect();
+        beatmap.apply_combos();
+        beatmap.apply_stacking();
 
-                    if (values.len() as i32) < 2 {
-                        continue;
-                    }
+        Ok((beatmap, warnings)) // return beatmap
+    }
+
+    fn parse_pair(&mut self, line: &LineContext, key: &str, value: &str) -> Result<(), ParseError> {
+        match line.section {
+            "General" => self.parse_general(line, key, value),
+            "Editor" => self.parse_editor(line, key, value),
+            "Difficulty" => self.parse_difficulty(line, key, value),
+            "Metadata" => self.parse_metadata(line, key, value),
+            "Colours" => self.parse_colour(line, key, value),
+            _ => Ok(()),
+        }
+    }
 
-                    if let Some(_val) = values.get(values.len() - 1) {
-                        let mut time: f32 = values[0].parse().unwrap_or(0.0);
+    fn parse_general(
+        &mut self,
+        line: &LineContext,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        match key {
+            // audio
+            "AudioFilename" => self.audio.filename = value.to_string(),
+            "AudioLeadIn" => self.audio.lead_in = line.parse(key, value)?,
+            "AudioHash" => self.audio.hash = value.to_string(),
+            "SampleSet" => self.audio.sample_set = line.parse(key, value)?,
+            "SamplesMatchPlaybackRate" => {
+                self.audio.samples_match_playback_rate = line.parse_bool(key, value)?
+            }
 
-                        if beatmap.format_version < 5 {
-                            time = time + 24.0;
-                        }
+            // gameplay
+            "PreviewTime" => self.metadata.preview_time = line.parse(key, value)?,
+            "Mode" => self.gamemode = line.parse(key, value)?,
+            "StackLeniency" => self.stack_leniency = line.parse(key, value)?,
+            "Countdown" => self.general.countdown = line.parse(key, value)?,
+            "CountdownOffset" => self.general.countdown_offset = line.parse(key, value)?,
+            "SpecialStyle" => self.general.special_style = line.parse_bool(key, value)?,
+            "EpilepsyWarning" => self.general.epilepsy_warning = line.parse_bool(key, value)?,
+
+            // presentation
+            "LetterboxInBreaks" => {
+                self.general.letterbox_in_breaks = line.parse_bool(key, value)?
+            }
+            "StoryFireInFront" => self.general.story_fire_in_front = line.parse_bool(key, value)?,
+            "UseSkinSprites" => self.general.use_skin_sprites = line.parse_bool(key, value)?,
+            "AlwaysShowPlayfield" => {
+                self.general.always_show_playfield = line.parse_bool(key, value)?
+            }
+            "OverlayPosition" => self.general.overlay_position = line.parse(key, value)?,
+            "SkinPreference" => self.general.skin_preference = value.to_string(),
+            "WidescreenStoryboard" => {
+                self.general.widescreen_storyboard = line.parse_bool(key, value)?
+            }
 
-                        let beat_length: f32 = values[1].parse().unwrap_or(0.0);
-                        let mut time_signature = 4;
-
-                        let mut timing_change = false;
-                        let change = values[2].parse::<i32>().unwrap_or(0);
-                        if values.len() >= 3 {
-                            if change == 0 {
-                                time_signature = 4;
-                            } else {
-                                time_signature = values[2].parse().unwrap_or(0);
-                            }
-                        }
+            // editor settings lived in [General] before [Editor] existed
+            "EditorBookmarks" => self.parse_editor(line, "Bookmarks", value)?,
+            "EditorDistanceSpacing" => self.parse_editor(line, "DistanceSpacing", value)?,
+            _ => {}
+        }
 
-                        if values.len() >= 7 {
-                            let timing_change_num = values[6].parse().unwrap_or(0);
-                            timing_change = timing_change_num == 1;
-                        }
+        Ok(())
+    }
 
-                        if timing_change == true {
-                            beatmap.uninherited_points.push(UninheritedTimingPoint {
-                                time: time,
-                                beat_length: beat_length,
-                                time_signature: time_signature,
-                            });
-
-                            beatmap.timing_points.push(TimingPoint {
-                                time: time,
-                                beat_length: beat_length,
-                                time_signature: time_signature,
-                                speed_multiplier: if beat_length < 0.0 {
-                                    100.0 / (-beat_length)
-                                } else {
-                                    1.0
-                                },
-                                point_type: TimingPointType::Uninherited,
-                            });
-                        } else {
-                            beatmap.inherited_points.push(InheritedTimingPoint {
-                                time: time,
-                                speed_multiplier: if beat_length < 0.0 {
-                                    100.0 / (-beat_length)
-                                } else {
-                                    1.0
-                                },
-                                inherited_from: timing_point.clone(),
-                            });
-
-                            beatmap.timing_points.push(TimingPoint {
-                                time: time,
-                                beat_length: beat_length,
-                                time_signature: time_signature,
-                                speed_multiplier: if beat_length < 0.0 {
-                                    100.0 / (-beat_length)
-                                } else {
-                                    1.0
-                                },
-                                point_type: TimingPointType::Inherited,
-                            });
-                        }
+    fn parse_editor(
+        &mut self,
+        line: &LineContext,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        match key {
+            "Bookmarks" => {
+                self.editor.bookmarks = value
+                    .split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(|s| line.parse(key, s))
+                    .collect::<Result<_, _>>()?
+            }
+            "DistanceSpacing" => self.editor.distance_spacing = line.parse(key, value)?,
+            "BeatDivisor" => self.editor.beat_divisor = line.parse(key, value)?,
+            "GridSize" => self.editor.grid_size = line.parse(key, value)?,
+            "TimelineZoom" => self.editor.timeline_zoom = line.parse(key, value)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn parse_difficulty(
+        &mut self,
+        line: &LineContext,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        match key {
+            "HPDrainRate" => self.difficulty.hp_drain = line.parse(key, value)?,
+            "CircleSize" => self.difficulty.circle_size = line.parse(key, value)?,
+            "OverallDifficulty" => self.difficulty.overall_difficulty = line.parse(key, value)?,
+            "ApproachRate" => self.difficulty.approach_rate = line.parse(key, value)?,
+            "SliderMultiplier" => self.difficulty.slider_multiplier = line.parse(key, value)?,
+            "SliderTickRate" => self.difficulty.slider_tickrate = line.parse(key, value)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn parse_metadata(
+        &mut self,
+        line: &LineContext,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        match key {
+            "Title" => self.title = value.to_string(),
+            "TitleUnicode" => self.title_unicode = value.to_string(),
+
+            "Artist" => self.artist = value.to_string(),
+            "ArtistUnicode" => self.artist_unicode = value.to_string(),
+
+            "Creator" => self.creator = value.to_string(),
+            "Source" => self.source = value.to_string(),
+
+            "Version" => self.difficulty_name = value.to_string(),
+            "BeatmapID" => self.metadata.beatmap_id = BeatmapFile::parse_id(line, key, value)?,
+            "BeatmapSetID" => {
+                self.metadata.beatmapset_id = BeatmapFile::parse_id(line, key, value)?
+            }
+            "Tags" => {
+                self.metadata.tags = value.split_whitespace().map(|s| s.to_string()).collect()
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn parse_colour(
+        &mut self,
+        line: &LineContext,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        match key {
+            "SliderTrackOverride" => {
+                self.colours.slider_track_override = Some(line.parse(key, value)?)
+            }
+            "SliderBorder" => self.colours.slider_border = Some(line.parse(key, value)?),
+            _ => {
+                if let Some(number) = key.strip_prefix("Combo") {
+                    // combo colours are numbered from 1 and may come in any order, osu!
+                    // only has room for 8 of them
+                    let index = line.parse::<usize>(key, number)?;
+                    if !(1..=MAX_COMBO_COLOURS).contains(&index) {
+                        return Err(line.error(ParseErrorKind::InvalidValue, key, number));
                     }
-                }
 
-                "HitObjects" => {
-                    // oh no
-                    let values: Vec<String> = s.clone().split(",").map(|s| s.to_string()).collect();
-                    let mut base = HitObject {
-                        x: values[0].parse().unwrap_or(0.0),
-                        y: values[1].parse().unwrap_or(0.0),
-                        position: Vector2::new(
-                            values[0].parse().unwrap_or(0.0),
-                            values[1].parse().unwrap_or(0.0),
-                        ),
-                        end_position: Vector2::new(0.0, 0.0),
-                        start_time: values[2].parse().unwrap_or(0.0),
-                        end_time: 0.0,
-                        hit_sound: values[4].parse().unwrap_or(0),
-                        hit_type: values[3].parse().unwrap_or(1),
-                        stack_height: 0,
-                        slider_data: None,
-                        slider_objects: None,
-                        extra_data: None,
-                    };
-
-                    if let Some(val) = values.get(values.len() - 1) {
-                        if val.contains(":") {
-                            if (base.hit_type & HitType::Hold as i32) != 0 {
-                                // mania hold
-                                let t: Vec<String> =
-                                    val.clone().split(":").map(|s| s.to_string()).collect();
-
-                                // set extra data
-                                base.end_time = t[0].parse().unwrap_or(0.0);
-                                base.extra_data = Some(HitObjectExtra {
-                                    hit_sample: HitSample {
-                                        normal_set: t[1].parse().unwrap_or(0),
-                                        additional_set: t[2].parse().unwrap_or(0),
-                                        index: t[3].parse().unwrap_or(0),
-                                        volume: t[4].parse().unwrap_or(0),
-                                        file_name: t[5].clone(),
-                                    },
-                                });
-                            } else {
-                                // normal hitcircle
-                                let t: Vec<String> =
-                                    val.clone().split(":").map(|s| s.to_string()).collect();
-
-                                // set extra data
-                                base.extra_data = Some(HitObjectExtra {
-                                    hit_sample: HitSample {
-                                        normal_set: t[0].parse().unwrap_or(0),
-                                        additional_set: t[1].parse().unwrap_or(0),
-                                        index: t[2].parse().unwrap_or(0),
-                                        volume: t[3].parse().unwrap_or(0),
-                                        file_name: t[4].clone(),
-                                    },
-                                });
-                            }
-                        }
+                    let index = index - 1;
+                    let colour = line.parse(key, value)?;
+                    let colours = &mut self.colours.combo_colours;
+
+                    if index >= colours.len() {
+                        colours.resize(index + 1, colour);
                     }
+                    colours[index] = colour;
+                }
+            }
+        }
 
-                    // slider information
-                    if base.hit_type & (HitType::Slider as i32) != 0 {
-                        let slider_data = values.get(5).unwrap(); // has to have slider stuff
-                        let slider_split: Vec<String> =
-                            slider_data.split("|").map(|s| s.to_string()).collect();
-
-                        // setup base
-                        let mut slider_base = SliderData {
-                            curve_type: slider_split[0].parse().unwrap_or(CurveType::Catmull),
-                            base_points: vec![],
-                            slider_points: vec![Vector2::new(0.0, 0.0)],
-                            slider_body: SliderBody {
-                                body: vec![],
-                                length: vec![0.0],
-                            },
-                        };
-
-                        for point in slider_split {
-                            if point.contains(":") {
-                                // sliderpoint
-                                let point_data: Vec<String> =
-                                    point.split(":").map(|s| s.to_string()).collect();
-
-                                slider_base.base_points.push(Vector2::new(
-                                    point_data[0].parse().unwrap_or(0.0),
-                                    point_data[1].parse().unwrap_or(0.0),
-                                ));
-                                slider_base.slider_points.push(
-                                    Vector2::new(
-                                        point_data[0].parse().unwrap_or(0.0),
-                                        point_data[1].parse().unwrap_or(0.0),
-                                    ) - Vector2::new(base.x, base.y),
-                                );
-                            }
-                        }
+        Ok(())
+    }
 
-                        // check if point is linear
-                        if slider_base.slider_points.len() == 3 // length is at least 3
-                            && slider_base.curve_type as i32 == CurveType::PerfectCurve as i32
-                        // is a perfect curve
-                        {
-                            // precision check
-                            let point1 = &slider_base.slider_points[0];
-                            let point2 = &slider_base.slider_points[1];
-                            let point3 = &slider_base.slider_points[2];
-
-                            let is_linear = f32::abs(
-                                0.0 - ((point2.y - point1.y) * (point3.x - point1.x)
-                                    - (point2.x - point1.x) * (point3.y - point1.y)),
-                            ) <= 0.001;
-
-                            if is_linear == true {
-                                // this is linear
-                                slider_base.curve_type = CurveType::Linear;
-                            }
-                        }
+    // unsubmitted maps use -1 in place of an id
+    fn parse_id(line: &LineContext, key: &str, value: &str) -> Result<Option<i64>, ParseError> {
+        let id: i64 = line.parse(key, value)?;
+        Ok(if id == -1 { None } else { Some(id) })
+    }
 
-                        let mut slider_length = 0.0;
-                        let mut repeat_count: i32 = values[6].parse().unwrap();
-                        repeat_count = i32::max(0, repeat_count - 1);
+    fn parse_event(&mut self, line: &LineContext) -> Result<(), ParseError> {
+        let time_offset = if self.format_version < 5 { 24.0 } else { 0.0 };
 
-                        if values.len() > 7 {
-                            // slider length
-                            slider_length = values[7].parse().unwrap_or(0.0);
-                        }
+        self.events.push(Event::parse(line, time_offset)?);
+        Ok(())
+    }
 
-                        // handle slider body
-                        let expected_distance = f32::max(0.0, slider_length);
-                        let mut slider_start = 0;
-                        let mut slider_end = 0;
-
-                        for (i, _slider_point) in
-                            slider_base.slider_points.clone().into_iter().enumerate()
-                        {
-                            slider_end += 1;
-
-                            if i == &slider_base.slider_points.len() - 1
-                                || (slider_base.slider_points[i].x
-                                    == slider_base.slider_points[i + 1].x
-                                    && slider_base.slider_points[i].y
-                                        == slider_base.slider_points[i + 1].y)
-                            {
-                                // get a specific vector
-                                let sub_path =
-                                    slider_base.slider_points[slider_start..slider_end].to_vec();
-                                let mut approximated_path;
-
-                                // approximate subpath
-                                // TODO: impl partialeq for curvetype
-                                if slider_base.curve_type as i32 == CurveType::Linear as i32 {
-                                    approximated_path = sub_path.clone();
-                                } else if slider_base.curve_type as i32
-                                    == CurveType::PerfectCurve as i32
-                                {
-                                    if slider_base.slider_points.len() != 3 || sub_path.len() != 3 {
-                                        approximated_path =
-                                            BeatmapFile::approximate_bezier(&sub_path);
-                                    } else {
-                                        approximated_path =
-                                            BeatmapFile::approximate_perfect_curve(&sub_path);
-
-                                        if approximated_path.len() == 0 {
-                                            approximated_path =
-                                                BeatmapFile::approximate_bezier(&sub_path);
-                                        }
-                                    }
-                                } else if slider_base.curve_type as i32 == CurveType::Catmull as i32
-                                {
-                                    approximated_path = BeatmapFile::approximate_catmull(&sub_path)
-                                } else {
-                                    approximated_path = BeatmapFile::approximate_bezier(&sub_path);
-                                }
-
-                                // add to slider body
-                                for point in &approximated_path {
-                                    // if (this.calculatedPath.length === 0 || this.calculatedPath[this.calculatedPath.length - 1].x !== t.x || this.calculatedPath[this.calculatedPath.length - 1].y !== t.y) {
-                                    if &slider_base.slider_body.body.len() == &0
-                                        || &slider_base.slider_body.body
-                                            [slider_base.slider_body.body.len() - 1]
-                                            .x
-                                            != &point.x
-                                        || &slider_base.slider_body.body
-                                            [slider_base.slider_body.body.len() - 1]
-                                            .y
-                                            != &point.y
-                                    {
-                                        &slider_base.slider_body.body.push(*point);
-                                    }
-                                }
-
-                                slider_start = slider_end
-                            }
-                        }
+    fn parse_timing_point(
+        &mut self,
+        line: &LineContext,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), ParseError> {
+        let values: Vec<&str> = line.text.split(',').map(|s| s.trim()).collect();
 
-                        // calculate path length
-                        let mut length = 0.0;
-                        for i in 0..slider_base.slider_body.body.len() - 1 {
-                            let point = slider_base.slider_body.body[i];
-                            let difference =
-                                *slider_base.slider_body.body.get(i + 1).unwrap() - point;
-                            let diff = difference.len();
-
-                            if (expected_distance - length) < diff {
-                                // :desolate:
-                                slider_base.slider_body.body[i + 1] =
-                                    point + point.scale((expected_distance - length) / diff);
-
-                                // calculate drain
-                                let index = i + 2;
-                                let drain_amount = index
-                                    ..(slider_base.slider_body.body.len()
-                                        - (slider_base.slider_body.body.len() - 2 - i));
-                                slider_base.slider_body.body.drain(drain_amount);
-
-                                length = expected_distance;
-                                slider_base.slider_body.length.push(length);
-                                break;
-                            }
-
-                            length += diff;
-                            slider_base.slider_body.length.push(length);
-                        }
+        if values.len() < 2 {
+            return Err(line.error(ParseErrorKind::MissingValue, "beatLength", line.text));
+        }
 
-                        let cloned_body = slider_base.slider_body.body.clone();
-                        let cloned_len = slider_base.slider_body.length.clone();
-                        if length < expected_distance && cloned_body.len() > 1 {
-                            let difference = *slider_base
-                                .slider_body
-                                .body
-                                .get(cloned_body.len() - 1)
-                                .unwrap()
-                                - *slider_base
-                                    .slider_body
-                                    .body
-                                    .get(cloned_body.len() - 2)
-                                    .unwrap();
-                            let diff = difference.len();
-
-                            if diff <= 0.0 {
-                                // do nothing
-                            } else {
-                                slider_base.slider_body.body[cloned_body.len() - 1] =
-                                    slider_base.slider_body.body[cloned_body.len() - 1]
-                                        + difference.scale((expected_distance - length) / diff);
-                                slider_base.slider_body.length[cloned_len.len() - 1] =
-                                    expected_distance;
-                            }
-                        }
+        if values.len() < 8 {
+            // older maps leave off the trailing fields, osu! defaults them
+            warnings.push(line.warning(
+                ParseWarningKind::DefaultedValue,
+                TIMING_POINT_FIELDS[values.len()],
+                line.text,
+            ));
+        }
 
-                        // slider body obtained
-                        base.slider_data = Some(slider_base);
-
-                        // handle slider data //
-                        let distance = f32::min(f32::max(1.0, 0.0), 1.0) * expected_distance;
-                        let mut distance_index = cloned_len
-                            .iter()
-                            .position(|&f| f == distance)
-                            .unwrap_or(usize::MAX); // unsafe as fuck, but i cant use -1.
-
-                        // calculate ending position
-                        let mut returned = false;
-                        if distance_index == usize::MAX {
-                            for i in 0..cloned_len.len() {
-                                if cloned_len[i] > distance {
-                                    distance_index = i;
-                                    returned = true;
-                                    break;
-                                }
-                            }
-
-                            if returned == false {
-                                distance_index = cloned_len.len() - 1;
-                            }
-                        }
+        let mut time: f32 = line.parse("time", values[0])?;
 
-                        // interpolate slider vertices
-                        let mut vertice = Vector2::new(0.0, 0.0);
-
-                        if distance_index <= 0 {
-                            vertice = cloned_body[0];
-                        } else if distance_index >= cloned_body.len() {
-                            vertice = cloned_body[cloned_body.len() - 1];
-                        } else {
-                            let start = cloned_body[distance_index - 1];
-                            let end = cloned_body[distance_index];
-
-                            let distance_start = cloned_len[distance_index - 1];
-                            let distance_end = cloned_len[distance_index];
-
-                            if f32::abs(distance_start - distance_end)
-                                <= constants::PRECISION_LENIENCE
-                            {
-                                vertice = start
-                            } else {
-                                let scale =
-                                    (distance - distance_start) / (distance_end - distance_start);
-                                vertice = start + ((end - start).scale(scale));
-                            }
-                        }
+        if self.format_version < 5 {
+            time += 24.0;
+        }
 
-                        // calculate and setend position
-                        base.end_position = base.position + vertice;
-
-                        // calculate slider timing data
-                        let timing_point = beatmap.get_timing_point(base.start_time);
-                        let scoring_distance = 100.0
-                            * beatmap.difficulty.slider_multiplier
-                            * timing_point.speed_multiplier;
-                        let velocity = scoring_distance / timing_point.beat_length;
-                        let span_count = repeat_count + 1;
-                        let tick_distance =
-                            scoring_distance / beatmap.difficulty.slider_tickrate * 1.0;
-                        let end_time =
-                            base.start_time + (span_count as f32 * expected_distance / velocity);
-                        let duration = end_time - base.start_time;
-
-                        base.end_time = end_time;
-
-                        // create slider hitobjects
-                        let mut hitobjects = vec![];
-
-                        // slider head & end
-                        hitobjects.push(SliderObject {
-                            x: base.position.x,
-                            y: base.position.y,
-                            position: base.position,
-                            start_time: base.start_time,
-                            span_index: 0,
-                            repeat_index: 0,
-                            span_start_time: 0.0,
-                            slider_object_type: SliderObjectType::SliderHead,
-                        });
-
-                        // create slider ticks
-                        let length = f32::min(100000.0, expected_distance);
-                        let certified_tick_distance =
-                            f32::min(f32::max(tick_distance, 0.0), length);
-
-                        if certified_tick_distance == 0.0 {
-                            // do nothing
-                        } else {
-                            let min_distance_from_end = velocity * 10.0;
-                            let span_duration = duration / span_count as f32;
-
-                            for span in 0..span_count {
-                                let span_start = base.start_time + span as f32 * span_duration;
-                                let reversed = span % 2 == 1;
-
-                                let mut d = tick_distance;
-                                while d < length - min_distance_from_end {
-                                    if d > length - min_distance_from_end {
-                                        break;
-                                    }
-                                    let progress = d / length;
-                                    let time_progress = if reversed == true {
-                                        1.0 - progress
-                                    } else {
-                                        progress
-                                    };
-
-                                    // calculate tick position
-                                    let distance =
-                                        f32::min(f32::max(progress, 0.0), 1.0) * expected_distance;
-                                    let mut index = cloned_len
-                                        .iter()
-                                        .position(|&f| f == distance)
-                                        .unwrap_or(usize::MAX);
-                                    let mut returned = false;
-                                    if index == usize::MAX {
-                                        for i in 0..cloned_len.len() {
-                                            if cloned_len[i] > distance {
-                                                index = i;
-                                                returned = true;
-                                                break;
-                                            }
-                                        }
-
-                                        if returned == false {
-                                            index = cloned_len.len() - 1;
-                                        }
-                                    }
-                                    // interpolate slider vertices
-                                    let mut vertice = Vector2::new(0.0, 0.0);
-
-                                    if index <= 0 {
-                                        vertice = cloned_body[0];
-                                    } else if index >= cloned_body.len() {
-                                        vertice = cloned_body[cloned_body.len() - 1];
-                                    } else {
-                                        let start = cloned_body[index - 1];
-                                        let end = cloned_body[index];
-
-                                        let distance_start = cloned_len[index - 1];
-                                        let distance_end = cloned_len[index];
-
-                                        if f32::abs(distance_start - distance_end)
-                                            <= constants::PRECISION_LENIENCE
-                                        {
-                                            vertice = start
-                                        } else {
-                                            let scale = (distance - distance_start)
-                                                / (distance_end - distance_start);
-                                            vertice = start + ((end - start).scale(scale));
-                                        }
-                                    }
-                                    let tick_position = base.position + vertice;
-
-                                    hitobjects.push(SliderObject {
-                                        x: tick_position.x,
-                                        y: tick_position.y,
-                                        position: tick_position,
-                                        start_time: span_start + time_progress * span_duration,
-                                        span_index: span,
-                                        repeat_index: 0,
-                                        span_start_time: span_start,
-                                        slider_object_type: SliderObjectType::SliderTick,
-                                    });
-
-                                    d += tick_distance;
-                                }
-                            }
-                        }
+        let beat_length: f32 = line.parse("beatLength", values[1])?;
+        let mut time_signature = 4;
+        let mut timing_change = true;
 
-                        // parse repeat points
-                        let mut repeat = 1.0;
-                        let mut repeat_index = 0;
-                        while repeat_index < repeat_count {
-                            let distance =
-                                f32::min(f32::max(repeat % 2.0, 0.0), 1.0) * expected_distance;
-                            let mut index = cloned_len
-                                .iter()
-                                .position(|&f| f == distance)
-                                .unwrap_or(usize::MAX);
-                            let mut returned = false;
-                            if index == usize::MAX {
-                                for i in 0..cloned_len.len() {
-                                    if cloned_len[i] > distance {
-                                        index = i;
-                                        returned = true;
-                                        break;
-                                    }
-                                }
-
-                                if returned == false {
-                                    index = cloned_len.len() - 1;
-                                }
-                            }
-                            // interpolate slider vertices
-                            let mut vertice = Vector2::new(0.0, 0.0);
-
-                            if index <= 0 {
-                                vertice = cloned_body[0];
-                            } else if index >= cloned_body.len() {
-                                vertice = cloned_body[cloned_body.len() - 1];
-                            } else {
-                                let start = cloned_body[index - 1];
-                                let end = cloned_body[index];
-
-                                let distance_start = cloned_len[index - 1];
-                                let distance_end = cloned_len[index];
-
-                                if f32::abs(distance_start - distance_end)
-                                    <= constants::PRECISION_LENIENCE
-                                {
-                                    vertice = start
-                                } else {
-                                    let scale = (distance - distance_start)
-                                        / (distance_end - distance_start);
-                                    vertice = start + ((end - start).scale(scale));
-                                }
-                            }
-                            
-                            let repeat_position = base.position + vertice;
-                            let span_duration = duration / span_count as f32;
-
-                            hitobjects.push(SliderObject {
-                                x: repeat_position.x,
-                                y: repeat_position.y,
-                                position: repeat_position,
-                                start_time: base.start_time + (repeat * span_duration),
-                                span_index: 0,
-                                repeat_index: repeat_index,
-                                span_start_time: span_duration,
-                                slider_object_type: SliderObjectType::SliderTick,
-                            });
-
-                            repeat_index += 1;
-                            repeat += 1.0;
-                        }
+        if let Some(meter) = values.get(2) {
+            let change = meter.parse::<i32>().unwrap_or(0);
+            if change != 0 {
+                time_signature = change;
+            }
+        }
 
-                        // add sliderend
-                        hitobjects.push(SliderObject {
-                            x: base.end_position.x,
-                            y: base.end_position.y,
-                            position: base.end_position,
-                            start_time: f32::max(base.start_time + duration / 2.0, base.end_time - constants::LEGACY_TICK_OFFSET),
-                            span_index: 0,
-                            repeat_index: 0,
-                            span_start_time: 0.0,
-                            slider_object_type: SliderObjectType::SliderEnd,
-                        });
-
-                        hitobjects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
-                        base.slider_objects = Some(hitobjects);
-                    }
+        if let Some(uninherited) = values.get(6) {
+            timing_change = uninherited.parse().unwrap_or(0) == 1;
+        }
 
-                    // spinner
-                    if base.hit_type & (HitType::Spinner as i32) != 0 {
-                        base.end_time = values[5].parse().unwrap_or(0.0);
-
-                        if let Some(s) = base.extra_data {
-                            base.extra_data = Some(s);
-                        } else {
-                            if let Some(val) = values.get(values.len() - 1) {
-                                if val.contains(":") {
-                                    // set extra data
-                                    base.extra_data = Some(HitObjectExtra {
-                                        hit_sample: BeatmapFile::parse_hitsample(val),
-                                    });
-                                } else {
-                                    base.extra_data = Some(HitObjectExtra {
-                                        hit_sample: HitSample::default(),
-                                    });
-                                }
-                            }
-                        }
-                    }
+        let speed_multiplier = if beat_length < 0.0 {
+            100.0 / (-beat_length)
+        } else {
+            1.0
+        };
 
-                    // push hitobject
-                    beatmap.hit_objects.push(base);
-                }
-                _ => continue,
+        let optional = |index: usize, default: i32| -> Result<i32, ParseError> {
+            match values.get(index) {
+                Some(value) => line.parse(TIMING_POINT_FIELDS[index], value),
+                None => Ok(default),
             }
-        }
+        };
 
-        // apply stacking
-        let stack_distance = 3.0;
-        let mut time_preempt = 600.0;
-        let end_index = beatmap.hit_objects.len() - 1;
+        let timing_point = TimingPoint {
+            time,
+            beat_length,
+            time_signature,
+            speed_multiplier,
+            point_type: if timing_change {
+                TimingPointType::Uninherited
+            } else {
+                TimingPointType::Inherited
+            },
+            sample_set: match values.get(3) {
+                Some(sample_set) => line.parse("sampleSet", sample_set)?,
+                None => SampleSet::Auto,
+            },
+            sample_index: optional(4, 0)?,
+            volume: optional(5, 100)?,
+            effects: optional(7, 0)?,
+        };
 
-        if beatmap.difficulty.approach_rate > 5.0 {
-            time_preempt = 1200.0 + (450.0 - 1200.0) * (beatmap.difficulty.approach_rate - 5.0) / 5.0;
-        } else if beatmap.difficulty.approach_rate < 5.0 {
-            time_preempt = 1200.0 + (1200.0 - 1800.0) * (5.0 - beatmap.difficulty.approach_rate) / 5.0;
+        if timing_change {
+            self.uninherited_points.push(UninheritedTimingPoint {
+                time,
+                beat_length,
+                time_signature,
+            });
         } else {
-            time_preempt = 1200.0;
+            self.inherited_points.push(InheritedTimingPoint {
+                time,
+                speed_multiplier,
+                inherited_from: UninheritedTimingPoint {
+                    time: 0.0,
+                    beat_length: 0.0,
+                    time_signature: 4,
+                },
+            });
         }
 
-        let mut extended_end = beatmap.hit_objects.len() - 1;
+        self.timing_points.push(timing_point);
 
-        if end_index < beatmap.hit_objects.len() - 1 {
-            let mut i = end_index;
-            while i >= 0 {
-                let stack_base = i;
+        Ok(())
+    }
 
-                let mut j = stack_base + 1;
-                while j < beatmap.hit_objects.len() {
-                    let stack_base_object = &beatmap.hit_objects[stack_base];
+    fn parse_hit_object(
+        &mut self,
+        line: &LineContext,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<(), ParseError> {
+        // oh no
+        let values: Vec<&str> = line.text.split(',').map(|s| s.trim()).collect();
+        let x: f32 = line.parse("x", line.value(&values, 0, "x")?)?;
+        let y: f32 = line.parse("y", line.value(&values, 1, "y")?)?;
+
+        let mut base = HitObject {
+            x,
+            y,
+            position: Vector2::new(x, y),
+            end_position: Vector2::new(0.0, 0.0),
+            start_time: line.parse("time", line.value(&values, 2, "time")?)?,
+            end_time: 0.0,
+            hit_sound: line.parse("hitSound", line.value(&values, 4, "hitSound")?)?,
+            hit_type: line.parse("type", line.value(&values, 3, "type")?)?,
+            stack_height: 0,
+            combo_index: 0,
+            combo_number: 0,
+            colour: None,
+            slider_data: None,
+            slider_objects: None,
+            extra_data: None,
+        };
 
-                    if stack_base_object.hit_type as i32 == HitType::Spinner as i32 {
-                        break;
-                    }
+        // the hitsample sits at a different index depending on the object type
+        let sample_index = if base.hit_type & (HitType::Slider as i32) != 0 {
+            10
+        } else if base.hit_type & (HitType::Spinner as i32) != 0 {
+            6
+        } else {
+            5
+        };
 
-                    let stack_j = &beatmap.hit_objects[j];
+        if (base.hit_type & HitType::Hold as i32) != 0 {
+            // mania hold
+            let val = line.value(&values, 5, "endTime")?;
+            let (end_time, hit_sample) = val.split_once(':').unwrap_or((val, ""));
+
+            // set extra data
+            base.end_time = line.parse("endTime", end_time)?;
+            base.extra_data = Some(HitObjectExtra {
+                hit_sample: BeatmapFile::parse_hitsample_checked(hit_sample, line, warnings),
+            });
+        } else if let Some(val) = values.get(sample_index) {
+            // normal hitcircle
+            base.extra_data = Some(HitObjectExtra {
+                hit_sample: BeatmapFile::parse_hitsample_checked(val, line, warnings),
+            });
+        }
 
-                    if stack_j.hit_type as i32 == HitType::Spinner as i32 {
-                        break;
-                    }
+        // slider information
+        if base.hit_type & (HitType::Slider as i32) != 0 {
+            self.parse_slider(&mut base, &values, line)?;
+        }
 
-                    let end_time = if stack_base_object.hit_type as i32 == HitType::Slider as i32 {
-                        stack_base_object.end_time
-                    } else {
-                        stack_base_object.start_time
-                    };
-                    let stack_threshold = time_preempt * beatmap.stack_leniency;
+        // spinner
+        if base.hit_type & (HitType::Spinner as i32) != 0 {
+            base.end_time = line.parse("endTime", line.value(&values, 5, "endTime")?)?;
 
-                    if stack_j.start_time - end_time > stack_threshold {
-                        break;
-                    }
+            if base.extra_data.is_none() {
+                base.extra_data = Some(HitObjectExtra {
+                    hit_sample: HitSample::default(),
+                });
+            }
+        }
 
-                    let endpos_distance_check = if stack_base_object.hit_type as i32 == HitType::Slider as i32 {
-                        stack_base_object.end_position.distance(stack_j.position) < stack_distance
-                    } else {
-                        false
-                    };
+        // push hitobject
+        self.hit_objects.push(base);
+        Ok(())
+    }
 
-                    if stack_base_object.position.distance(stack_j.position) < stack_distance || endpos_distance_check {
-                        beatmap.hit_objects[j].stack_height = 0;
-                    }
-                    
-                    j += 1;
-                }
+    fn parse_hitsample_checked(
+        val: &str,
+        line: &LineContext,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> HitSample {
+        if val.split(':').count() < 5 {
+            warnings.push(line.warning(ParseWarningKind::DefaultedValue, "hitSample", val));
+        }
+
+        BeatmapFile::parse_hitsample(val)
+    }
 
-                if stack_base > extended_end {
-                    extended_end = stack_base;
+    fn parse_slider(
+        &self,
+        base: &mut HitObject,
+        values: &[&str],
+        line: &LineContext,
+    ) -> Result<(), ParseError> {
+        let slider_data = line.value(values, 5, "curveParams")?; // has to have slider stuff
+        let slider_split: Vec<&str> = slider_data.split('|').collect();
+
+        // setup base
+        let mut slider_base = SliderData {
+            curve_type: slider_split[0].parse().unwrap_or(CurveType::Catmull),
+            base_points: vec![],
+            slider_points: vec![Vector2::new(0.0, 0.0)],
+            slider_body: SliderBody {
+                body: vec![],
+                length: vec![0.0],
+            },
+            slides: 1,
+            length: 0.0,
+            edge_sounds: vec![],
+            edge_sets: vec![],
+        };
 
-                    if extended_end == beatmap.hit_objects.len() - 1 {
-                        break;
-                    }
-                }
+        for point in slider_split {
+            if let Some((point_x, point_y)) = point.split_once(':') {
+                // sliderpoint
+                let point = Vector2::new(
+                    line.parse("curvePoints", point_x)?,
+                    line.parse("curvePoints", point_y)?,
+                );
 
-                i -= 1;
+                slider_base.base_points.push(point);
+                slider_base
+                    .slider_points
+                    .push(point - Vector2::new(base.x, base.y));
             }
         }
 
-        let mut extended_start = 0;
-        let mut i2 = extended_end;
-        while i2 > 0 {
-            let mut n = i2;
-
-            if beatmap.hit_objects[i2].stack_height != 0 || beatmap.hit_objects[i2].hit_type as i32 == HitType::Slider as i32 {
-                i2 -= 1;
-                continue;
+        // a perfect curve through three collinear points is drawn as a line,
+        // the written curve type is kept as-is so the map can be saved again
+        let mut path_type = slider_base.curve_type;
+        if slider_base.slider_points.len() == 3 // length is at least 3
+            && path_type as i32 == CurveType::PerfectCurve as i32
+        // is a perfect curve
+        {
+            // precision check
+            let point1 = &slider_base.slider_points[0];
+            let point2 = &slider_base.slider_points[1];
+            let point3 = &slider_base.slider_points[2];
+
+            let is_linear = f32::abs(
+                0.0 - ((point2.y - point1.y) * (point3.x - point1.x)
+                    - (point2.x - point1.x) * (point3.y - point1.y)),
+            ) <= 0.001;
+
+            if is_linear {
+                // this is linear
+                path_type = CurveType::Linear;
             }
+        }
 
-            let stack_threshold = time_preempt * beatmap.stack_leniency;
+        let mut slider_length = 0.0;
+        let slides: i32 = line.parse("slides", line.value(values, 6, "slides")?)?;
+        let repeat_count = i32::max(0, slides - 1);
 
-            if beatmap.hit_objects[i2].hit_type as i32 == HitType::Normal as i32 {
-                while n >= 0 {
-                    n -= 1;
+        if let Some(length) = values.get(7) {
+            // slider length
+            slider_length = line.parse("length", length)?;
+        }
 
-                    if beatmap.hit_objects[n].hit_type as i32 == HitType::Spinner as i32 {
-                        continue;
-                    }
+        slider_base.slides = slides;
+        slider_base.length = slider_length;
 
-                    let end_time = if beatmap.hit_objects[n].hit_type as i32 == HitType::Normal as i32 {
-                        beatmap.hit_objects[n].start_time
-                    } else {
-                        beatmap.hit_objects[n].end_time
-                    };
+        if let Some(edge_sounds) = values.get(8).filter(|s| !s.is_empty()) {
+            for sound in edge_sounds.split('|') {
+                slider_base
+                    .edge_sounds
+                    .push(line.parse("edgeSounds", sound)?);
+            }
+        }
 
-                    if &beatmap.hit_objects[i2].start_time - end_time > stack_threshold {
-                        break;
-                    }
-                    
-                    if n < extended_start {
-                        beatmap.hit_objects[n].stack_height = 0;
-                        extended_start = n;
-                    }
+        if let Some(edge_sets) = values.get(9).filter(|s| !s.is_empty()) {
+            for set in edge_sets.split('|') {
+                let (normal_set, addition_set) = set.split_once(':').unwrap_or((set, "0"));
+                slider_base.edge_sets.push((
+                    line.parse("edgeSets", normal_set)?,
+                    line.parse("edgeSets", addition_set)?,
+                ));
+            }
+        }
 
-                    let endpos_distance_check = if beatmap.hit_objects[n].hit_type as i32 == HitType::Slider as i32 {
-                        beatmap.hit_objects[n].end_position.distance(beatmap.hit_objects[i2].position) < stack_distance
+        // handle slider body
+        let expected_distance = f32::max(0.0, slider_length);
+        let mut slider_start = 0;
+        let mut slider_end = 0;
+        let slider_points = slider_base.slider_points.clone();
+
+        for i in 0..slider_points.len() {
+            slider_end += 1;
+
+            if i == slider_points.len() - 1 || slider_points[i] == slider_points[i + 1] {
+                // get a specific vector
+                let sub_path = &slider_points[slider_start..slider_end];
+                let mut approximated_path;
+
+                // approximate subpath
+                // TODO: impl partialeq for curvetype
+                if path_type as i32 == CurveType::Linear as i32 {
+                    approximated_path = sub_path.to_vec();
+                } else if path_type as i32 == CurveType::PerfectCurve as i32 {
+                    if slider_points.len() != 3 || sub_path.len() != 3 {
+                        approximated_path = BeatmapFile::approximate_bezier(sub_path);
                     } else {
-                        false
-                    };
-                    if endpos_distance_check {
-                        let offset = beatmap.hit_objects[i2].stack_height - beatmap.hit_objects[n].stack_height + 1;
-                        let mut j = n + 1;
-                        while j <= i2 + 1 {
-                            let stack_j = &beatmap.hit_objects[j];
-                            if beatmap.hit_objects[n].end_position.distance(stack_j.position) < stack_distance {
-                                beatmap.hit_objects[j].stack_height -= offset;
-                            }
-                        }
+                        approximated_path = BeatmapFile::approximate_perfect_curve(sub_path);
 
-                        break;
+                        if approximated_path.is_empty() {
+                            approximated_path = BeatmapFile::approximate_bezier(sub_path);
+                        }
                     }
+                } else if path_type as i32 == CurveType::Catmull as i32 {
+                    approximated_path = BeatmapFile::approximate_catmull(sub_path)
+                } else {
+                    approximated_path = BeatmapFile::approximate_bezier(sub_path);
+                }
 
-                    if beatmap.hit_objects[n].position.distance(beatmap.hit_objects[i2].position) < stack_distance {
-                        beatmap.hit_objects[n].stack_height = beatmap.hit_objects[i2].stack_height + 1;
+                // add to slider body
+                let body = &mut slider_base.slider_body.body;
+                for point in approximated_path {
+                    if body.last() != Some(&point) {
+                        body.push(point);
                     }
                 }
-            } else if beatmap.hit_objects[i2].hit_type as i32 == HitType::Slider as i32 {
-                while n >= 0 {
-                    n -= 1;
 
-                    let stack_n = &beatmap.hit_objects[n];
+                slider_start = slider_end
+            }
+        }
 
-                    if stack_n.hit_type as i32 == HitType::Spinner as i32 {
-                        continue;
-                    }
+        if slider_base.slider_body.body.is_empty() {
+            // degenerate path, keep the slider on its head
+            slider_base.slider_body.body.push(Vector2::new(0.0, 0.0));
+        }
 
-                    if &beatmap.hit_objects[i2].start_time - stack_n.start_time > stack_threshold {
-                        break;
-                    }
+        // calculate path length
+        let mut length = 0.0;
+        let body = &mut slider_base.slider_body;
+        for i in 0..body.body.len() - 1 {
+            let point = body.body[i];
+            let difference = body.body[i + 1] - point;
+            let diff = difference.len();
 
-                    let stack_n_endpos = if stack_n.hit_type as i32 == HitType::Normal as i32 {
-                        stack_n.position
-                    } else {
-                        stack_n.end_position
-                    };
+            if (expected_distance - length) < diff {
+                // :desolate:
+                body.body[i + 1] = point + difference.scale((expected_distance - length) / diff);
 
-                    if stack_n_endpos.distance(beatmap.hit_objects[i2].position) < stack_distance {
-                        beatmap.hit_objects[n].stack_height = beatmap.hit_objects[i2].stack_height + 1;
-                    }
-                }
+                // drop the remainder of the path
+                body.body.drain(i + 2..);
+
+                length = expected_distance;
+                body.length.push(length);
+                break;
             }
 
-            i2 -= 1;
+            length += diff;
+            body.length.push(length);
         }
 
-        beatmap // return beatmap
-    }
+        let body_len = body.body.len();
+        if length < expected_distance && body_len > 1 {
+            let difference = body.body[body_len - 1] - body.body[body_len - 2];
+            let diff = difference.len();
 
-    pub fn get_timing_point(&self, time: f32) -> TimingPoint {
-        // sort timing points
-        let mut timing_points = self.timing_points.clone();
-        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+            if diff > 0.0 {
+                body.body[body_len - 1] =
+                    body.body[body_len - 1] + difference.scale((expected_distance - length) / diff);
 
-        let mut current_index = 0;
-        let mut current_point = &timing_points[0];
+                let length_len = body.length.len();
+                body.length[length_len - 1] = expected_distance;
+            }
+        }
 
-        for timing_point in &timing_points {
-            if timing_point.time > time {
-                if current_index == 0 {
-                    break;
-                }
+        // slider body obtained
+        let slider_body = slider_base.slider_body.clone();
+        base.slider_data = Some(slider_base);
 
-                current_index = current_index - 1;
-                current_point = timing_point;
-                break;
-            }
+        // calculate and set end position, an even number of slides ends back on the head
+        let end_distance = if slides % 2 == 0 {
+            0.0
+        } else {
+            expected_distance
+        };
+        base.end_position = base.position + slider_body.position_at(end_distance);
 
-            current_index += 1;
+        // calculate slider timing data
+        if self.uninherited_points.is_empty() {
+            return Err(line.error(ParseErrorKind::MissingValue, "TimingPoints", line.text));
         }
 
-        if current_point.time < time {
-            return timing_points[0];
+        // inherited points only carry a multiplier, the beat length comes from the red line
+        let beat_length = self
+            .get_uninherited_timing_point(base.start_time)
+            .beat_length;
+        let scoring_distance =
+            100.0 * self.difficulty.slider_multiplier * self.get_speed_multiplier(base.start_time);
+        let velocity = scoring_distance / beat_length;
+        let span_count = repeat_count + 1;
+        let tick_distance = scoring_distance / self.difficulty.slider_tickrate;
+        let end_time = base.start_time + (span_count as f32 * expected_distance / velocity);
+        let duration = end_time - base.start_time;
+
+        base.end_time = end_time;
+
+        // create slider hitobjects
+        let mut hitobjects = vec![];
+
+        // slider head & end
+        hitobjects.push(SliderObject {
+            x: base.position.x,
+            y: base.position.y,
+            position: base.position,
+            start_time: base.start_time,
+            span_index: 0,
+            repeat_index: 0,
+            span_start_time: 0.0,
+            slider_object_type: SliderObjectType::SliderHead,
+        });
+
+        // create slider ticks
+        let length = f32::min(100000.0, expected_distance);
+        let certified_tick_distance = tick_distance.clamp(0.0, length);
+
+        if certified_tick_distance != 0.0 {
+            let min_distance_from_end = velocity * 10.0;
+            let span_duration = duration / span_count as f32;
+
+            for span in 0..span_count {
+                let span_start = base.start_time + span as f32 * span_duration;
+                let reversed = span % 2 == 1;
+
+                let mut d = tick_distance;
+                while d < length - min_distance_from_end {
+                    let progress = d / length;
+                    let time_progress = if reversed { 1.0 - progress } else { progress };
+
+                    // calculate tick position
+                    let distance = progress.clamp(0.0, 1.0) * expected_distance;
+                    let tick_position = base.position + slider_body.position_at(distance);
+
+                    hitobjects.push(SliderObject {
+                        x: tick_position.x,
+                        y: tick_position.y,
+                        position: tick_position,
+                        start_time: span_start + time_progress * span_duration,
+                        span_index: span,
+                        repeat_index: 0,
+                        span_start_time: span_start,
+                        slider_object_type: SliderObjectType::SliderTick,
+                    });
+
+                    d += tick_distance;
+                }
+            }
         }
 
-        timing_points[current_index]
-    }
-
-    pub fn get_uninherited_timing_point(&self, time: f32) -> UninheritedTimingPoint {
-        // sort timing points
-        let mut timing_points = self.uninherited_points.clone();
-        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+        // parse repeat points
+        let mut repeat = 1.0;
+        let mut repeat_index = 0;
+        while repeat_index < repeat_count {
+            let distance = f32::clamp(repeat % 2.0, 0.0, 1.0) * expected_distance;
+            let repeat_position = base.position + slider_body.position_at(distance);
+            let span_duration = duration / span_count as f32;
+
+            hitobjects.push(SliderObject {
+                x: repeat_position.x,
+                y: repeat_position.y,
+                position: repeat_position,
+                start_time: base.start_time + (repeat * span_duration),
+                span_index: repeat_index,
+                repeat_index,
+                span_start_time: base.start_time + (repeat - 1.0) * span_duration,
+                slider_object_type: SliderObjectType::SliderRepeat,
+            });
+
+            repeat_index += 1;
+            repeat += 1.0;
+        }
 
-        let mut current_index = 0;
-        let mut current_point = &timing_points[0];
+        // add sliderend
+        hitobjects.push(SliderObject {
+            x: base.end_position.x,
+            y: base.end_position.y,
+            position: base.end_position,
+            start_time: f32::max(
+                base.start_time + duration / 2.0,
+                base.end_time - constants::LEGACY_TICK_OFFSET,
+            ),
+            span_index: span_count - 1,
+            repeat_index: 0,
+            span_start_time: base.start_time
+                + (span_count - 1) as f32 * duration / span_count as f32,
+            slider_object_type: SliderObjectType::SliderEnd,
+        });
+
+        hitobjects.sort_by(|a, b| {
+            a.start_time
+                .partial_cmp(&b.start_time)
+                .unwrap_or(Ordering::Equal)
+        });
+        base.slider_objects = Some(hitobjects);
+
+        Ok(())
+    }
 
-        for timing_point in &timing_points {
-            if timing_point.time > time {
-                if current_index == 0 {
-                    break;
+    fn apply_combos(&mut self) {
+        let mut combo_index = -1;
+        let mut colour_index = -1;
+        let mut combo_number = 0;
+        let mut force_new_combo = true;
+        // colours skipped by spinners, passed on to the next object
+        let mut extra_combo_offset = 0;
+
+        for object in &mut self.hit_objects {
+            let new_combo = object.hit_type & (HitType::NewCombo as i32) != 0;
+
+            if object.hit_type & (HitType::Spinner as i32) != 0 {
+                // spinners never start a combo themselves, the next object does instead.
+                // before v9 every spinner ends the combo
+                force_new_combo |= self.format_version <= 8 || new_combo;
+                extra_combo_offset += object.combo_offset();
+            } else {
+                if force_new_combo || new_combo {
+                    combo_index += 1;
+                    colour_index += 1 + object.combo_offset() + extra_combo_offset;
+                    combo_number = 0;
+                    force_new_combo = false;
                 }
 
-                current_index = current_index - 1;
-                current_point = timing_point;
-                break;
+                extra_combo_offset = 0;
             }
 
-            current_index += 1;
-        }
+            combo_number += 1;
+            object.combo_index = i32::max(0, combo_index);
+            object.combo_number = combo_number;
 
-        if current_point.time < time {
-            return timing_points[0];
+            let colours = &self.colours.combo_colours;
+            if !colours.is_empty() {
+                let index = i32::max(0, colour_index) as usize % colours.len();
+                object.colour = Some(colours[index]);
+            }
         }
+    }
 
-        timing_points[current_index]
+    pub fn breaks(&self) -> Vec<BreakPeriod> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Break(period) => Some(*period),
+                _ => None,
+            })
+            .collect()
     }
 
-    pub fn get_inherited_timing_point(&self, time: f32) -> InheritedTimingPoint {
-        // sort timing points
-        let mut timing_points = self.inherited_points.clone();
-        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    pub fn background(&self) -> Option<&str> {
+        self.events.iter().find_map(|event| match event {
+            Event::Background { filename, .. } => Some(filename.as_str()),
+            _ => None,
+        })
+    }
 
-        let mut current_index = 0;
-        let mut current_point = &timing_points[0];
+    pub fn video(&self) -> Option<&str> {
+        self.events.iter().find_map(|event| match event {
+            Event::Video { filename, .. } => Some(filename.as_str()),
+            _ => None,
+        })
+    }
 
-        for timing_point in &timing_points {
-            if timing_point.time > time {
-                if current_index == 0 {
-                    break;
-                }
+    // the last point at or before `time`, objects before the first point use the first one
+    pub fn get_timing_point(&self, time: f32) -> TimingPoint {
+        // sort timing points
+        let mut timing_points = self.timing_points.clone();
+        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        timing_points
+            .iter()
+            .rev()
+            .find(|point| point.time <= time)
+            .copied()
+            .unwrap_or(timing_points[0])
+    }
 
-                current_index = current_index - 1;
-                current_point = timing_point;
-                break;
-            }
+    pub fn get_uninherited_timing_point(&self, time: f32) -> UninheritedTimingPoint {
+        // sort timing points
+        let mut timing_points = self.uninherited_points.clone();
+        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        timing_points
+            .iter()
+            .rev()
+            .find(|point| point.time <= time)
+            .copied()
+            .unwrap_or(timing_points[0])
+    }
 
-            current_index += 1;
-        }
+    pub fn get_inherited_timing_point(&self, time: f32) -> InheritedTimingPoint {
+        // sort timing points
+        let mut timing_points = self.inherited_points.clone();
+        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        timing_points
+            .iter()
+            .rev()
+            .find(|point| point.time <= time)
+            .copied()
+            .unwrap_or(timing_points[0])
+    }
+
+    // slider velocity multiplier at `time`, a new uninherited point resets it back to 1
+    pub fn get_speed_multiplier(&self, time: f32) -> f32 {
+        let mut timing_points = self.timing_points.clone();
+        timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
 
-        if current_point.time < time {
-            return timing_points[0];
+        let mut speed_multiplier = 1.0;
+        for point in timing_points.iter().take_while(|point| point.time <= time) {
+            speed_multiplier = match point.point_type {
+                TimingPointType::Uninherited => 1.0,
+                TimingPointType::Inherited => point.speed_multiplier.clamp(0.1, 10.0),
+            };
         }
 
-        timing_points[current_index]
+        speed_multiplier
     }
 
     pub fn parse_hitsample(val: &str) -> HitSample {
-        let t: Vec<String> = val.clone().split(":").map(|s| s.to_string()).collect();
+        let t: Vec<&str> = val.split(':').collect();
+        let field = |i: usize| t.get(i).and_then(|v| v.parse().ok()).unwrap_or(0);
 
         HitSample {
-            normal_set: t[0].parse().unwrap_or(0),
-            additional_set: t[1].parse().unwrap_or(0),
-            index: t[2].parse().unwrap_or(0),
-            volume: t[3].parse().unwrap_or(0),
-            file_name: t[4].clone(),
+            normal_set: field(0),
+            additional_set: field(1),
+            index: field(2),
+            volume: field(3),
+            file_name: t.get(4).unwrap_or(&"").to_string(),
         }
     }
 
-    pub fn approximate_bezier(sub_points: &Vec<Vector2>) -> Vec<Vector2> {
+    pub fn approximate_bezier(sub_points: &[Vector2]) -> Vec<Vector2> {
         let mut approximated_path = vec![];
 
-        if sub_points.len() == 0 {
+        if sub_points.is_empty() {
             // nothing, just return nothing
             return approximated_path;
         }
 
-        let mut subdiv_buffer1 = vec![];
-        let mut subdiv_buffer2 = vec![];
-
-        for _i in 0..sub_points.len() {
-            subdiv_buffer1.push(Vector2::new(0.0, 0.0));
-        }
-
-        for _i in 0..((sub_points.len() * 2) - 1) {
-            subdiv_buffer2.push(Vector2::new(0.0, 0.0));
-        }
+        let count = sub_points.len();
+        let mut subdiv_buffer1 = vec![Vector2::new(0.0, 0.0); count];
+        let mut subdiv_buffer2 = vec![Vector2::new(0.0, 0.0); (count * 2) - 1];
 
         let mut to_flatten = vec![];
         let mut free_buffers = vec![];
-        let deep_copy = sub_points.clone();
 
         // copy the base slider points to avoid overriding original ones
-        to_flatten.push(deep_copy);
-
-        while to_flatten.len() > 0 {
-            let mut parent = to_flatten.pop().unwrap();
-            let mut flat_enough = true;
-
-            // are the control points we're using flat enough?
-            for i in 1..parent.len() - 1 {
-                let scale = parent[i].scale(2.0);
-                let subtract = parent[i - 1] - scale;
-                let sum = subtract + parent[i + 1];
-
-                if sum.len().powf(2.0)
+        to_flatten.push(sub_points.to_vec());
+
+        while let Some(mut parent) = to_flatten.pop() {
+            // are the control points we're using flat enough? written as "none too far
+            // off" so points that aren't numbers count as flat and end the subdivision
+            let flat_enough = !parent.windows(3).any(|w| {
+                let sum = w[0] - w[1].scale(2.0) + w[2];
+                sum.len().powf(2.0)
                     > constants::BEZIER_TOLERANCE * constants::BEZIER_TOLERANCE * 4.0
-                {
-                    flat_enough = false;
-                    break;
-                }
-            }
+            });
 
-            if flat_enough == true {
+            if flat_enough {
                 // subdivide
                 let approxmid_points = &mut subdiv_buffer1;
-                for i in 0..sub_points.len() {
-                    approxmid_points[i] = parent[i];
-                }
+                approxmid_points.copy_from_slice(&parent);
 
-                for i in 0..sub_points.len() {
-                    subdiv_buffer2[i] = approxmid_points[0];
-                    approxmid_points[parent.len() - i - 1] = approxmid_points[parent.len() - i - 1];
+                for (i, left) in subdiv_buffer2[..count].iter_mut().enumerate() {
+                    *left = approxmid_points[0];
 
-                    for j in 0..((sub_points.len() - i) - 1) {
+                    for j in 0..((count - i) - 1) {
                         approxmid_points[j] =
                             (approxmid_points[j] + approxmid_points[j + 1]).div(2.0);
                     }
                 }
 
                 // reuse 2nd buffer for next iteration
-                for i in 0..(sub_points.len() - 1) {
-                    subdiv_buffer2[sub_points.len() + i] = subdiv_buffer1[i + 1];
-                }
+                subdiv_buffer2[count..].copy_from_slice(&subdiv_buffer1[1..]);
 
                 approximated_path.push(parent[0]);
 
-                for i in 1..(sub_points.len() - 1) {
+                for i in 1..(count - 1) {
                     let index = 2 * i;
                     let vector = (subdiv_buffer2[index - 1]
                         + (Vector2::new(2.0, 2.0).mul(subdiv_buffer2[index])
@@ -1183,49 +1191,40 @@ impl BeatmapFile {
             // no, it is not.
             // further flatten the curve to get a close enough approximation
             // we might not yet have a flat approximation, ` we'd need to subdivide a bare array
-            let mut right_child = vec![];
-            if free_buffers.len() > 0 {
-                right_child = free_buffers.pop().unwrap();
-            } else {
-                for _i in 0..sub_points.len() {
-                    right_child.push(Vector2::new(0.0, 0.0));
-                }
-            }
+            let mut right_child = free_buffers
+                .pop()
+                .unwrap_or_else(|| vec![Vector2::new(0.0, 0.0); count]);
 
             // subdivide
             let mid_points = &mut subdiv_buffer1;
-            for i in 0..sub_points.len() {
-                mid_points[i] = parent[i].clone();
-            }
+            mid_points.copy_from_slice(&parent);
 
-            for i in 0..sub_points.len() {
+            for i in 0..count {
                 subdiv_buffer2[i] = mid_points[0];
-                right_child[sub_points.len() - i - 1] = mid_points[sub_points.len() - i - 1];
+                right_child[count - i - 1] = mid_points[count - i - 1];
 
-                for j in 0..(sub_points.len() - i - 1) {
+                for j in 0..(count - i - 1) {
                     mid_points[j] = (mid_points[j] + mid_points[j + 1]).div(2.0);
                 }
             }
 
-            for i in 0..sub_points.len() {
-                parent[i] = subdiv_buffer2[i];
-            }
+            parent.copy_from_slice(&subdiv_buffer2[..count]);
 
             to_flatten.push(right_child);
             to_flatten.push(parent);
         }
 
-        approximated_path.push(sub_points[sub_points.len() - 1]);
+        approximated_path.push(sub_points[count - 1]);
         approximated_path
     }
 
-    pub fn approximate_perfect_curve(sub_points: &Vec<Vector2>) -> Vec<Vector2> {
+    pub fn approximate_perfect_curve(sub_points: &[Vector2]) -> Vec<Vector2> {
         let mut approximated_path = vec![];
 
         // clone points to avoid overriding original points
-        let point1 = sub_points[0].clone();
-        let point2 = sub_points[1].clone();
-        let point3 = sub_points[2].clone();
+        let point1 = sub_points[0];
+        let point2 = sub_points[1];
+        let point3 = sub_points[2];
 
         // squared point lengths
         let point1_sq = (point2 - point3).len().powf(2.0);
@@ -1288,13 +1287,14 @@ impl BeatmapFile {
             let fraction = i as f32 / (points - 1) as f32;
             let theta = theta_start + dir * fraction * theta_range;
 
-            approximated_path.push(Vector2::new(f32::cos(theta), f32::sin(theta)).scale(rad));
+            approximated_path
+                .push(point_center + Vector2::new(f32::cos(theta), f32::sin(theta)).scale(rad));
         }
 
         approximated_path
     }
 
-    pub fn approximate_catmull(sub_points: &Vec<Vector2>) -> Vec<Vector2> {
+    pub fn approximate_catmull(sub_points: &[Vector2]) -> Vec<Vector2> {
         let mut approximated_path = vec![];
 
         for i in 0..(sub_points.len() - 1) {
@@ -1325,13 +1325,25 @@ impl BeatmapFile {
                 let t3_b = t_b * t2_b;
 
                 let p1 = Vector2::new(
-                    0.5 * (2.0 * vec2.x + (-vec1.x + vec3.x) * t + (2.0 * vec1.x - 5.0 * vec2.x + 4.0 * vec3.x - vec4.x) * t2 + (-vec1.x + 3.0 * vec2.x - 3.0 * vec3.x + vec4.x) * t3),
-                    0.5 * (2.0 * vec2.y + (-vec1.y + vec3.y) * t + (2.0 * vec1.y - 5.0 * vec2.y + 4.0 * vec3.y - vec4.y) * t2 + (-vec1.y + 3.0 * vec2.y - 3.0 * vec3.y + vec4.y) * t3)
+                    0.5 * (2.0 * vec2.x
+                        + (-vec1.x + vec3.x) * t
+                        + (2.0 * vec1.x - 5.0 * vec2.x + 4.0 * vec3.x - vec4.x) * t2
+                        + (-vec1.x + 3.0 * vec2.x - 3.0 * vec3.x + vec4.x) * t3),
+                    0.5 * (2.0 * vec2.y
+                        + (-vec1.y + vec3.y) * t
+                        + (2.0 * vec1.y - 5.0 * vec2.y + 4.0 * vec3.y - vec4.y) * t2
+                        + (-vec1.y + 3.0 * vec2.y - 3.0 * vec3.y + vec4.y) * t3),
                 );
 
                 let p2 = Vector2::new(
-                    0.5 * (2.0 * vec2.x + (-vec1.x + vec3.x) * t_b + (2.0 * vec1.x - 5.0 * vec2.x + 4.0 * vec3.x - vec4.x) * t2_b + (-vec1.x + 3.0 * vec2.x - 3.0 * vec3.x + vec4.x) * t3_b),
-                    0.5 * (2.0 * vec2.y + (-vec1.y + vec3.y) * t_b + (2.0 * vec1.y - 5.0 * vec2.y + 4.0 * vec3.y - vec4.y) * t2_b + (-vec1.y + 3.0 * vec2.y - 3.0 * vec3.y + vec4.y) * t3_b)
+                    0.5 * (2.0 * vec2.x
+                        + (-vec1.x + vec3.x) * t_b
+                        + (2.0 * vec1.x - 5.0 * vec2.x + 4.0 * vec3.x - vec4.x) * t2_b
+                        + (-vec1.x + 3.0 * vec2.x - 3.0 * vec3.x + vec4.x) * t3_b),
+                    0.5 * (2.0 * vec2.y
+                        + (-vec1.y + vec3.y) * t_b
+                        + (2.0 * vec1.y - 5.0 * vec2.y + 4.0 * vec3.y - vec4.y) * t2_b
+                        + (-vec1.y + 3.0 * vec2.y - 3.0 * vec3.y + vec4.y) * t3_b),
                 );
 
                 approximated_path.push(p1);
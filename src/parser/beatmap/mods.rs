@@ -0,0 +1,152 @@
+use crate::{
+    game::{Gamemode, ModSet, Mods},
+    parser::beatmap::{attributes::adjust, events::Event, BeatmapFile, DifficultyMetadata},
+    util::Vector2,
+};
+
+// the height of the playfield hard rock flips objects over
+const PLAYFIELD_HEIGHT: f32 = 384.0;
+
+impl BeatmapFile {
+    // a copy of the map as it's played with `mods`. hard rock and easy change the
+    // difficulty settings and hard rock flips standard maps upside down, the rate mods
+    // speed up or slow down every time in the map. storyboard commands are left alone.
+    // the calculators expect the unmodified map together with the mods, so don't pass
+    // the mods to them again for the copy
+    pub fn with_mods(&self, mods: ModSet) -> BeatmapFile {
+        let has = |m: Mods| mods.contains(m);
+        let mut beatmap = self.clone();
+
+        beatmap.difficulty = self.difficulty.with_mods(self.gamemode, mods);
+
+        if has(Mods::HardRock) && self.gamemode == Gamemode::Standard {
+            beatmap.flip_vertically();
+        }
+
+        // stacks depend on the approach rate, so they're worked out again before the
+        // times change, like the game does
+        if has(Mods::HardRock) || has(Mods::Easy) {
+            beatmap.apply_stacking();
+        }
+
+        let clock_rate = mods.clock_rate();
+        if clock_rate != 1.0 {
+            beatmap.scale_times(clock_rate as f32);
+        }
+
+        beatmap
+    }
+
+    fn flip_vertically(&mut self) {
+        let flip = |point: Vector2| Vector2::new(point.x, PLAYFIELD_HEIGHT - point.y);
+
+        for object in &mut self.hit_objects {
+            object.position = flip(object.position);
+            object.x = object.position.x;
+            object.y = object.position.y;
+            object.end_position = flip(object.end_position);
+
+            if let Some(slider_data) = &mut object.slider_data {
+                // the path is relative to the head, so it only changes direction
+                for point in &mut slider_data.base_points {
+                    *point = flip(*point);
+                }
+
+                for point in slider_data
+                    .slider_points
+                    .iter_mut()
+                    .chain(slider_data.slider_body.body.iter_mut())
+                {
+                    point.y = -point.y;
+                }
+
+                let end_distance = if slider_data.slides % 2 == 0 {
+                    0.0
+                } else {
+                    slider_data.length.max(0.0)
+                };
+                object.end_position =
+                    object.position + slider_data.slider_body.position_at(end_distance);
+            }
+
+            for slider_object in object.slider_objects.iter_mut().flatten() {
+                slider_object.position = flip(slider_object.position);
+                slider_object.x = slider_object.position.x;
+                slider_object.y = slider_object.position.y;
+            }
+        }
+    }
+
+    // everything that happens at a point in the song moves with the rate, beat lengths
+    // shrink with it and slider velocities stay as they are
+    fn scale_times(&mut self, clock_rate: f32) {
+        for point in &mut self.timing_points {
+            point.time /= clock_rate;
+
+            // inherited points store a velocity, not a length
+            if point.beat_length > 0.0 {
+                point.beat_length /= clock_rate;
+            }
+        }
+
+        for point in &mut self.uninherited_points {
+            point.time /= clock_rate;
+            point.beat_length /= clock_rate;
+        }
+
+        for point in &mut self.inherited_points {
+            point.time /= clock_rate;
+            point.inherited_from.time /= clock_rate;
+            point.inherited_from.beat_length /= clock_rate;
+        }
+
+        for object in &mut self.hit_objects {
+            object.start_time /= clock_rate;
+            object.end_time /= clock_rate;
+
+            for slider_object in object.slider_objects.iter_mut().flatten() {
+                slider_object.start_time /= clock_rate;
+                slider_object.span_start_time /= clock_rate;
+            }
+        }
+
+        for event in &mut self.events {
+            match event {
+                Event::Video { start_time, .. } => *start_time /= clock_rate,
+                Event::Break(period) => {
+                    period.start_time /= clock_rate;
+                    period.end_time /= clock_rate;
+                }
+                _ => {}
+            }
+        }
+
+        let scale = |time: i32| (time as f32 / clock_rate) as i32;
+        self.metadata.preview_time = scale(self.metadata.preview_time);
+        self.audio.lead_in = scale(self.audio.lead_in);
+        for bookmark in &mut self.editor.bookmarks {
+            *bookmark = scale(*bookmark);
+        }
+    }
+}
+
+impl DifficultyMetadata {
+    // the settings as hard rock and easy change them, a mania map's circle size is its
+    // key count and never changes
+    pub fn with_mods(&self, gamemode: Gamemode, mods: ModSet) -> DifficultyMetadata {
+        let circle_size = if gamemode == Gamemode::Mania {
+            self.circle_size
+        } else {
+            adjust(self.circle_size, 1.3, mods) as f32
+        };
+
+        DifficultyMetadata {
+            hp_drain: adjust(self.hp_drain, 1.4, mods) as f32,
+            circle_size,
+            overall_difficulty: adjust(self.overall_difficulty, 1.4, mods) as f32,
+            approach_rate: adjust(self.approach_rate, 1.4, mods) as f32,
+            slider_multiplier: self.slider_multiplier,
+            slider_tickrate: self.slider_tickrate,
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use std::{
-    convert::TryFrom,
-    str::FromStr
-};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
-use crate::util::Vector2;
+use crate::{
+    constants,
+    util::{Colour, Vector2},
+};
 
 #[derive(Debug)]
 pub struct HitObject {
@@ -16,9 +16,12 @@ pub struct HitObject {
     pub hit_sound: i32,
     pub hit_type: i32,
     pub stack_height: i32,
+    pub combo_index: i32,
+    pub combo_number: i32,
+    pub colour: Option<Colour>,
     pub slider_data: Option<SliderData>,
     pub slider_objects: Option<Vec<SliderObject>>,
-    pub extra_data: Option<HitObjectExtra>
+    pub extra_data: Option<HitObjectExtra>,
 }
 
 impl Clone for HitObject {
@@ -33,6 +36,9 @@ impl Clone for HitObject {
             hit_sound: self.hit_sound,
             hit_type: self.hit_type,
             stack_height: self.stack_height,
+            combo_index: self.combo_index,
+            combo_number: self.combo_number,
+            colour: self.colour,
             slider_data: self.slider_data.clone(),
             slider_objects: self.slider_objects.clone(),
             extra_data: self.extra_data.clone(),
@@ -40,6 +46,38 @@ impl Clone for HitObject {
     }
 }
 
+impl HitObject {
+    // number of combo colours skipped when this object starts a new combo
+    pub fn combo_offset(&self) -> i32 {
+        (self.hit_type
+            & (HitType::ComboSkip1 as i32
+                | HitType::ComboSkip2 as i32
+                | HitType::ComboSkip3 as i32))
+            >> 4
+    }
+
+    // stacked objects move up and left by a tenth of the circle radius per level
+    pub fn stack_offset(&self, radius: f32) -> Vector2 {
+        let offset = -(self.stack_height as f32) * radius / 10.0;
+        Vector2::new(offset, offset)
+    }
+
+    pub fn stacked_position(&self, radius: f32) -> Vector2 {
+        self.position + self.stack_offset(radius)
+    }
+
+    // where a slider's end is drawn, anything else ends where it starts
+    pub fn stacked_end_position(&self, radius: f32) -> Vector2 {
+        let end_position = if self.hit_type & HitType::Slider as i32 != 0 {
+            self.end_position
+        } else {
+            self.position
+        };
+
+        end_position + self.stack_offset(radius)
+    }
+}
+
 #[derive(Debug)]
 pub struct HitObjectExtra {
     pub hit_sample: HitSample,
@@ -86,12 +124,13 @@ impl Clone for HitSample {
     }
 }
 
+// bits of `HitObject::hit_sound`
 #[derive(Debug)]
 pub enum HitSound {
-    Normal,
-    Whistle,
-    Finish,
-    Clap,
+    Normal = 1 << 0,
+    Whistle = 1 << 1,
+    Finish = 1 << 2,
+    Clap = 1 << 3,
 }
 
 impl TryFrom<i32> for HitSound {
@@ -168,6 +207,95 @@ impl FromStr for HitType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleSet {
+    Auto,
+    Normal,
+    Soft,
+    Drum,
+}
+
+impl FromStr for SampleSet {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Auto" | "None" | "0" => Ok(SampleSet::Auto),
+            "Normal" | "1" => Ok(SampleSet::Normal),
+            "Soft" | "2" => Ok(SampleSet::Soft),
+            "Drum" | "3" => Ok(SampleSet::Drum),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for SampleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SampleSet::Auto => "None",
+            SampleSet::Normal => "Normal",
+            SampleSet::Soft => "Soft",
+            SampleSet::Drum => "Drum",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Countdown {
+    None,
+    Normal,
+    Half,
+    Double,
+}
+
+impl FromStr for Countdown {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "0" => Ok(Countdown::None),
+            "1" => Ok(Countdown::Normal),
+            "2" => Ok(Countdown::Half),
+            "3" => Ok(Countdown::Double),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlayPosition {
+    NoChange,
+    Below,
+    Above,
+}
+
+impl FromStr for OverlayPosition {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "NoChange" => Ok(OverlayPosition::NoChange),
+            "Below" => Ok(OverlayPosition::Below),
+            "Above" => Ok(OverlayPosition::Above),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for OverlayPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            OverlayPosition::NoChange => "NoChange",
+            OverlayPosition::Below => "Below",
+            OverlayPosition::Above => "Above",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Debug)]
 pub enum CurveType {
     Catmull = 1,
@@ -190,6 +318,19 @@ impl FromStr for CurveType {
     }
 }
 
+impl fmt::Display for CurveType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self {
+            CurveType::Catmull => "C",
+            CurveType::Bezier => "B",
+            CurveType::Linear => "L",
+            CurveType::PerfectCurve => "P",
+        };
+
+        write!(f, "{}", letter)
+    }
+}
+
 impl Copy for CurveType {}
 
 impl Clone for CurveType {
@@ -204,27 +345,25 @@ pub struct TimingPoint {
     pub beat_length: f32,
     pub time_signature: i32,
     pub speed_multiplier: f32,
-    pub point_type: TimingPointType
+    pub point_type: TimingPointType,
+    pub sample_set: SampleSet,
+    pub sample_index: i32,
+    pub volume: i32,
+    pub effects: i32,
 }
 
 impl Copy for TimingPoint {}
 
 impl Clone for TimingPoint {
     fn clone(&self) -> Self {
-        TimingPoint {
-            time: self.time,
-            beat_length: self.beat_length,
-            time_signature: self.time_signature,
-            speed_multiplier: self.speed_multiplier,
-            point_type: self.point_type
-        }
+        *self
     }
 }
 
 #[derive(Debug)]
 pub enum TimingPointType {
     Uninherited,
-    Inherited
+    Inherited,
 }
 
 impl Copy for TimingPointType {}
@@ -239,18 +378,14 @@ impl Clone for TimingPointType {
 pub struct UninheritedTimingPoint {
     pub time: f32,
     pub beat_length: f32,
-    pub time_signature: i32
+    pub time_signature: i32,
 }
 
 impl Copy for UninheritedTimingPoint {}
 
 impl Clone for UninheritedTimingPoint {
     fn clone(&self) -> Self {
-        UninheritedTimingPoint {
-            time: self.time,
-            beat_length: self.beat_length,
-            time_signature: self.time_signature
-        }
+        *self
     }
 }
 
@@ -258,18 +393,14 @@ impl Clone for UninheritedTimingPoint {
 pub struct InheritedTimingPoint {
     pub time: f32,
     pub speed_multiplier: f32,
-    pub inherited_from: UninheritedTimingPoint
+    pub inherited_from: UninheritedTimingPoint,
 }
 
 impl Copy for InheritedTimingPoint {}
 
 impl Clone for InheritedTimingPoint {
     fn clone(&self) -> Self {
-        InheritedTimingPoint {
-            time: self.time,
-            speed_multiplier: self.speed_multiplier,
-            inherited_from: self.inherited_from.clone(),
-        }
+        *self
     }
 }
 
@@ -279,6 +410,10 @@ pub struct SliderData {
     pub base_points: Vec<Vector2>,
     pub slider_points: Vec<Vector2>,
     pub slider_body: SliderBody,
+    pub slides: i32,
+    pub length: f32,
+    pub edge_sounds: Vec<i32>,
+    pub edge_sets: Vec<(i32, i32)>,
 }
 
 impl Clone for SliderData {
@@ -288,6 +423,10 @@ impl Clone for SliderData {
             base_points: self.base_points.clone(),
             slider_points: self.slider_points.clone(),
             slider_body: self.slider_body.clone(),
+            slides: self.slides,
+            length: self.length,
+            edge_sounds: self.edge_sounds.clone(),
+            edge_sets: self.edge_sets.clone(),
         }
     }
 }
@@ -307,6 +446,42 @@ impl Clone for SliderBody {
     }
 }
 
+impl SliderBody {
+    // position along the body (relative to the slider head) at a given distance
+    pub fn position_at(&self, distance: f32) -> Vector2 {
+        if self.body.is_empty() {
+            return Vector2::new(0.0, 0.0);
+        }
+
+        let index = self
+            .length
+            .iter()
+            .position(|&l| l == distance)
+            .or_else(|| self.length.iter().position(|&l| l > distance))
+            .unwrap_or_else(|| self.length.len().saturating_sub(1));
+
+        // interpolate slider vertices
+        if index == 0 {
+            return self.body[0];
+        } else if index >= self.body.len() {
+            return self.body[self.body.len() - 1];
+        }
+
+        let start = self.body[index - 1];
+        let end = self.body[index];
+
+        let distance_start = self.length[index - 1];
+        let distance_end = self.length[index];
+
+        if f32::abs(distance_start - distance_end) <= constants::PRECISION_LENIENCE {
+            start
+        } else {
+            let scale = (distance - distance_start) / (distance_end - distance_start);
+            start + ((end - start).scale(scale))
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct SliderObject {
     pub x: f32,
@@ -316,7 +491,7 @@ pub struct SliderObject {
     pub span_index: i32,
     pub repeat_index: i32,
     pub span_start_time: f32,
-    pub slider_object_type: SliderObjectType
+    pub slider_object_type: SliderObjectType,
 }
 
 impl Copy for SliderObject {}
@@ -341,4 +516,4 @@ impl Clone for SliderObjectType {
     fn clone(&self) -> Self {
         *self
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,178 @@
+use crate::{
+    game::{Gamemode, ModSet},
+    parser::beatmap::{
+        objects::{HitObject, HitType},
+        BeatmapFile,
+    },
+    util::Vector2,
+};
+
+// objects closer than this to each other stack
+const STACK_DISTANCE: f32 = 3.0;
+
+impl BeatmapFile {
+    // works out every object's stack height like osu! does for standard maps, maps
+    // before v6 use the old algorithm. the difficulty is expected to already have
+    // any mods applied, since the approach rate decides how far apart stacks can be
+    pub(super) fn apply_stacking(&mut self) {
+        for object in &mut self.hit_objects {
+            object.stack_height = 0;
+        }
+
+        if self.gamemode != Gamemode::Standard || self.hit_objects.is_empty() {
+            return;
+        }
+
+        // osu! keeps the preempt as a single precision float
+        let preempt = self
+            .difficulty
+            .attributes(Gamemode::Standard, ModSet::default())
+            .preempt as f32;
+        let threshold = preempt * self.stack_leniency;
+
+        if self.format_version >= 6 {
+            stack(&mut self.hit_objects, threshold);
+        } else {
+            stack_old(&mut self.hit_objects, threshold);
+        }
+    }
+
+    // the map with stacks worked out for the approach rate of `mods`, everything else
+    // stays unmodified. the calculators take the unmodified map and the mods, but hard
+    // rock and easy can stack objects differently
+    pub(crate) fn restacked(&self, mods: ModSet) -> BeatmapFile {
+        let mut beatmap = self.clone();
+        beatmap.difficulty = self.difficulty.with_mods(self.gamemode, mods);
+        beatmap.apply_stacking();
+        beatmap.difficulty = self.difficulty.clone();
+        beatmap
+    }
+}
+
+fn is_slider(object: &HitObject) -> bool {
+    object.hit_type & HitType::Slider as i32 != 0
+}
+
+fn is_spinner(object: &HitObject) -> bool {
+    object.hit_type & HitType::Spinner as i32 != 0
+}
+
+// circles don't store an end time
+fn end_time(object: &HitObject) -> f32 {
+    if is_slider(object) || is_spinner(object) {
+        object.end_time
+    } else {
+        object.start_time
+    }
+}
+
+// only sliders store an end position
+fn end_position(object: &HitObject) -> Vector2 {
+    if is_slider(object) {
+        object.end_position
+    } else {
+        object.position
+    }
+}
+
+// stacks are built backwards from their last object. circles under the end of a
+// slider stack down and right from it, everything else up and left
+fn stack(objects: &mut [HitObject], threshold: f32) {
+    for i in (1..objects.len()).rev() {
+        if objects[i].stack_height != 0 || is_spinner(&objects[i]) {
+            continue;
+        }
+
+        // the object the next one has to be close to, it moves back as the stack grows
+        let mut current = i;
+
+        if is_slider(&objects[i]) {
+            // from the first slider of a stack on, everything stacks up
+            for n in (0..i).rev() {
+                if is_spinner(&objects[n]) {
+                    continue;
+                }
+
+                if objects[current].start_time - objects[n].start_time > threshold {
+                    break;
+                }
+
+                if end_position(&objects[n]).distance(objects[current].position) < STACK_DISTANCE {
+                    objects[n].stack_height = objects[current].stack_height + 1;
+                    current = n;
+                }
+            }
+
+            continue;
+        }
+
+        for n in (0..i).rev() {
+            if is_spinner(&objects[n]) {
+                continue;
+            }
+
+            if objects[current].start_time - end_time(&objects[n]) > threshold {
+                break;
+            }
+
+            // the circles under this slider's end move below it instead. the slider
+            // itself is left for when the outer loop reaches it
+            if is_slider(&objects[n])
+                && objects[n].end_position.distance(objects[current].position) < STACK_DISTANCE
+            {
+                let offset = objects[current].stack_height - objects[n].stack_height + 1;
+                let slider_end = objects[n].end_position;
+
+                for object in &mut objects[n + 1..=i] {
+                    if slider_end.distance(object.position) < STACK_DISTANCE {
+                        object.stack_height -= offset;
+                    }
+                }
+
+                break;
+            }
+
+            if objects[n].position.distance(objects[current].position) < STACK_DISTANCE {
+                objects[n].stack_height = objects[current].stack_height + 1;
+                current = n;
+            }
+        }
+    }
+}
+
+// the algorithm before v6 works forwards, every object pushes up the ones stacked
+// on it. objects on the end of a slider's path are pushed down and right instead
+fn stack_old(objects: &mut [HitObject], threshold: f32) {
+    for i in 0..objects.len() {
+        if objects[i].stack_height != 0 && !is_slider(&objects[i]) {
+            continue;
+        }
+
+        let position = objects[i].position;
+        let path_end = match &objects[i].slider_data {
+            Some(slider_data) if is_slider(&objects[i]) => {
+                position + slider_data.slider_body.position_at(slider_data.length)
+            }
+            _ => position,
+        };
+
+        let mut start_time = end_time(&objects[i]);
+        let mut slider_stack = 0;
+
+        for j in i + 1..objects.len() {
+            if objects[j].start_time - threshold > start_time {
+                break;
+            }
+
+            // osu!stable compares against the next object's start time, not its end
+            if objects[j].position.distance(position) < STACK_DISTANCE {
+                objects[i].stack_height += 1;
+                start_time = objects[j].start_time;
+            } else if objects[j].position.distance(path_end) < STACK_DISTANCE {
+                slider_stack += 1;
+                objects[j].stack_height -= slider_stack;
+                start_time = objects[j].start_time;
+            }
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::io::{self, Write};
+
+use crate::{
+    game::Gamemode,
+    parser::beatmap::{
+        events::Event,
+        objects::{HitObject, HitSample, HitType, TimingPoint, TimingPointType},
+        BeatmapFile,
+    },
+    util::Colour,
+};
+
+// the order osu! writes sections in
+pub(crate) const SECTIONS: [&str; 8] = [
+    "General",
+    "Editor",
+    "Metadata",
+    "Difficulty",
+    "Events",
+    "TimingPoints",
+    "Colours",
+    "HitObjects",
+];
+
+impl BeatmapFile {
+    // the output follows `format_version`, change it before writing to target another version
+    pub fn to_osu_string(&self) -> String {
+        let mut buffer = vec![];
+        self.write_to(&mut buffer)
+            .expect("writing to a Vec<u8> cannot fail");
+
+        String::from_utf8(buffer).expect("beatmap output is always valid utf-8")
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "osu file format v{}", self.format_version)?;
+
+        for section in &SECTIONS {
+            let lines = self.section_lines(section);
+
+            if lines.is_empty() && *section == "Colours" {
+                continue;
+            }
+
+            writeln!(writer)?;
+            writeln!(writer, "[{}]", section)?;
+
+            for line in lines {
+                writeln!(writer, "{}", line)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    pub(crate) fn section_lines(&self, section: &str) -> Vec<String> {
+        let separator = pair_separator(section);
+        let pairs = self.section_pairs(section);
+
+        if pairs.is_empty() {
+            self.section_items(section)
+        } else {
+            pairs
+                .into_iter()
+                .map(|(key, value)| format!("{}{}{}", key, separator, value))
+                .collect()
+        }
+    }
+
+    // key/value sections
+    pub(crate) fn section_pairs(&self, section: &str) -> Vec<(String, String)> {
+        let mut pairs: Vec<(&str, String)> = vec![];
+
+        match section {
+            "General" => {
+                let general = &self.general;
+                let mode = match self.gamemode {
+                    Gamemode::Standard => 0,
+                    Gamemode::Taiko => 1,
+                    Gamemode::Catch => 2,
+                    Gamemode::Mania => 3,
+                };
+
+                pairs.push(("AudioFilename", self.audio.filename.clone()));
+                pairs.push(("AudioLeadIn", self.audio.lead_in.to_string()));
+                if !self.audio.hash.is_empty() {
+                    pairs.push(("AudioHash", self.audio.hash.clone()));
+                }
+                pairs.push(("PreviewTime", self.metadata.preview_time.to_string()));
+                pairs.push(("Countdown", (general.countdown as i32).to_string()));
+                pairs.push(("SampleSet", self.audio.sample_set.to_string()));
+                pairs.push(("StackLeniency", self.stack_leniency.to_string()));
+                pairs.push(("Mode", mode.to_string()));
+                pairs.push(("LetterboxInBreaks", flag(general.letterbox_in_breaks)));
+                pairs.push(("StoryFireInFront", flag(general.story_fire_in_front)));
+                pairs.push(("UseSkinSprites", flag(general.use_skin_sprites)));
+                pairs.push(("AlwaysShowPlayfield", flag(general.always_show_playfield)));
+                pairs.push(("OverlayPosition", general.overlay_position.to_string()));
+                if !general.skin_preference.is_empty() {
+                    pairs.push(("SkinPreference", general.skin_preference.clone()));
+                }
+                pairs.push(("EpilepsyWarning", flag(general.epilepsy_warning)));
+                pairs.push(("CountdownOffset", general.countdown_offset.to_string()));
+                pairs.push(("SpecialStyle", flag(general.special_style)));
+                pairs.push(("WidescreenStoryboard", flag(general.widescreen_storyboard)));
+                pairs.push((
+                    "SamplesMatchPlaybackRate",
+                    flag(self.audio.samples_match_playback_rate),
+                ));
+            }
+
+            "Editor" => {
+                let editor = &self.editor;
+
+                if !editor.bookmarks.is_empty() {
+                    pairs.push(("Bookmarks", join(&editor.bookmarks, ",")));
+                }
+                pairs.push(("DistanceSpacing", editor.distance_spacing.to_string()));
+                pairs.push(("BeatDivisor", editor.beat_divisor.to_string()));
+                pairs.push(("GridSize", editor.grid_size.to_string()));
+                pairs.push(("TimelineZoom", editor.timeline_zoom.to_string()));
+            }
+
+            "Metadata" => {
+                pairs.push(("Title", self.title.clone()));
+                pairs.push(("TitleUnicode", self.title_unicode.clone()));
+                pairs.push(("Artist", self.artist.clone()));
+                pairs.push(("ArtistUnicode", self.artist_unicode.clone()));
+                pairs.push(("Creator", self.creator.clone()));
+                pairs.push(("Version", self.difficulty_name.clone()));
+                pairs.push(("Source", self.source.clone()));
+                pairs.push(("Tags", self.metadata.tags.join(" ")));
+                pairs.push((
+                    "BeatmapID",
+                    self.metadata.beatmap_id.unwrap_or(-1).to_string(),
+                ));
+                pairs.push((
+                    "BeatmapSetID",
+                    self.metadata.beatmapset_id.unwrap_or(-1).to_string(),
+                ));
+            }
+
+            "Difficulty" => {
+                let difficulty = &self.difficulty;
+
+                pairs.push(("HPDrainRate", difficulty.hp_drain.to_string()));
+                pairs.push(("CircleSize", difficulty.circle_size.to_string()));
+                pairs.push((
+                    "OverallDifficulty",
+                    difficulty.overall_difficulty.to_string(),
+                ));
+                pairs.push(("ApproachRate", difficulty.approach_rate.to_string()));
+                pairs.push(("SliderMultiplier", difficulty.slider_multiplier.to_string()));
+                pairs.push(("SliderTickRate", difficulty.slider_tickrate.to_string()));
+            }
+
+            "Colours" => {
+                let colours = &self.colours;
+                let mut combos = vec![];
+
+                for (index, colour) in colours.combo_colours.iter().enumerate() {
+                    combos.push((format!("Combo{}", index + 1), rgb(colour)));
+                }
+
+                if let Some(colour) = &colours.slider_track_override {
+                    combos.push(("SliderTrackOverride".to_string(), rgb(colour)));
+                }
+
+                if let Some(colour) = &colours.slider_border {
+                    combos.push(("SliderBorder".to_string(), rgb(colour)));
+                }
+
+                return combos;
+            }
+
+            _ => {}
+        }
+
+        pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    // one line per event, timing point or hit object
+    pub(crate) fn section_items(&self, section: &str) -> Vec<String> {
+        match section {
+            "Events" => self
+                .events
+                .iter()
+                .map(|event| self.format_event(event))
+                .collect(),
+            "TimingPoints" => self
+                .timing_points
+                .iter()
+                .map(|point| self.format_timing_point(point))
+                .collect(),
+            "HitObjects" => self
+                .hit_objects
+                .iter()
+                .map(BeatmapFile::format_hit_object)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    // v4 and older maps are read 24ms late, undo that so the file reads back the same
+    fn time_offset(&self) -> f32 {
+        if self.format_version < 5 {
+            24.0
+        } else {
+            0.0
+        }
+    }
+
+    fn format_event(&self, event: &Event) -> String {
+        let offset = self.time_offset();
+
+        match event {
+            Event::Background {
+                filename,
+                x_offset,
+                y_offset,
+            } => format!("0,0,\"{}\",{},{}", filename, x_offset, y_offset),
+            Event::Video {
+                start_time,
+                filename,
+                x_offset,
+                y_offset,
+            } => format!(
+                "Video,{},\"{}\",{},{}",
+                start_time - offset,
+                filename,
+                x_offset,
+                y_offset
+            ),
+            Event::Break(period) => format!(
+                "2,{},{}",
+                period.start_time - offset,
+                period.end_time - offset
+            ),
+            Event::Raw(line) => line.clone(),
+        }
+    }
+
+    fn format_timing_point(&self, point: &TimingPoint) -> String {
+        let uninherited = point.point_type as i32 == TimingPointType::Uninherited as i32;
+
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            point.time - self.time_offset(),
+            point.beat_length,
+            point.time_signature,
+            point.sample_set as i32,
+            point.sample_index,
+            point.volume,
+            flag(uninherited),
+            point.effects
+        )
+    }
+
+    fn format_hit_object(hit_object: &HitObject) -> String {
+        let mut line = format!(
+            "{},{},{},{},{}",
+            hit_object.x,
+            hit_object.y,
+            hit_object.start_time,
+            hit_object.hit_type,
+            hit_object.hit_sound
+        );
+
+        let hit_sample = hit_object
+            .extra_data
+            .as_ref()
+            .map(|extra| format_hit_sample(&extra.hit_sample));
+
+        if hit_object.hit_type & HitType::Hold as i32 != 0 {
+            // mania holds pack the end time in front of the hitsample
+            line += &format!(
+                ",{}:{}",
+                hit_object.end_time,
+                hit_sample.unwrap_or_else(|| format_hit_sample(&HitSample::default()))
+            );
+            return line;
+        }
+
+        if let Some(slider) = &hit_object.slider_data {
+            let points: Vec<String> = slider
+                .base_points
+                .iter()
+                .map(|point| format!("{}:{}", point.x, point.y))
+                .collect();
+
+            line += &format!(
+                ",{}|{},{},{}",
+                slider.curve_type,
+                points.join("|"),
+                slider.slides,
+                slider.length
+            );
+
+            // edge fields are positional, leave them empty when only the hitsample is set
+            if !slider.edge_sounds.is_empty()
+                || !slider.edge_sets.is_empty()
+                || hit_sample.is_some()
+            {
+                let edge_sets: Vec<String> = slider
+                    .edge_sets
+                    .iter()
+                    .map(|(normal_set, addition_set)| format!("{}:{}", normal_set, addition_set))
+                    .collect();
+
+                line += &format!(
+                    ",{},{}",
+                    join(&slider.edge_sounds, "|"),
+                    edge_sets.join("|")
+                );
+            }
+        } else if hit_object.hit_type & HitType::Spinner as i32 != 0 {
+            line += &format!(",{}", hit_object.end_time);
+        }
+
+        if let Some(hit_sample) = hit_sample {
+            line += &format!(",{}", hit_sample);
+        }
+
+        line
+    }
+}
+
+pub(crate) fn pair_separator(section: &str) -> &'static str {
+    match section {
+        "General" | "Editor" => ": ",
+        "Colours" => " : ",
+        _ => ":",
+    }
+}
+
+fn flag(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
+
+fn join<T: ToString>(values: &[T], separator: &str) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn rgb(colour: &Colour) -> String {
+    format!("{},{},{}", colour.r, colour.g, colour.b)
+}
+
+fn format_hit_sample(hit_sample: &HitSample) -> String {
+    format!(
+        "{}:{}:{}:{}:{}",
+        hit_sample.normal_set,
+        hit_sample.additional_set,
+        hit_sample.index,
+        hit_sample.volume,
+        hit_sample.file_name
+    )
+}
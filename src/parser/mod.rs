@@ -1,2 +1,3 @@
 pub mod beatmap;
 pub mod replays;
+pub mod storyboard;
@@ -0,0 +1,61 @@
+use std::{error::Error, fmt, io};
+
+#[derive(Debug)]
+pub enum ReplayErrorKind {
+    Io(io::Error),
+    UnexpectedEof,
+    InvalidValue,
+    Lzma(String),
+}
+
+#[derive(Debug)]
+pub struct ReplayError {
+    pub kind: ReplayErrorKind,
+    // byte offset into the file, frames are reported by their index instead
+    pub offset: usize,
+    pub field: String,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ReplayErrorKind::Io(err) => write!(f, "could not read `{}`: {}", self.field, err),
+            ReplayErrorKind::UnexpectedEof => write!(
+                f,
+                "offset {}: file ended while reading {}",
+                self.offset, self.field
+            ),
+            ReplayErrorKind::InvalidValue => {
+                write!(
+                    f,
+                    "offset {}: invalid value for {}",
+                    self.offset, self.field
+                )
+            }
+            ReplayErrorKind::Lzma(err) => write!(
+                f,
+                "offset {}: could not decompress {}: {}",
+                self.offset, self.field, err
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ReplayErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ReplayError {
+    pub fn io(err: io::Error, path: &str) -> Self {
+        ReplayError {
+            kind: ReplayErrorKind::Io(err),
+            offset: 0,
+            field: path.to_string(),
+        }
+    }
+}
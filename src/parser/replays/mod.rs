@@ -1 +1,236 @@
+use std::{convert::TryFrom, fs, path::PathBuf};
+
+use crate::{
+    game::{score::Score, Gamemode, ModSet, Mods},
+    replay::Frame,
+};
+
+use self::{
+    error::{ReplayError, ReplayErrorKind},
+    objects::LifeBarPoint,
+    reader::ReplayReader,
+};
+
+// exports
+pub mod error;
 pub mod objects;
+mod reader;
+mod writer;
+
+// the time field of the frame carrying the rng seed
+const SEED_FRAME_TIME: i64 = -12345;
+
+// versions that changed the layout of the trailing fields
+const SCORE_ID_VERSION: i32 = 20121008;
+const LONG_SCORE_ID_VERSION: i32 = 20140721;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub gamemode: Gamemode,
+    pub game_version: i32,
+    pub beatmap_hash: String,
+    pub player_name: String,
+    pub replay_hash: String,
+
+    // judgements
+    pub count_300: i32,
+    pub count_100: i32,
+    pub count_50: i32,
+    pub count_geki: i32,
+    pub count_katu: i32,
+    pub count_miss: i32,
+
+    pub score: i32,
+    pub max_combo: i32,
+    pub perfect: bool,
+    pub mods: ModSet,
+
+    pub life_bar: Vec<LifeBarPoint>,
+    // windows ticks, 100ns since 0001-01-01
+    pub timestamp: i64,
+    pub frames: Vec<Frame>,
+    pub seed: Option<i32>,
+    pub online_score_id: i64,
+    // only stored for target practice plays
+    pub target_accuracy: Option<f64>,
+}
+
+impl Replay {
+    pub fn from_file(path: &str) -> Replay {
+        Replay::try_from_file(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_pathbuf(path: PathBuf) -> Replay {
+        Replay::try_from_pathbuf(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Replay {
+        Replay::try_from_bytes(data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_file(path: &str) -> Result<Replay, ReplayError> {
+        let data = fs::read(path).map_err(|e| ReplayError::io(e, path))?;
+        Replay::try_from_bytes(&data)
+    }
+
+    pub fn try_from_pathbuf(path: PathBuf) -> Result<Replay, ReplayError> {
+        let data = fs::read(&path).map_err(|e| ReplayError::io(e, &path.to_string_lossy()))?;
+        Replay::try_from_bytes(&data)
+    }
+
+    pub fn try_from_bytes(data: &[u8]) -> Result<Replay, ReplayError> {
+        let mut reader = ReplayReader::new(data);
+
+        let mode = reader.u8("mode")?;
+        let gamemode = Gamemode::try_from(mode as i32)
+            .map_err(|_| reader.error(ReplayErrorKind::InvalidValue, "mode"))?;
+
+        let mut replay = Replay {
+            gamemode,
+            game_version: reader.i32("version")?,
+            beatmap_hash: reader.string("beatmap hash")?,
+            player_name: reader.string("player name")?,
+            replay_hash: reader.string("replay hash")?,
+            count_300: reader.i16("300s")? as u16 as i32,
+            count_100: reader.i16("100s")? as u16 as i32,
+            count_50: reader.i16("50s")? as u16 as i32,
+            count_geki: reader.i16("gekis")? as u16 as i32,
+            count_katu: reader.i16("katus")? as u16 as i32,
+            count_miss: reader.i16("misses")? as u16 as i32,
+            score: reader.i32("score")?,
+            max_combo: reader.i16("max combo")? as u16 as i32,
+            perfect: reader.u8("perfect")? != 0,
+            mods: ModSet::from_bits(reader.i32("mods")? as u32 as i64),
+            life_bar: vec![],
+            timestamp: 0,
+            frames: vec![],
+            seed: None,
+            online_score_id: 0,
+            target_accuracy: None,
+        };
+
+        let life_bar_offset = reader.offset;
+        let life_bar = reader.string("life bar")?;
+        replay.life_bar = Replay::parse_life_bar(&life_bar).ok_or_else(|| ReplayError {
+            kind: ReplayErrorKind::InvalidValue,
+            offset: life_bar_offset,
+            field: "life bar".to_string(),
+        })?;
+
+        replay.timestamp = reader.i64("timestamp")?;
+
+        let length = reader.i32("replay length")?;
+        let frames_offset = reader.offset;
+        let compressed = reader.bytes(length.max(0) as usize, "replay data")?;
+        replay.parse_frames(compressed, frames_offset)?;
+
+        // older replays end early
+        if replay.game_version >= LONG_SCORE_ID_VERSION {
+            replay.online_score_id = reader.i64("online score id")?;
+        } else if replay.game_version >= SCORE_ID_VERSION && !reader.is_empty() {
+            replay.online_score_id = reader.i32("online score id")? as i64;
+        }
+
+        if replay.mods.contains(Mods::Target) && !reader.is_empty() {
+            replay.target_accuracy = Some(reader.f64("target accuracy")?);
+        }
+
+        Ok(replay)
+    }
+
+    // the play the replay records, replays don't know which map or player they're for
+    pub fn score(&self) -> Score {
+        Score {
+            score_id: self.online_score_id,
+            user_id: 0,
+            beatmap_id: 0,
+            mods: self.mods,
+            score: self.score,
+            max_combo: self.max_combo,
+            count_300: self.count_300,
+            count_100: self.count_100,
+            count_50: self.count_50,
+            count_geki: self.count_geki,
+            count_katu: self.count_katu,
+            count_miss: self.count_miss,
+        }
+    }
+
+    // "time|life,time|life,"
+    fn parse_life_bar(life_bar: &str) -> Option<Vec<LifeBarPoint>> {
+        life_bar
+            .split(',')
+            .filter(|point| !point.trim().is_empty())
+            .map(|point| {
+                let (time, life) = point.split_once('|')?;
+
+                Some(LifeBarPoint {
+                    time: time.trim().parse().ok()?,
+                    life: life.trim().parse().ok()?,
+                })
+            })
+            .collect()
+    }
+
+    fn parse_frames(&mut self, compressed: &[u8], offset: usize) -> Result<(), ReplayError> {
+        let error = |kind: ReplayErrorKind, field: String| ReplayError {
+            kind,
+            offset,
+            field,
+        };
+
+        if compressed.is_empty() {
+            return Ok(());
+        }
+
+        let mut decompressed = vec![];
+        lzma_rs::lzma_decompress(&mut &compressed[..], &mut decompressed)
+            .map_err(|e| error(ReplayErrorKind::Lzma(e.to_string()), "replay data".into()))?;
+
+        let data = String::from_utf8(decompressed)
+            .map_err(|_| error(ReplayErrorKind::InvalidValue, "replay data".into()))?;
+
+        // frames are "delta|x|y|keys" separated by commas
+        let mut time: i64 = 0;
+        for (index, frame) in data.split(',').enumerate() {
+            if frame.trim().is_empty() {
+                continue;
+            }
+
+            let field = || format!("frame {}", index);
+            let values: Vec<&str> = frame.split('|').map(|s| s.trim()).collect();
+
+            if values.len() < 4 {
+                return Err(error(ReplayErrorKind::UnexpectedEof, field()));
+            }
+
+            let delta: i64 = values[0]
+                .parse()
+                .map_err(|_| error(ReplayErrorKind::InvalidValue, field()))?;
+            let x: f32 = values[1]
+                .parse()
+                .map_err(|_| error(ReplayErrorKind::InvalidValue, field()))?;
+            let y: f32 = values[2]
+                .parse()
+                .map_err(|_| error(ReplayErrorKind::InvalidValue, field()))?;
+            let keys: i32 = values[3]
+                .parse()
+                .map_err(|_| error(ReplayErrorKind::InvalidValue, field()))?;
+
+            if delta == SEED_FRAME_TIME && x == 0.0 && y == 0.0 {
+                self.seed = Some(keys);
+                continue;
+            }
+
+            time += delta;
+            self.frames.push(Frame {
+                time: time as i32,
+                x,
+                y,
+                keys,
+            });
+        }
+
+        Ok(())
+    }
+}
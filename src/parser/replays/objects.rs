@@ -0,0 +1,6 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LifeBarPoint {
+    pub time: i32,
+    // 0.0 to 1.0
+    pub life: f32,
+}
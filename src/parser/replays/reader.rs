@@ -0,0 +1,101 @@
+use std::convert::TryInto;
+
+use super::error::{ReplayError, ReplayErrorKind};
+
+// little endian reader over the raw .osr bytes
+pub(crate) struct ReplayReader<'a> {
+    data: &'a [u8],
+    pub offset: usize,
+}
+
+impl<'a> ReplayReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        ReplayReader { data, offset: 0 }
+    }
+
+    pub fn error(&self, kind: ReplayErrorKind, field: &str) -> ReplayError {
+        ReplayError {
+            kind,
+            offset: self.offset,
+            field: field.to_string(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    pub fn bytes(&mut self, count: usize, field: &str) -> Result<&'a [u8], ReplayError> {
+        let end = self
+            .offset
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.error(ReplayErrorKind::UnexpectedEof, field))?;
+
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self, field: &str) -> Result<u8, ReplayError> {
+        Ok(self.bytes(1, field)?[0])
+    }
+
+    pub fn i16(&mut self, field: &str) -> Result<i16, ReplayError> {
+        Ok(i16::from_le_bytes(
+            self.bytes(2, field)?.try_into().unwrap(),
+        ))
+    }
+
+    pub fn i32(&mut self, field: &str) -> Result<i32, ReplayError> {
+        Ok(i32::from_le_bytes(
+            self.bytes(4, field)?.try_into().unwrap(),
+        ))
+    }
+
+    pub fn i64(&mut self, field: &str) -> Result<i64, ReplayError> {
+        Ok(i64::from_le_bytes(
+            self.bytes(8, field)?.try_into().unwrap(),
+        ))
+    }
+
+    pub fn f64(&mut self, field: &str) -> Result<f64, ReplayError> {
+        Ok(f64::from_le_bytes(
+            self.bytes(8, field)?.try_into().unwrap(),
+        ))
+    }
+
+    pub fn uleb128(&mut self, field: &str) -> Result<usize, ReplayError> {
+        let mut value = 0usize;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.u8(field)?;
+            if shift >= usize::BITS {
+                return Err(self.error(ReplayErrorKind::InvalidValue, field));
+            }
+
+            value |= ((byte & 0x7f) as usize) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    // 0x00 for an empty string, otherwise 0x0b followed by the length and utf-8 bytes
+    pub fn string(&mut self, field: &str) -> Result<String, ReplayError> {
+        match self.u8(field)? {
+            0x00 => Ok(String::new()),
+            0x0b => {
+                let length = self.uleb128(field)?;
+                let bytes = self.bytes(length, field)?;
+
+                String::from_utf8(bytes.to_vec())
+                    .map_err(|_| self.error(ReplayErrorKind::InvalidValue, field))
+            }
+            _ => Err(self.error(ReplayErrorKind::InvalidValue, field)),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::io::{self, Write};
+
+use crate::{
+    game::Mods,
+    parser::replays::{Replay, LONG_SCORE_ID_VERSION, SCORE_ID_VERSION, SEED_FRAME_TIME},
+};
+
+impl Replay {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+        self.write_to(&mut buffer)
+            .expect("writing to a Vec<u8> cannot fail");
+
+        buffer
+    }
+
+    // an empty `replay_hash` is filled in with `replay_checksum`
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let replay_hash = if self.replay_hash.is_empty() {
+            self.replay_checksum()
+        } else {
+            self.replay_hash.clone()
+        };
+
+        writer.write_all(&[self.gamemode as u8])?;
+        writer.write_all(&self.game_version.to_le_bytes())?;
+        write_string(&mut writer, &self.beatmap_hash)?;
+        write_string(&mut writer, &self.player_name)?;
+        write_string(&mut writer, &replay_hash)?;
+
+        for count in &[
+            self.count_300,
+            self.count_100,
+            self.count_50,
+            self.count_geki,
+            self.count_katu,
+            self.count_miss,
+        ] {
+            writer.write_all(&(*count as u16).to_le_bytes())?;
+        }
+
+        writer.write_all(&self.score.to_le_bytes())?;
+        writer.write_all(&(self.max_combo as u16).to_le_bytes())?;
+        writer.write_all(&[self.perfect as u8])?;
+        writer.write_all(&(self.mods.bits() as u32).to_le_bytes())?;
+        write_string(&mut writer, &self.life_bar_string())?;
+        writer.write_all(&self.timestamp.to_le_bytes())?;
+
+        let compressed = self.compress_frames()?;
+        writer.write_all(&(compressed.len() as i32).to_le_bytes())?;
+        writer.write_all(&compressed)?;
+
+        if self.game_version >= LONG_SCORE_ID_VERSION {
+            writer.write_all(&self.online_score_id.to_le_bytes())?;
+        } else if self.game_version >= SCORE_ID_VERSION {
+            writer.write_all(&(self.online_score_id as i32).to_le_bytes())?;
+        }
+
+        if self.mods.contains(Mods::Target) {
+            writer.write_all(&self.target_accuracy.unwrap_or(0.0).to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    // the md5 osu!stable stores in the replay, booleans are written the way .NET writes
+    // them. the trailing "True" is the pass flag, there are no replays of failed plays
+    pub fn replay_checksum(&self) -> String {
+        let summary = format!(
+            "{}p{}o{}o{}t{}a{}r{}e{}y{}o{}u{}{}True",
+            self.count_100 + self.count_300,
+            self.count_50,
+            self.count_geki,
+            self.count_katu,
+            self.count_miss,
+            self.beatmap_hash,
+            self.max_combo,
+            if self.perfect { "True" } else { "False" },
+            self.player_name,
+            self.score,
+            self.score().grade(self.gamemode),
+            self.mods.bits()
+        );
+
+        format!("{:x}", md5::compute(summary))
+    }
+
+    // "time|life,time|life,"
+    pub fn life_bar_string(&self) -> String {
+        self.life_bar
+            .iter()
+            .map(|point| format!("{}|{},", point.time, point.life))
+            .collect()
+    }
+
+    fn compress_frames(&self) -> io::Result<Vec<u8>> {
+        let mut data = String::new();
+        let mut last_time = 0;
+
+        for frame in &self.frames {
+            data += &format!(
+                "{}|{}|{}|{},",
+                frame.time as i64 - last_time,
+                frame.x,
+                frame.y,
+                frame.keys
+            );
+            last_time = frame.time as i64;
+        }
+
+        if let Some(seed) = self.seed {
+            data += &format!("{}|0|0|{},", SEED_FRAME_TIME, seed);
+        }
+
+        let mut compressed = vec![];
+        lzma_rs::lzma_compress(&mut data.as_bytes(), &mut compressed)?;
+        Ok(compressed)
+    }
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    if value.is_empty() {
+        return writer.write_all(&[0x00]);
+    }
+
+    writer.write_all(&[0x0b])?;
+
+    let mut length = value.len();
+    loop {
+        let byte = (length & 0x7f) as u8;
+        length >>= 7;
+
+        if length == 0 {
+            writer.write_all(&[byte])?;
+            break;
+        }
+
+        writer.write_all(&[byte | 0x80])?;
+    }
+
+    writer.write_all(value.as_bytes())
+}
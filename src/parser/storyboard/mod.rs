@@ -0,0 +1,365 @@
+use std::{cmp::Reverse, fs, path::PathBuf};
+
+use crate::{
+    parser::beatmap::{
+        error::{LineContext, ParseError, ParseErrorKind},
+        events::unquote,
+    },
+    util::Vector2,
+};
+
+use self::objects::{
+    Animation, Command, CommandKind, CommandLoop, CommandTrigger, Element, LoopType, Sample, Sprite,
+};
+
+// exports
+pub mod objects;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Storyboard {
+    pub variables: Vec<(String, String)>,
+    pub elements: Vec<Element>,
+}
+
+// which command list the next indented command belongs to
+enum CommandTarget {
+    Sprite,
+    Loop,
+    Trigger,
+}
+
+impl Storyboard {
+    pub fn from_file(path: &str) -> Storyboard {
+        Storyboard::try_from_file(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_pathbuf(path: PathBuf) -> Storyboard {
+        Storyboard::try_from_pathbuf(path).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(storyboard_string: &str) -> Storyboard {
+        Storyboard::try_from_str(storyboard_string).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_file(path: &str) -> Result<Storyboard, ParseError> {
+        let storyboard_string = fs::read_to_string(path).map_err(|e| ParseError::io(e, path))?;
+        Storyboard::try_from_str(&storyboard_string)
+    }
+
+    pub fn try_from_pathbuf(path: PathBuf) -> Result<Storyboard, ParseError> {
+        let storyboard_string =
+            fs::read_to_string(&path).map_err(|e| ParseError::io(e, &path.to_string_lossy()))?;
+        Storyboard::try_from_str(&storyboard_string)
+    }
+
+    // reads the [Variables] and [Events] sections, so this takes either a .osb or a .osu
+    pub fn try_from_str(storyboard_string: &str) -> Result<Storyboard, ParseError> {
+        let lines = storyboard_string.trim_start_matches('\u{feff}').lines();
+        let mut section = "";
+
+        let mut storyboard = Storyboard::default();
+        let mut target = CommandTarget::Sprite;
+
+        for (index, s) in lines.enumerate() {
+            let s = s.trim_end();
+
+            if s.is_empty() || s.starts_with("//") {
+                // ignore comments
+                continue;
+            }
+
+            if s.starts_with('[') && s.ends_with(']') {
+                section = s[1..s.len() - 1].trim();
+                continue;
+            }
+
+            match section {
+                "Variables" => {
+                    if let Some((name, value)) = s.split_once('=') {
+                        storyboard
+                            .variables
+                            .push((name.trim().to_string(), value.trim().to_string()));
+
+                        // substitute longer names first so $ab doesn't get eaten by $a
+                        storyboard
+                            .variables
+                            .sort_by_key(|(name, _)| Reverse(name.len()));
+                    }
+                }
+
+                "Events" => {
+                    let text = storyboard.substitute(s);
+                    let line = LineContext {
+                        number: index + 1,
+                        section,
+                        text: &text,
+                    };
+
+                    let depth = text.len() - text.trim_start_matches(&[' ', '_'][..]).len();
+                    match depth {
+                        0 => {
+                            if let Some(element) = Storyboard::parse_element(&line)? {
+                                storyboard.elements.push(element);
+                            }
+                            target = CommandTarget::Sprite;
+                        }
+                        1 => target = storyboard.parse_command(&line, &text[1..])?,
+                        _ => {
+                            storyboard.parse_nested_command(&line, &target, &text[depth..])?;
+                        }
+                    }
+                }
+
+                _ => continue,
+            }
+        }
+
+        Ok(storyboard)
+    }
+
+    pub fn sprites(&self) -> Vec<&Sprite> {
+        self.elements
+            .iter()
+            .filter_map(|element| match element {
+                Element::Sprite(sprite) => Some(sprite),
+                Element::Animation(animation) => Some(&animation.sprite),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // every file the storyboard would load, animations expanded per frame
+    pub fn files(&self) -> Vec<String> {
+        let mut files = vec![];
+
+        for element in &self.elements {
+            match element {
+                Element::Sprite(sprite) => files.push(sprite.path.clone()),
+                Element::Animation(animation) => files.extend(animation.frame_paths()),
+                Element::Sample(sample) => files.push(sample.path.clone()),
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    fn substitute(&self, text: &str) -> String {
+        let mut text = text.to_string();
+
+        if text.contains('$') {
+            for (name, value) in &self.variables {
+                text = text.replace(name.as_str(), value);
+            }
+        }
+
+        text
+    }
+
+    fn parse_element(line: &LineContext) -> Result<Option<Element>, ParseError> {
+        let values: Vec<&str> = line.text.split(',').map(|s| s.trim()).collect();
+
+        let sprite = |values: &[&str]| -> Result<Sprite, ParseError> {
+            Ok(Sprite {
+                layer: line.parse("layer", line.value(values, 1, "layer")?)?,
+                origin: line.parse("origin", line.value(values, 2, "origin")?)?,
+                path: unquote(line.value(values, 3, "filepath")?),
+                position: Vector2::new(
+                    line.parse("x", line.value(values, 4, "x")?)?,
+                    line.parse("y", line.value(values, 5, "y")?)?,
+                ),
+                commands: vec![],
+                loops: vec![],
+                triggers: vec![],
+            })
+        };
+
+        match values[0] {
+            "Sprite" | "4" => Ok(Some(Element::Sprite(sprite(&values)?))),
+            "Animation" | "6" => Ok(Some(Element::Animation(Animation {
+                sprite: sprite(&values)?,
+                frame_count: line.parse("frameCount", line.value(&values, 6, "frameCount")?)?,
+                frame_delay: line.parse("frameDelay", line.value(&values, 7, "frameDelay")?)?,
+                loop_type: match values.get(8) {
+                    Some(loop_type) => line.parse("looptype", loop_type)?,
+                    None => LoopType::LoopForever,
+                },
+            }))),
+            "Sample" | "5" => Ok(Some(Element::Sample(Sample {
+                time: line.parse("time", line.value(&values, 1, "time")?)?,
+                layer: line.parse("layer", line.value(&values, 2, "layer")?)?,
+                path: unquote(line.value(&values, 3, "filepath")?),
+                volume: match values.get(4) {
+                    Some(volume) => line.parse("volume", volume)?,
+                    None => 100,
+                },
+            }))),
+
+            // backgrounds, videos and breaks belong to the beatmap
+            _ => Ok(None),
+        }
+    }
+
+    fn current_sprite(&mut self, line: &LineContext) -> Result<&mut Sprite, ParseError> {
+        match self.elements.last_mut() {
+            Some(Element::Sprite(sprite)) => Ok(sprite),
+            Some(Element::Animation(animation)) => Ok(&mut animation.sprite),
+            _ => Err(line.error(ParseErrorKind::MissingValue, "sprite", line.text)),
+        }
+    }
+
+    fn parse_command(
+        &mut self,
+        line: &LineContext,
+        text: &str,
+    ) -> Result<CommandTarget, ParseError> {
+        let values: Vec<&str> = text.split(',').map(|s| s.trim()).collect();
+        let sprite = self.current_sprite(line)?;
+
+        match values[0] {
+            "L" => {
+                sprite.loops.push(CommandLoop {
+                    start_time: line.parse("startTime", line.value(&values, 1, "startTime")?)?,
+                    loop_count: line.parse("loopCount", line.value(&values, 2, "loopCount")?)?,
+                    commands: vec![],
+                });
+                Ok(CommandTarget::Loop)
+            }
+            "T" => {
+                sprite.triggers.push(CommandTrigger {
+                    trigger_name: line.value(&values, 1, "triggerType")?.to_string(),
+                    start_time: line.parse("startTime", line.value(&values, 2, "startTime")?)?,
+                    end_time: line.parse("endTime", line.value(&values, 3, "endTime")?)?,
+                    group_number: match values.get(4) {
+                        Some(group) => line.parse("groupNumber", group)?,
+                        None => 0,
+                    },
+                    commands: vec![],
+                });
+                Ok(CommandTarget::Trigger)
+            }
+            _ => {
+                sprite
+                    .commands
+                    .extend(Storyboard::parse_commands(line, &values)?);
+                Ok(CommandTarget::Sprite)
+            }
+        }
+    }
+
+    fn parse_nested_command(
+        &mut self,
+        line: &LineContext,
+        target: &CommandTarget,
+        text: &str,
+    ) -> Result<(), ParseError> {
+        let values: Vec<&str> = text.split(',').map(|s| s.trim()).collect();
+        let commands = Storyboard::parse_commands(line, &values)?;
+        let sprite = self.current_sprite(line)?;
+
+        let group = match target {
+            CommandTarget::Loop => sprite.loops.last_mut().map(|l| &mut l.commands),
+            CommandTarget::Trigger => sprite.triggers.last_mut().map(|t| &mut t.commands),
+            CommandTarget::Sprite => None,
+        };
+
+        match group {
+            Some(group) => {
+                group.extend(commands);
+                Ok(())
+            }
+            None => Err(line.error(ParseErrorKind::MissingValue, "loop", line.text)),
+        }
+    }
+
+    // a command may chain several value sets, each one becomes its own command
+    // shifted along by the command's duration
+    fn parse_commands(line: &LineContext, values: &[&str]) -> Result<Vec<Command>, ParseError> {
+        let event = values[0];
+        let easing = line.parse("easing", line.value(values, 1, "easing")?)?;
+        let start_time: f32 = line.parse("startTime", line.value(values, 2, "startTime")?)?;
+        let end_time: f32 = match line.value(values, 3, "endTime")? {
+            "" => start_time,
+            end_time => line.parse("endTime", end_time)?,
+        };
+
+        let arity = match event {
+            "F" | "MX" | "MY" | "S" | "R" | "P" => 1,
+            "M" | "V" => 2,
+            "C" => 3,
+            _ => return Err(line.error(ParseErrorKind::InvalidValue, "event", event)),
+        };
+
+        let params = &values[4..];
+        if params.len() < arity {
+            return Err(line.error(ParseErrorKind::MissingValue, event, line.text));
+        }
+
+        let sets: Vec<&[&str]> = params
+            .chunks(arity)
+            .filter(|set| set.len() == arity)
+            .collect();
+        let pairs: Vec<(&[&str], &[&str])> = if sets.len() == 1 || event == "P" {
+            vec![(sets[0], sets[0])]
+        } else {
+            sets.windows(2).map(|pair| (pair[0], pair[1])).collect()
+        };
+
+        let duration = end_time - start_time;
+        let mut commands = vec![];
+
+        for (index, (start, end)) in pairs.into_iter().enumerate() {
+            let float = |value: &str| line.parse::<f32>(event, value);
+            let vector = |set: &[&str]| -> Result<Vector2, ParseError> {
+                Ok(Vector2::new(float(set[0])?, float(set[1])?))
+            };
+
+            let kind = match event {
+                "F" => CommandKind::Fade {
+                    start: float(start[0])?,
+                    end: float(end[0])?,
+                },
+                "MX" => CommandKind::MoveX {
+                    start: float(start[0])?,
+                    end: float(end[0])?,
+                },
+                "MY" => CommandKind::MoveY {
+                    start: float(start[0])?,
+                    end: float(end[0])?,
+                },
+                "S" => CommandKind::Scale {
+                    start: float(start[0])?,
+                    end: float(end[0])?,
+                },
+                "R" => CommandKind::Rotate {
+                    start: float(start[0])?,
+                    end: float(end[0])?,
+                },
+                "M" => CommandKind::Move {
+                    start: vector(start)?,
+                    end: vector(end)?,
+                },
+                "V" => CommandKind::VectorScale {
+                    start: vector(start)?,
+                    end: vector(end)?,
+                },
+                "C" => CommandKind::Colour {
+                    start: line.parse(event, &start.join(","))?,
+                    end: line.parse(event, &end.join(","))?,
+                },
+                _ => CommandKind::Parameter(line.parse(event, start[0])?),
+            };
+
+            commands.push(Command {
+                easing,
+                start_time: start_time + duration * index as f32,
+                end_time: end_time + duration * index as f32,
+                kind,
+            });
+        }
+
+        Ok(commands)
+    }
+}
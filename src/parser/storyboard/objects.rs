@@ -0,0 +1,282 @@
+use std::{convert::TryFrom, str::FromStr};
+
+use crate::util::{Colour, Vector2};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Sprite(Sprite),
+    Animation(Animation),
+    Sample(Sample),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    pub layer: Layer,
+    pub origin: Origin,
+    pub path: String,
+    pub position: Vector2,
+    pub commands: Vec<Command>,
+    pub loops: Vec<CommandLoop>,
+    pub triggers: Vec<CommandTrigger>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub sprite: Sprite,
+    pub frame_count: i32,
+    pub frame_delay: f32,
+    pub loop_type: LoopType,
+}
+
+impl Animation {
+    // "sb/blink.png" with 3 frames is drawn from sb/blink0.png, sb/blink1.png and sb/blink2.png
+    pub fn frame_paths(&self) -> Vec<String> {
+        let path = &self.sprite.path;
+        let (stem, extension) = match path.rfind('.') {
+            Some(index) => path.split_at(index),
+            None => (path.as_str(), ""),
+        };
+
+        (0..self.frame_count)
+            .map(|frame| format!("{}{}{}", stem, frame, extension))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub time: f32,
+    pub layer: Layer,
+    pub path: String,
+    pub volume: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLoop {
+    pub start_time: f32,
+    pub loop_count: i32,
+    // times are relative to the start of the loop
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandTrigger {
+    pub trigger_name: String,
+    pub start_time: f32,
+    pub end_time: f32,
+    pub group_number: i32,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub easing: Easing,
+    pub start_time: f32,
+    pub end_time: f32,
+    pub kind: CommandKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandKind {
+    Fade { start: f32, end: f32 },
+    Move { start: Vector2, end: Vector2 },
+    MoveX { start: f32, end: f32 },
+    MoveY { start: f32, end: f32 },
+    Scale { start: f32, end: f32 },
+    VectorScale { start: Vector2, end: Vector2 },
+    Rotate { start: f32, end: f32 },
+    Colour { start: Colour, end: Colour },
+    Parameter(Parameter),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layer {
+    Background,
+    Fail,
+    Pass,
+    Foreground,
+    Overlay,
+}
+
+impl FromStr for Layer {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "Background" | "0" => Ok(Layer::Background),
+            "Fail" | "1" => Ok(Layer::Fail),
+            "Pass" | "2" => Ok(Layer::Pass),
+            "Foreground" | "3" => Ok(Layer::Foreground),
+            "Overlay" | "4" => Ok(Layer::Overlay),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Origin {
+    TopLeft,
+    Centre,
+    CentreLeft,
+    TopRight,
+    BottomCentre,
+    TopCentre,
+    Custom,
+    CentreRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl FromStr for Origin {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "TopLeft" | "0" => Ok(Origin::TopLeft),
+            "Centre" | "1" => Ok(Origin::Centre),
+            "CentreLeft" | "2" => Ok(Origin::CentreLeft),
+            "TopRight" | "3" => Ok(Origin::TopRight),
+            "BottomCentre" | "4" => Ok(Origin::BottomCentre),
+            "TopCentre" | "5" => Ok(Origin::TopCentre),
+            "Custom" | "6" => Ok(Origin::Custom),
+            "CentreRight" | "7" => Ok(Origin::CentreRight),
+            "BottomLeft" | "8" => Ok(Origin::BottomLeft),
+            "BottomRight" | "9" => Ok(Origin::BottomRight),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopType {
+    LoopForever,
+    LoopOnce,
+}
+
+impl FromStr for LoopType {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "LoopForever" | "0" => Ok(LoopType::LoopForever),
+            "LoopOnce" | "1" => Ok(LoopType::LoopOnce),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+    FlipHorizontal,
+    FlipVertical,
+    AdditiveBlend,
+}
+
+impl FromStr for Parameter {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "H" => Ok(Parameter::FlipHorizontal),
+            "V" => Ok(Parameter::FlipVertical),
+            "A" => Ok(Parameter::AdditiveBlend),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EasingOut,
+    EasingIn,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticHalfOut,
+    ElasticQuarterOut,
+    ElasticInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+const EASINGS: [Easing; 35] = [
+    Easing::Linear,
+    Easing::EasingOut,
+    Easing::EasingIn,
+    Easing::QuadIn,
+    Easing::QuadOut,
+    Easing::QuadInOut,
+    Easing::CubicIn,
+    Easing::CubicOut,
+    Easing::CubicInOut,
+    Easing::QuartIn,
+    Easing::QuartOut,
+    Easing::QuartInOut,
+    Easing::QuintIn,
+    Easing::QuintOut,
+    Easing::QuintInOut,
+    Easing::SineIn,
+    Easing::SineOut,
+    Easing::SineInOut,
+    Easing::ExpoIn,
+    Easing::ExpoOut,
+    Easing::ExpoInOut,
+    Easing::CircIn,
+    Easing::CircOut,
+    Easing::CircInOut,
+    Easing::ElasticIn,
+    Easing::ElasticOut,
+    Easing::ElasticHalfOut,
+    Easing::ElasticQuarterOut,
+    Easing::ElasticInOut,
+    Easing::BackIn,
+    Easing::BackOut,
+    Easing::BackInOut,
+    Easing::BounceIn,
+    Easing::BounceOut,
+    Easing::BounceInOut,
+];
+
+impl TryFrom<i32> for Easing {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        usize::try_from(v)
+            .ok()
+            .and_then(|index| EASINGS.get(index).copied())
+            .ok_or(())
+    }
+}
+
+impl FromStr for Easing {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Easing::try_from(input.parse::<i32>().map_err(|_| ())?)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    difficulty::catch::CatchDifficulty,
+    game::{
+        score::{PartialScore, Score},
+        Mods,
+    },
+};
+
+// catch scores count fruits as 300s, droplets as 100s, tiny droplets as 50s and the
+// tiny droplets that were missed as katus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CatchPerformance {
+    pub total: f64,
+}
+
+// 0-1, every fruit and droplet of any size is worth the same
+pub fn accuracy(score: &Score) -> f64 {
+    let total =
+        (score.count_300 + score.count_100 + score.count_50 + score.count_katu + score.count_miss)
+            as f64;
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    ((score.count_300 + score.count_100 + score.count_50) as f64 / total).clamp(0.0, 1.0)
+}
+
+// misses are taken from droplets before fruits, and the rest of the accuracy is lost
+// on tiny droplets
+pub fn score_for(difficulty: &CatchDifficulty, partial: &PartialScore) -> Score {
+    let count_miss = partial.count_miss.clamp(0, difficulty.max_combo);
+    let count_100 = (difficulty.droplet_count - count_miss).max(0);
+    let count_300 = difficulty.fruit_count - (count_miss - (difficulty.droplet_count - count_100));
+
+    let accuracy = (partial.accuracy / 100.0).clamp(0.0, 1.0);
+    let total = difficulty.max_combo + difficulty.tiny_droplet_count;
+    let count_50 = ((accuracy * total as f64).round() as i32 - count_300 - count_100)
+        .clamp(0, difficulty.tiny_droplet_count);
+
+    let max_combo = partial
+        .max_combo
+        .unwrap_or(difficulty.max_combo)
+        .clamp(0, difficulty.max_combo);
+
+    Score {
+        score_id: 0,
+        user_id: 0,
+        beatmap_id: 0,
+        mods: partial.mods,
+        score: 0,
+        max_combo,
+        count_300,
+        count_100,
+        count_50,
+        count_geki: 0,
+        count_katu: difficulty.tiny_droplet_count - count_50,
+        count_miss,
+    }
+}
+
+pub fn calculate_partial(difficulty: &CatchDifficulty, partial: &PartialScore) -> CatchPerformance {
+    calculate(difficulty, &score_for(difficulty, partial))
+}
+
+// the difficulty should be calculated with the same mods as the score
+pub fn calculate(difficulty: &CatchDifficulty, score: &Score) -> CatchPerformance {
+    let has = |m: Mods| score.mods.contains(m);
+
+    // tiny droplets don't give combo
+    let combo_hits = (score.count_300 + score.count_100 + score.count_miss) as f64;
+    if combo_hits == 0.0 {
+        return CatchPerformance { total: 0.0 };
+    }
+
+    let mut value = (5.0 * (difficulty.stars / 0.0049).max(1.0) - 4.0).powi(2) / 100000.0;
+
+    let mut length_bonus = 0.95 + 0.3 * (combo_hits / 2500.0).min(1.0);
+    if combo_hits > 2500.0 {
+        length_bonus += (combo_hits / 2500.0).log10() * 0.475;
+    }
+    value *= length_bonus;
+
+    value *= 0.97f64.powi(score.count_miss);
+
+    if difficulty.max_combo > 0 {
+        value *=
+            ((score.max_combo as f64).powf(0.8) / (difficulty.max_combo as f64).powf(0.8)).min(1.0);
+    }
+
+    let approach_rate = difficulty.approach_rate;
+    let mut approach_rate_factor = 1.0;
+    if approach_rate > 9.0 {
+        approach_rate_factor += 0.1 * (approach_rate - 9.0);
+    }
+    if approach_rate > 10.0 {
+        approach_rate_factor += 0.1 * (approach_rate - 10.0);
+    } else if approach_rate < 8.0 {
+        approach_rate_factor += 0.025 * (8.0 - approach_rate);
+    }
+    value *= approach_rate_factor;
+
+    if has(Mods::Hidden) {
+        // hidden is worth less the less time there is to read anyway
+        if approach_rate <= 10.0 {
+            value *= 1.05 + 0.075 * (10.0 - approach_rate);
+        } else {
+            value *= 1.01 + 0.04 * (11.0 - approach_rate.min(11.0));
+        }
+    }
+
+    if has(Mods::Flashlight) {
+        value *= 1.35 * length_bonus;
+    }
+
+    value *= accuracy(score).powf(5.5);
+
+    if has(Mods::NoFail) {
+        value *= 0.9;
+    }
+
+    CatchPerformance { total: value }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    difficulty::mania::ManiaDifficulty,
+    game::{
+        score::{PartialScore, Score},
+        Mods,
+    },
+};
+
+// mania scores count MAXes as gekis and 200s as katus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManiaPerformance {
+    pub difficulty: f64,
+    pub total: f64,
+}
+
+// 0-1, MAXes are worth a little more than 300s
+pub fn accuracy(score: &Score) -> f64 {
+    let total = total_hits(score);
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    (score.count_geki * 320
+        + score.count_300 * 300
+        + score.count_katu * 200
+        + score.count_100 * 100
+        + score.count_50 * 50) as f64
+        / (total * 320.0)
+}
+
+fn total_hits(score: &Score) -> f64 {
+    (score.count_geki
+        + score.count_300
+        + score.count_katu
+        + score.count_100
+        + score.count_50
+        + score.count_miss) as f64
+}
+
+// spreads the partial score's accuracy over the map's notes and holds. everything
+// starts out as a 50 and is upgraded as far as the accuracy allows, osu!stable's
+// accuracy doesn't tell MAXes and 300s apart so they're all taken to be MAXes
+pub fn score_for(difficulty: &ManiaDifficulty, partial: &PartialScore) -> Score {
+    let total = difficulty.note_count + difficulty.hold_count;
+    let count_miss = partial.count_miss.clamp(0, total);
+    let accuracy = (partial.accuracy / 100.0).clamp(0.0, 1.0);
+
+    // 300s and MAXes are 6, 200s 4, 100s 2 and 50s 1
+    let target = (accuracy * total as f64 * 6.0).round() as i32;
+    let mut remaining = total - count_miss;
+    let mut delta = (target - remaining).max(0);
+
+    let count_geki = (delta / 5).min(remaining);
+    delta -= count_geki * 5;
+    remaining -= count_geki;
+
+    let count_katu = (delta / 3).min(remaining);
+    delta -= count_katu * 3;
+    remaining -= count_katu;
+
+    let count_100 = delta.min(remaining);
+    remaining -= count_100;
+
+    Score {
+        score_id: 0,
+        user_id: 0,
+        beatmap_id: 0,
+        mods: partial.mods,
+        score: 0,
+        // nothing here looks at combo, which holds make hard to count anyway
+        max_combo: partial.max_combo.unwrap_or(0),
+        count_300: 0,
+        count_100,
+        count_50: remaining,
+        count_geki,
+        count_katu,
+        count_miss,
+    }
+}
+
+pub fn calculate_partial(difficulty: &ManiaDifficulty, partial: &PartialScore) -> ManiaPerformance {
+    calculate(difficulty, &score_for(difficulty, partial))
+}
+
+// the difficulty should be calculated with the same mods as the score
+pub fn calculate(difficulty: &ManiaDifficulty, score: &Score) -> ManiaPerformance {
+    let has = |m: Mods| score.mods.contains(m);
+    let total_hits = total_hits(score);
+
+    let mut performance = ManiaPerformance {
+        difficulty: 0.0,
+        total: 0.0,
+    };
+
+    if total_hits == 0.0 {
+        return performance;
+    }
+
+    let mut multiplier = 8.0;
+
+    if has(Mods::NoFail) {
+        multiplier *= 0.75;
+    }
+
+    if has(Mods::Easy) {
+        multiplier *= 0.5;
+    }
+
+    // nothing below 80% accuracy, a twentieth of the value for every percent above,
+    // and up to 10% more for long maps
+    performance.difficulty = (difficulty.stars - 0.15).max(0.05).powf(2.2)
+        * (5.0 * accuracy(score) - 4.0).max(0.0)
+        * (1.0 + 0.1 * (total_hits / 1500.0).min(1.0));
+    performance.total = performance.difficulty * multiplier;
+
+    performance
+}
@@ -0,0 +1,5 @@
+// exports
+pub mod catch;
+pub mod mania;
+pub mod standard;
+pub mod taiko;
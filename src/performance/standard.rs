@@ -0,0 +1,375 @@
+// osu!'s standard pp from the November 2022 deploy, the same version as the
+// difficulty in difficulty::standard
+
+use crate::{
+    difficulty::standard::{StandardDifficulty, PERFORMANCE_BASE_MULTIPLIER},
+    game::{
+        score::{PartialScore, Score},
+        Mods,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StandardPerformance {
+    pub aim: f64,
+    pub speed: f64,
+    pub accuracy: f64,
+    pub flashlight: f64,
+    // misses plus the slider breaks the combo suggests
+    pub effective_miss_count: f64,
+    pub total: f64,
+}
+
+// 0-1, by the score's 300s, 100s, 50s and misses
+pub fn accuracy(score: &Score) -> f64 {
+    let total = total_hits(score);
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    (score.count_300 * 6 + score.count_100 * 2 + score.count_50) as f64 / (total * 6.0)
+}
+
+fn total_hits(score: &Score) -> f64 {
+    (score.count_300 + score.count_100 + score.count_50 + score.count_miss) as f64
+}
+
+// spreads the partial score's accuracy over the map's objects, using 100s before 50s
+pub fn score_for(difficulty: &StandardDifficulty, partial: &PartialScore) -> Score {
+    let total = difficulty.circle_count + difficulty.slider_count + difficulty.spinner_count;
+    let count_miss = partial.count_miss.clamp(0, total);
+    let remaining = total - count_miss;
+    let accuracy = (partial.accuracy / 100.0).clamp(0.0, 1.0);
+
+    // 300 * (remaining - n100) + 100 * n100 = 300 * total * accuracy
+    let mut count_100 =
+        ((-3.0 * ((accuracy - 1.0) * total as f64 + count_miss as f64)) / 2.0).round() as i32;
+    let mut count_50 = 0;
+
+    if count_100 > remaining {
+        // too low for 100s alone, 300 * (remaining - n50) + 50 * n50 instead
+        count_100 = 0;
+        count_50 =
+            ((-6.0 * ((accuracy - 1.0) * total as f64 + count_miss as f64)) / 5.0).round() as i32;
+        count_50 = count_50.min(remaining);
+    }
+
+    let count_100 = count_100.max(0);
+    let count_50 = count_50.max(0);
+    let max_combo = partial
+        .max_combo
+        .unwrap_or(difficulty.max_combo)
+        .clamp(0, difficulty.max_combo);
+
+    Score {
+        score_id: 0,
+        user_id: 0,
+        beatmap_id: 0,
+        mods: partial.mods,
+        score: 0,
+        max_combo,
+        count_300: remaining - count_100 - count_50,
+        count_100,
+        count_50,
+        count_geki: 0,
+        count_katu: 0,
+        count_miss,
+    }
+}
+
+pub fn calculate_partial(
+    difficulty: &StandardDifficulty,
+    partial: &PartialScore,
+) -> StandardPerformance {
+    calculate(difficulty, &score_for(difficulty, partial))
+}
+
+// the difficulty should be calculated with the same mods as the score
+pub fn calculate(difficulty: &StandardDifficulty, score: &Score) -> StandardPerformance {
+    let has = |m: Mods| score.mods.contains(m);
+    let total_hits = total_hits(score);
+
+    let mut performance = StandardPerformance {
+        aim: 0.0,
+        speed: 0.0,
+        accuracy: 0.0,
+        flashlight: 0.0,
+        effective_miss_count: 0.0,
+        total: 0.0,
+    };
+
+    if total_hits == 0.0 {
+        return performance;
+    }
+
+    let mut effective_miss_count = effective_miss_count(difficulty, score);
+    let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+
+    if has(Mods::NoFail) {
+        multiplier *= (1.0 - 0.02 * effective_miss_count).max(0.9);
+    }
+
+    if has(Mods::SpunOut) {
+        multiplier *= 1.0 - (difficulty.spinner_count as f64 / total_hits).powf(0.85);
+    }
+
+    if has(Mods::Relax) {
+        // 100s and 50s are as good as misses when you don't click, od 13.33 has no 300 window
+        let od = difficulty.overall_difficulty;
+        let (ok_multiplier, meh_multiplier) = if od > 0.0 {
+            (
+                (1.0 - (od / 13.33).powf(1.8)).max(0.0),
+                (1.0 - (od / 13.33).powi(5)).max(0.0),
+            )
+        } else {
+            (1.0, 1.0)
+        };
+
+        effective_miss_count = (effective_miss_count
+            + score.count_100 as f64 * ok_multiplier
+            + score.count_50 as f64 * meh_multiplier)
+            .min(total_hits);
+    }
+
+    performance.effective_miss_count = effective_miss_count;
+    performance.aim = aim_value(difficulty, score, effective_miss_count);
+    performance.speed = speed_value(difficulty, score, effective_miss_count);
+    performance.accuracy = accuracy_value(difficulty, score);
+    performance.flashlight = flashlight_value(difficulty, score, effective_miss_count);
+
+    performance.total = (performance.aim.powf(1.1)
+        + performance.speed.powf(1.1)
+        + performance.accuracy.powf(1.1)
+        + performance.flashlight.powf(1.1))
+    .powf(1.0 / 1.1)
+        * multiplier;
+
+    performance
+}
+
+// sliders breaks don't show up as misses, so guess them from the combo
+fn effective_miss_count(difficulty: &StandardDifficulty, score: &Score) -> f64 {
+    let mut combo_based_miss_count = 0.0;
+
+    if difficulty.slider_count > 0 {
+        let full_combo_threshold =
+            difficulty.max_combo as f64 - 0.1 * difficulty.slider_count as f64;
+
+        if (score.max_combo as f64) < full_combo_threshold {
+            combo_based_miss_count = full_combo_threshold / (score.max_combo as f64).max(1.0);
+        }
+    }
+
+    // no more breaks than there were imperfect hits
+    combo_based_miss_count =
+        combo_based_miss_count.min((score.count_100 + score.count_50 + score.count_miss) as f64);
+
+    combo_based_miss_count.max(score.count_miss as f64)
+}
+
+fn combo_scaling(difficulty: &StandardDifficulty, score: &Score) -> f64 {
+    if difficulty.max_combo <= 0 {
+        return 1.0;
+    }
+
+    ((score.max_combo as f64).powf(0.8) / (difficulty.max_combo as f64).powf(0.8)).min(1.0)
+}
+
+fn length_bonus(total_hits: f64) -> f64 {
+    let bonus = 0.95 + 0.4 * (total_hits / 2000.0).min(1.0);
+
+    if total_hits > 2000.0 {
+        bonus + (total_hits / 2000.0).log10() * 0.5
+    } else {
+        bonus
+    }
+}
+
+fn base_value(rating: f64) -> f64 {
+    (5.0 * (rating / 0.0675).max(1.0) - 4.0).powi(3) / 100000.0
+}
+
+fn aim_value(difficulty: &StandardDifficulty, score: &Score, effective_miss_count: f64) -> f64 {
+    let has = |m: Mods| score.mods.contains(m);
+    let total_hits = total_hits(score);
+
+    let mut value = base_value(difficulty.aim);
+    let length_bonus = length_bonus(total_hits);
+    value *= length_bonus;
+
+    // any miss costs 3%, more the larger a share of the map they are
+    if effective_miss_count > 0.0 {
+        value *= 0.97
+            * (1.0 - (effective_miss_count / total_hits).powf(0.775)).powf(effective_miss_count);
+    }
+
+    value *= combo_scaling(difficulty, score);
+
+    let mut approach_rate_factor = 0.0;
+    if difficulty.approach_rate > 10.33 {
+        approach_rate_factor = 0.3 * (difficulty.approach_rate - 10.33);
+    } else if difficulty.approach_rate < 8.0 {
+        approach_rate_factor = 0.05 * (8.0 - difficulty.approach_rate);
+    }
+
+    if has(Mods::Relax) {
+        approach_rate_factor = 0.0;
+    }
+
+    // high ar is harder the longer the map is
+    value *= 1.0 + approach_rate_factor * length_bonus;
+
+    // hidden is harder to aim the lower the ar
+    if has(Mods::Hidden) {
+        value *= 1.0 + 0.04 * (12.0 - difficulty.approach_rate);
+    }
+
+    // assume 15% of the sliders are hard, and that dropped combo went on their ends
+    if difficulty.slider_count > 0 {
+        let difficult_sliders = difficulty.slider_count as f64 * 0.15;
+        let dropped_ends = ((score.count_100 + score.count_50 + score.count_miss)
+            .min(difficulty.max_combo - score.max_combo) as f64)
+            .clamp(0.0, difficult_sliders);
+        let slider_nerf = (1.0 - difficulty.slider_factor)
+            * (1.0 - dropped_ends / difficult_sliders).powi(3)
+            + difficulty.slider_factor;
+
+        value *= slider_nerf;
+    }
+
+    value *= accuracy(score);
+    value *= 0.98 + difficulty.overall_difficulty.powi(2) / 2500.0;
+
+    value
+}
+
+fn speed_value(difficulty: &StandardDifficulty, score: &Score, effective_miss_count: f64) -> f64 {
+    let has = |m: Mods| score.mods.contains(m);
+    if has(Mods::Relax) {
+        return 0.0;
+    }
+
+    let total_hits = total_hits(score);
+
+    let mut value = base_value(difficulty.speed);
+    let length_bonus = length_bonus(total_hits);
+    value *= length_bonus;
+
+    if effective_miss_count > 0.0 {
+        value *= 0.97
+            * (1.0 - (effective_miss_count / total_hits).powf(0.775))
+                .powf(effective_miss_count.powf(0.875));
+    }
+
+    value *= combo_scaling(difficulty, score);
+
+    let mut approach_rate_factor = 0.0;
+    if difficulty.approach_rate > 10.33 {
+        approach_rate_factor = 0.3 * (difficulty.approach_rate - 10.33);
+    }
+
+    value *= 1.0 + approach_rate_factor * length_bonus;
+
+    if has(Mods::Hidden) {
+        value *= 1.0 + 0.04 * (12.0 - difficulty.approach_rate);
+    }
+
+    // accuracy on the hardest notes, assuming the worst case that every 100 and 50
+    // landed on them
+    let count_300 = score.count_300 as f64;
+    let count_100 = score.count_100 as f64;
+    let count_50 = score.count_50 as f64;
+    let note_count = difficulty.speed_note_count;
+
+    let relevant_total_difference = total_hits - note_count;
+    let relevant_count_300 = (count_300 - relevant_total_difference).max(0.0);
+    let relevant_count_100 =
+        (count_100 - (relevant_total_difference - count_300).max(0.0)).max(0.0);
+    let relevant_count_50 =
+        (count_50 - (relevant_total_difference - count_300 - count_100).max(0.0)).max(0.0);
+    let relevant_accuracy = if note_count == 0.0 {
+        0.0
+    } else {
+        (relevant_count_300 * 6.0 + relevant_count_100 * 2.0 + relevant_count_50)
+            / (note_count * 6.0)
+    };
+
+    let od = difficulty.overall_difficulty;
+    value *= (0.95 + od.powi(2) / 750.0)
+        * ((accuracy(score) + relevant_accuracy) / 2.0).powf((14.5 - od.max(8.0)) / 2.0);
+
+    // 50s are a sign of double tapping
+    let allowed_50s = total_hits / 500.0;
+    if count_50 >= allowed_50s {
+        value *= 0.99f64.powf(count_50 - allowed_50s);
+    }
+
+    value
+}
+
+fn accuracy_value(difficulty: &StandardDifficulty, score: &Score) -> f64 {
+    let has = |m: Mods| score.mods.contains(m);
+    if has(Mods::Relax) {
+        return 0.0;
+    }
+
+    // only circles are timed on their own, assume sliders and spinners got the 300s
+    let circles = difficulty.circle_count as f64;
+    let better_accuracy = if circles > 0.0 {
+        (((score.count_300 as f64 - (total_hits(score) - circles)) * 6.0
+            + score.count_100 as f64 * 2.0
+            + score.count_50 as f64)
+            / (circles * 6.0))
+            .max(0.0)
+    } else {
+        0.0
+    };
+
+    let mut value =
+        1.52163f64.powf(difficulty.overall_difficulty) * better_accuracy.powi(24) * 2.83;
+
+    // keeping accuracy up over more circles is harder
+    value *= (circles / 1000.0).powf(0.3).min(1.15);
+
+    if has(Mods::Hidden) {
+        value *= 1.08;
+    }
+    if has(Mods::Flashlight) {
+        value *= 1.02;
+    }
+
+    value
+}
+
+fn flashlight_value(
+    difficulty: &StandardDifficulty,
+    score: &Score,
+    effective_miss_count: f64,
+) -> f64 {
+    if !score.mods.contains(Mods::Flashlight) {
+        return 0.0;
+    }
+
+    let total_hits = total_hits(score);
+    let mut value = difficulty.flashlight.powi(2) * 25.0;
+
+    if effective_miss_count > 0.0 {
+        value *= 0.97
+            * (1.0 - (effective_miss_count / total_hits).powf(0.775))
+                .powf(effective_miss_count.powf(0.875));
+    }
+
+    value *= combo_scaling(difficulty, score);
+
+    // short maps spend more of their time with the larger low combo radius
+    let mut length_factor = 0.7 + 0.1 * (total_hits / 200.0).min(1.0);
+    if total_hits > 200.0 {
+        length_factor += 0.2 * ((total_hits - 200.0) / 200.0).min(1.0);
+    }
+    value *= length_factor;
+
+    value *= 0.5 + accuracy(score) / 2.0;
+    value *= 0.98 + difficulty.overall_difficulty.powi(2) / 2500.0;
+
+    value
+}
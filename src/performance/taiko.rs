@@ -0,0 +1,131 @@
+use crate::{
+    difficulty::taiko::TaikoDifficulty,
+    game::{
+        score::{PartialScore, Score},
+        Mods,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaikoPerformance {
+    pub strain: f64,
+    pub accuracy: f64,
+    pub total: f64,
+}
+
+// 0-1, goods are worth half a great
+pub fn accuracy(score: &Score) -> f64 {
+    let total = total_hits(score);
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    (score.count_300 as f64 + score.count_100 as f64 * 0.5) / total
+}
+
+fn total_hits(score: &Score) -> f64 {
+    (score.count_300 + score.count_100 + score.count_50 + score.count_miss) as f64
+}
+
+// spreads the partial score's accuracy over the map's notes, taiko has no 50s
+pub fn score_for(difficulty: &TaikoDifficulty, partial: &PartialScore) -> Score {
+    let total = difficulty.max_combo;
+    let count_miss = partial.count_miss.clamp(0, total);
+    let remaining = total - count_miss;
+    let accuracy = (partial.accuracy / 100.0).clamp(0.0, 1.0);
+
+    // (remaining - n100) + 0.5 * n100 = total * accuracy
+    let count_100 =
+        ((2.0 * (remaining as f64 - accuracy * total as f64)).round() as i32).clamp(0, remaining);
+    let max_combo = partial
+        .max_combo
+        .unwrap_or(difficulty.max_combo)
+        .clamp(0, difficulty.max_combo);
+
+    Score {
+        score_id: 0,
+        user_id: 0,
+        beatmap_id: 0,
+        mods: partial.mods,
+        score: 0,
+        max_combo,
+        count_300: remaining - count_100,
+        count_100,
+        count_50: 0,
+        count_geki: 0,
+        count_katu: 0,
+        count_miss,
+    }
+}
+
+pub fn calculate_partial(difficulty: &TaikoDifficulty, partial: &PartialScore) -> TaikoPerformance {
+    calculate(difficulty, &score_for(difficulty, partial))
+}
+
+// the difficulty should be calculated with the same mods as the score
+pub fn calculate(difficulty: &TaikoDifficulty, score: &Score) -> TaikoPerformance {
+    let has = |m: Mods| score.mods.contains(m);
+    let total_hits = total_hits(score);
+
+    let mut performance = TaikoPerformance {
+        strain: 0.0,
+        accuracy: 0.0,
+        total: 0.0,
+    };
+
+    if total_hits == 0.0 {
+        return performance;
+    }
+
+    let mut multiplier = 1.1;
+
+    if has(Mods::NoFail) {
+        multiplier *= 0.9;
+    }
+
+    if has(Mods::Hidden) {
+        multiplier *= 1.1;
+    }
+
+    let accuracy = accuracy(score);
+    performance.strain = strain_value(difficulty, score, accuracy);
+    performance.accuracy = accuracy_value(difficulty, total_hits, accuracy);
+    performance.total = (performance.strain.powf(1.1) + performance.accuracy.powf(1.1))
+        .powf(1.0 / 1.1)
+        * multiplier;
+
+    performance
+}
+
+fn strain_value(difficulty: &TaikoDifficulty, score: &Score, accuracy: f64) -> f64 {
+    let has = |m: Mods| score.mods.contains(m);
+
+    let mut value = (5.0 * (difficulty.stars / 0.0075).max(1.0) - 4.0).powi(2) / 100000.0;
+
+    let length_bonus = 1.0 + 0.1 * (total_hits(score) / 1500.0).min(1.0);
+    value *= length_bonus;
+
+    value *= 0.985f64.powi(score.count_miss);
+
+    if has(Mods::Hidden) {
+        value *= 1.025;
+    }
+
+    if has(Mods::Flashlight) {
+        value *= 1.05 * length_bonus;
+    }
+
+    value * accuracy
+}
+
+fn accuracy_value(difficulty: &TaikoDifficulty, total_hits: f64, accuracy: f64) -> f64 {
+    if difficulty.great_hit_window <= 0.0 {
+        return 0.0;
+    }
+
+    (150.0 / difficulty.great_hit_window).powf(1.1)
+        * accuracy.powi(15)
+        * 22.0
+        // long maps are worth more, up to 15%
+        * (total_hits / 1500.0).powf(0.3).min(1.15)
+}
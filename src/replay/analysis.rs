@@ -0,0 +1,169 @@
+use crate::{
+    replay::{Frame, Keys, KEYS},
+    util::Vector2,
+};
+
+// stable puts two frames at (256, -500) in front of every replay
+const SKIP_FRAME_POSITION: (f32, f32) = (256.0, -500.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyAction {
+    Press,
+    Release,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+    pub key: Keys,
+    pub action: KeyAction,
+    pub time: i32,
+    pub position: Vector2,
+}
+
+// a single press from key down to key up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPress {
+    pub key: Keys,
+    pub start_time: i32,
+    pub end_time: i32,
+    pub position: Vector2,
+}
+
+impl KeyPress {
+    pub fn duration(&self) -> i32 {
+        self.end_time - self.start_time
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CursorMotion {
+    pub time: i32,
+    pub position: Vector2,
+    // px/ms and px/ms²
+    pub velocity: Vector2,
+    pub acceleration: Vector2,
+}
+
+impl CursorMotion {
+    pub fn speed(&self) -> f32 {
+        self.velocity.len()
+    }
+}
+
+// the frames that are actually played back: drops the two skip frames at the start
+// and any frame recorded with a negative delta, the same way lazer does. frames after
+// a rewind are kept even when they're still earlier than the frame before it
+pub fn playable_frames(frames: &[Frame]) -> Vec<Frame> {
+    let mut playable = vec![];
+    let mut last_time = 0;
+
+    for (index, frame) in frames.iter().enumerate() {
+        let delta = frame.time - last_time;
+        last_time = frame.time;
+
+        if index < 2 && (frame.x, frame.y) == SKIP_FRAME_POSITION {
+            continue;
+        }
+
+        if delta < 0 {
+            continue;
+        }
+
+        playable.push(*frame);
+    }
+
+    playable
+}
+
+pub fn key_events(frames: &[Frame]) -> Vec<KeyEvent> {
+    let mut events = vec![];
+    let mut held = [false; 4];
+
+    for frame in playable_frames(frames) {
+        for (index, key) in KEYS.iter().enumerate() {
+            let pressed = frame.is_pressed(*key);
+
+            if pressed != held[index] {
+                events.push(KeyEvent {
+                    key: *key,
+                    action: if pressed {
+                        KeyAction::Press
+                    } else {
+                        KeyAction::Release
+                    },
+                    time: frame.time,
+                    position: frame.position(),
+                });
+
+                held[index] = pressed;
+            }
+        }
+    }
+
+    events
+}
+
+// presses still held on the last frame end there
+pub fn key_presses(frames: &[Frame]) -> Vec<KeyPress> {
+    let mut presses: Vec<KeyPress> = vec![];
+    let mut open: [Option<usize>; 4] = [None; 4];
+
+    for event in key_events(frames) {
+        let slot = KEYS.iter().position(|key| *key == event.key).unwrap();
+
+        match event.action {
+            KeyAction::Press => {
+                open[slot] = Some(presses.len());
+                presses.push(KeyPress {
+                    key: event.key,
+                    start_time: event.time,
+                    end_time: event.time,
+                    position: event.position,
+                });
+            }
+            KeyAction::Release => {
+                if let Some(index) = open[slot].take() {
+                    presses[index].end_time = event.time;
+                }
+            }
+        }
+    }
+
+    if let Some(last) = playable_frames(frames).last() {
+        for index in open.iter().flatten() {
+            presses[*index].end_time = last.time;
+        }
+    }
+
+    presses
+}
+
+pub fn cursor_motion(frames: &[Frame]) -> Vec<CursorMotion> {
+    let mut motion: Vec<CursorMotion> = vec![];
+
+    for frame in playable_frames(frames) {
+        let position = frame.position();
+
+        let (velocity, acceleration) = match motion.last() {
+            Some(previous) if frame.time > previous.time => {
+                let elapsed = (frame.time - previous.time) as f32;
+                let velocity = (position - previous.position).div(elapsed);
+                let acceleration = (velocity - previous.velocity).div(elapsed);
+
+                (velocity, acceleration)
+            }
+            // frames on the same millisecond can't tell us anything new
+            Some(previous) => (previous.velocity, previous.acceleration),
+            None => (Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0)),
+        };
+
+        motion.push(CursorMotion {
+            time: frame.time,
+            position,
+            velocity,
+            acceleration,
+        });
+    }
+
+    motion
+}
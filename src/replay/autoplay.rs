@@ -0,0 +1,188 @@
+use crate::{
+    game::{Gamemode, ModSet},
+    parser::beatmap::{
+        objects::{HitObject, HitType},
+        BeatmapFile,
+    },
+    replay::{Frame, Keys},
+    util::Vector2,
+};
+
+// keys are let go this long after a circle, or at the end of a slider or spinner
+const KEY_UP_DELAY: f32 = 50.0;
+
+// objects closer than this to the end of the previous one are hit with the other key
+const ALTERNATE_THRESHOLD: f32 = 266.0;
+
+// ~60 frames a second while moving, following sliders and spinning
+const FRAME_INTERVAL: f32 = 1000.0 / 60.0;
+
+const SPINNER_CENTRE: (f32, f32) = (256.0, 192.0);
+const SPINNER_RADIUS: f32 = 50.0;
+// just under the ~477 rpm cap so no rotation is thrown away, in radians per millisecond
+const SPIN_RATE: f32 = 0.0499;
+
+// a perfect play of an osu!standard map: every circle is clicked on time at its
+// stacked position, sliders are followed along their body and spinners spun at the
+// max rpm. the frames start with the first movement, no skip frames are added
+pub fn generate(beatmap: &BeatmapFile) -> Vec<Frame> {
+    let objects = &beatmap.hit_objects;
+    let mut frames = vec![];
+
+    let first = match objects.first() {
+        Some(first) => first,
+        None => return frames,
+    };
+
+    let radius = beatmap
+        .attributes(Gamemode::Standard, ModSet::default())
+        .circle_radius as f32;
+    let centre = Vector2::new(SPINNER_CENTRE.0, SPINNER_CENTRE.1);
+
+    push(&mut frames, first.start_time - 1000.0, centre, 0);
+
+    let mut button = 0;
+    let mut last_end: Option<f32> = None;
+
+    for (index, object) in objects.iter().enumerate() {
+        let is_spinner = object.hit_type & HitType::Spinner as i32 != 0;
+        let is_slider =
+            object.hit_type & HitType::Slider as i32 != 0 && object.slider_data.is_some();
+
+        // same as the autopilot generator, fast sections alternate and anything
+        // after a pause starts over on the first key
+        button = match last_end {
+            Some(end) if object.start_time - end < ALTERNATE_THRESHOLD => button + 1,
+            _ => 0,
+        };
+        let keys = if button % 2 == 0 {
+            Keys::M1 as i32 | Keys::K1 as i32
+        } else {
+            Keys::M2 as i32 | Keys::K2 as i32
+        };
+
+        let target = if is_spinner {
+            spinner_position(centre, 0.0)
+        } else {
+            object.stacked_position(radius)
+        };
+        move_to(&mut frames, target, object.start_time);
+        push(&mut frames, object.start_time, target, keys);
+
+        let end_time = if is_spinner {
+            spin(&mut frames, object, centre, keys);
+            object.end_time
+        } else if is_slider {
+            follow_slider(&mut frames, object, radius, keys);
+            object.end_time
+        } else {
+            object.start_time
+        };
+
+        // let go before the next object is due so its press registers
+        let release = match objects.get(index + 1) {
+            Some(next) => (end_time + KEY_UP_DELAY).min(next.start_time - 1.0),
+            None => end_time + KEY_UP_DELAY,
+        };
+        if let Some(last) = frames.last().copied() {
+            if release.round() as i32 > last.time {
+                push(&mut frames, release, last.position(), 0);
+            }
+        }
+
+        last_end = Some(end_time);
+    }
+
+    frames
+}
+
+// frames only carry whole milliseconds and never go back in time
+fn push(frames: &mut Vec<Frame>, time: f32, position: Vector2, keys: i32) {
+    let time = match frames.last() {
+        Some(last) => (time.round() as i32).max(last.time),
+        None => time.round() as i32,
+    };
+
+    frames.push(Frame {
+        time,
+        x: position.x,
+        y: position.y,
+        keys,
+    });
+}
+
+// a straight line from wherever the cursor is, the frame on `time` is left to the caller
+fn move_to(frames: &mut Vec<Frame>, target: Vector2, time: f32) {
+    let last = match frames.last().copied() {
+        Some(last) => last,
+        None => return,
+    };
+
+    let start_time = last.time as f32;
+    let duration = time - start_time;
+    let mut current = start_time + FRAME_INTERVAL;
+
+    while current < time {
+        let progress = (current - start_time) / duration;
+        let position = last.position() + (target - last.position()).scale(progress);
+
+        push(frames, current, position, last.keys);
+        current += FRAME_INTERVAL;
+    }
+}
+
+fn follow_slider(frames: &mut Vec<Frame>, object: &HitObject, radius: f32, keys: i32) {
+    let slider = match &object.slider_data {
+        Some(slider) => slider,
+        None => return,
+    };
+
+    let slides = slider.slides.max(1);
+    let duration = object.end_time - object.start_time;
+    let span_duration = duration / slides as f32;
+    let distance = slider.length.max(0.0);
+    let head = object.stacked_position(radius);
+
+    let position_at = |time: f32| {
+        let elapsed = ((time - object.start_time) / span_duration).clamp(0.0, slides as f32);
+        let span = (elapsed.floor() as i32).min(slides - 1);
+        let mut progress = elapsed - span as f32;
+
+        // every other span runs back towards the head
+        if span % 2 == 1 {
+            progress = 1.0 - progress;
+        }
+
+        head + slider.slider_body.position_at(progress * distance)
+    };
+
+    let mut current = object.start_time + FRAME_INTERVAL;
+    while current < object.end_time {
+        push(frames, current, position_at(current.round()), keys);
+        current += FRAME_INTERVAL;
+    }
+
+    push(frames, object.end_time, position_at(object.end_time), keys);
+}
+
+fn spin(frames: &mut Vec<Frame>, object: &HitObject, centre: Vector2, keys: i32) {
+    let mut current = object.start_time + FRAME_INTERVAL;
+    while current < object.end_time {
+        // the angle follows the rounded frame time so no step goes over the cap
+        let angle = (current.round() - object.start_time) * SPIN_RATE;
+        push(frames, current, spinner_position(centre, angle), keys);
+        current += FRAME_INTERVAL;
+    }
+
+    let angle = (object.end_time - object.start_time) * SPIN_RATE;
+    push(
+        frames,
+        object.end_time,
+        spinner_position(centre, angle),
+        keys,
+    );
+}
+
+fn spinner_position(centre: Vector2, angle: f32) -> Vector2 {
+    centre + Vector2::new(angle.cos(), angle.sin()).scale(SPINNER_RADIUS)
+}
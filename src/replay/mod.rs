@@ -0,0 +1,40 @@
+use crate::util::Vector2;
+
+// exports
+pub mod analysis;
+pub mod autoplay;
+pub mod simulator;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    // absolute time, the file stores the delta from the previous frame
+    pub time: i32,
+    pub x: f32,
+    pub y: f32,
+    pub keys: i32,
+}
+
+impl Frame {
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
+    }
+
+    // keyboard presses also set the matching mouse bit, K1 comes through as M1 | K1
+    pub fn is_pressed(&self, key: Keys) -> bool {
+        match key {
+            Keys::M1 => self.keys & Keys::M1 as i32 != 0 && self.keys & Keys::K1 as i32 == 0,
+            Keys::M2 => self.keys & Keys::M2 as i32 != 0 && self.keys & Keys::K2 as i32 == 0,
+            _ => self.keys & key as i32 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Keys {
+    M1 = 1 << 0,
+    M2 = 1 << 1,
+    K1 = 1 << 2,
+    K2 = 1 << 3,
+}
+
+pub const KEYS: [Keys; 4] = [Keys::M1, Keys::M2, Keys::K1, Keys::K2];
@@ -0,0 +1,493 @@
+use std::f32::consts::PI;
+
+use crate::{
+    game::{score::Score, Gamemode, Mods},
+    parser::{
+        beatmap::{
+            attributes::DifficultyAttributes,
+            objects::{HitObject, HitType, SliderObjectType},
+            BeatmapFile,
+        },
+        replays::Replay,
+    },
+    replay::{
+        analysis::{self, KeyAction},
+        Frame,
+    },
+    util::Vector2,
+};
+
+// clicks earlier than this are ignored instead of counting as a miss
+const MISS_WINDOW: f32 = 400.0;
+
+// the follow circle while a slider is being held
+const FOLLOW_RADIUS_SCALE: f32 = 2.4;
+
+const SPINNER_CENTRE: (f32, f32) = (256.0, 192.0);
+// ~477 rpm, in radians per millisecond
+const MAX_SPIN_RATE: f32 = 0.05;
+
+const PLAYFIELD_HEIGHT: f32 = 384.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitResult {
+    Hit300,
+    Hit100,
+    Hit50,
+    Miss,
+}
+
+impl HitResult {
+    pub fn score(&self) -> i32 {
+        match self {
+            HitResult::Hit300 => 300,
+            HitResult::Hit100 => 100,
+            HitResult::Hit50 => 50,
+            HitResult::Miss => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Judgement {
+    // index into `hit_objects`
+    pub index: usize,
+    pub time: f32,
+    pub result: HitResult,
+    // ms, negative is early, `None` for spinners and anything that wasn't clicked
+    pub hit_error: Option<f32>,
+    // a missed slider head, tick or repeat dropped the combo
+    pub slider_break: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub judgements: Vec<Judgement>,
+    pub score: Score,
+}
+
+impl Simulation {
+    pub fn hit_errors(&self) -> Vec<f32> {
+        self.judgements.iter().filter_map(|j| j.hit_error).collect()
+    }
+
+    // ten times the standard deviation of the hit errors
+    pub fn unstable_rate(&self) -> f32 {
+        let errors = self.hit_errors();
+        if errors.is_empty() {
+            return 0.0;
+        }
+
+        let mean = errors.iter().sum::<f32>() / errors.len() as f32;
+        let variance =
+            errors.iter().map(|e| (e - mean) * (e - mean)).sum::<f32>() / errors.len() as f32;
+
+        variance.sqrt() * 10.0
+    }
+
+    pub fn slider_breaks(&self) -> usize {
+        self.judgements.iter().filter(|j| j.slider_break).count()
+    }
+}
+
+// running score and combo, scored like osu!stable's score v1
+struct Scoring {
+    combo: i32,
+    max_combo: i32,
+    score: i64,
+    multiplier: f32,
+}
+
+impl Scoring {
+    fn hit(&mut self, value: i32) {
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo);
+        self.score += value as i64;
+    }
+
+    fn miss(&mut self) {
+        self.combo = 0;
+    }
+
+    fn judge(&mut self, result: HitResult) {
+        let value = result.score() as f32;
+        let combo = (self.combo - 1).max(0) as f32;
+
+        self.score += (value + value * combo * self.multiplier / 25.0) as i64;
+    }
+}
+
+struct Press {
+    time: f32,
+    position: Vector2,
+}
+
+// osu!standard only, replays of other modes don't carry cursor positions
+pub fn simulate(beatmap: &BeatmapFile, replay: &Replay) -> Simulation {
+    let mods = replay.mods;
+    let has = |m: Mods| mods.contains(m);
+    let hard_rock = has(Mods::HardRock);
+
+    // the stacks from parsing are for the unmodified approach rate
+    let restacked;
+    let beatmap = if hard_rock || has(Mods::Easy) {
+        restacked = beatmap.restacked(mods);
+        &restacked
+    } else {
+        beatmap
+    };
+
+    let attributes = beatmap.attributes(Gamemode::Standard, mods);
+
+    let radius = attributes.circle_radius as f32;
+    let window_300 = attributes.hit_window_300 as f32;
+    let window_100 = attributes.hit_window_100 as f32;
+    let window_50 = attributes.hit_window_50 as f32;
+
+    // hard rock flips the map, the replay is recorded on the flipped map
+    let place = |position: Vector2| {
+        if hard_rock {
+            Vector2::new(position.x, PLAYFIELD_HEIGHT - position.y)
+        } else {
+            position
+        }
+    };
+
+    let frames = analysis::playable_frames(&replay.frames);
+    let presses: Vec<Press> = analysis::key_events(&frames)
+        .into_iter()
+        .filter(|event| event.action == KeyAction::Press)
+        .map(|event| Press {
+            time: event.time as f32,
+            position: event.position,
+        })
+        .collect();
+
+    let mut scoring = Scoring {
+        combo: 0,
+        max_combo: 0,
+        score: 0,
+        multiplier: difficulty_multiplier(beatmap)
+            * mods.score_multiplier(Gamemode::Standard) as f32,
+    };
+
+    let mut judgements = vec![];
+    let mut press_index = 0;
+
+    for (index, object) in beatmap.hit_objects.iter().enumerate() {
+        if object.hit_type & HitType::Spinner as i32 != 0 {
+            let (result, half_spins, required) = judge_spinner(object, &frames, &attributes);
+
+            scoring.score += spin_score(half_spins, required);
+            if result == HitResult::Miss {
+                scoring.miss();
+            } else {
+                scoring.hit(0);
+            }
+            scoring.judge(result);
+
+            judgements.push(Judgement {
+                index,
+                time: object.end_time,
+                result,
+                hit_error: None,
+                slider_break: false,
+            });
+            continue;
+        }
+
+        // stacks always lean up and left, even on a flipped map
+        let position = place(object.position) + object.stack_offset(radius);
+
+        // first click on the circle inside its hit window, clicks that land anywhere
+        // else while the object is waiting are eaten by note lock
+        let mut hit_error = None;
+        while let Some(press) = presses.get(press_index) {
+            let error = press.time - object.start_time;
+            if error > window_50 {
+                break;
+            }
+
+            press_index += 1;
+            if error < -MISS_WINDOW {
+                continue;
+            }
+
+            if press.position.distance(position) <= radius {
+                hit_error = Some(error);
+                break;
+            }
+        }
+
+        let head_result = match hit_error {
+            Some(error) if error.abs() <= window_300 => HitResult::Hit300,
+            Some(error) if error.abs() <= window_100 => HitResult::Hit100,
+            Some(error) if error.abs() <= window_50 => HitResult::Hit50,
+            _ => HitResult::Miss,
+        };
+
+        // clicking too early is a miss, not a hit
+        let hit_error = hit_error.filter(|_| head_result != HitResult::Miss);
+
+        let slider_objects = match &object.slider_objects {
+            Some(slider_objects) if object.hit_type & HitType::Slider as i32 != 0 => slider_objects,
+            _ => {
+                if head_result == HitResult::Miss {
+                    scoring.miss();
+                } else {
+                    scoring.hit(0);
+                }
+                scoring.judge(head_result);
+
+                judgements.push(Judgement {
+                    index,
+                    time: object.start_time,
+                    result: head_result,
+                    hit_error,
+                    slider_break: false,
+                });
+                continue;
+            }
+        };
+
+        let mut slider_break = head_result == HitResult::Miss;
+        let mut hits = 0;
+        let mut total = 1;
+
+        if slider_break {
+            scoring.miss();
+        } else {
+            hits += 1;
+            scoring.hit(30);
+        }
+
+        for slider_object in slider_objects {
+            let value = match slider_object.slider_object_type {
+                SliderObjectType::SliderHead => continue,
+                SliderObjectType::SliderTick => 10,
+                SliderObjectType::SliderRepeat | SliderObjectType::SliderEnd => 30,
+            };
+
+            total += 1;
+            let target = place(slider_object.position) + object.stack_offset(radius);
+            let held = match frame_at(&frames, slider_object.start_time) {
+                Some(frame) => {
+                    frame.keys & 0b1111 != 0
+                        && cursor_at(&frames, slider_object.start_time).distance(target)
+                            <= radius * FOLLOW_RADIUS_SCALE
+                }
+                None => false,
+            };
+
+            if held {
+                hits += 1;
+                scoring.hit(value);
+            } else if slider_object.slider_object_type as i32 != SliderObjectType::SliderEnd as i32
+            {
+                // dropping the end only costs score, anything else breaks combo
+                slider_break = true;
+                scoring.miss();
+            }
+        }
+
+        let result = if hits == total {
+            HitResult::Hit300
+        } else if hits * 2 >= total {
+            HitResult::Hit100
+        } else if hits > 0 {
+            HitResult::Hit50
+        } else {
+            HitResult::Miss
+        };
+        scoring.judge(result);
+
+        judgements.push(Judgement {
+            index,
+            time: object.start_time,
+            result,
+            hit_error,
+            slider_break,
+        });
+    }
+
+    let count = |result: HitResult| judgements.iter().filter(|j| j.result == result).count() as i32;
+    let (count_geki, count_katu) = combo_bonuses(beatmap, &judgements);
+
+    let score = Score {
+        score_id: replay.online_score_id,
+        user_id: 0,
+        beatmap_id: beatmap.metadata.beatmap_id.unwrap_or(0) as i32,
+        mods,
+        score: scoring.score.min(i32::MAX as i64) as i32,
+        max_combo: scoring.max_combo,
+        count_300: count(HitResult::Hit300),
+        count_100: count(HitResult::Hit100),
+        count_50: count(HitResult::Hit50),
+        count_geki,
+        count_katu,
+        count_miss: count(HitResult::Miss),
+    };
+
+    Simulation { judgements, score }
+}
+
+// the last frame at or before `time`
+fn frame_at(frames: &[Frame], time: f32) -> Option<&Frame> {
+    frames.iter().rev().find(|frame| frame.time as f32 <= time)
+}
+
+fn cursor_at(frames: &[Frame], time: f32) -> Vector2 {
+    let next = frames.iter().position(|frame| frame.time as f32 >= time);
+
+    match next {
+        Some(0) => frames[0].position(),
+        Some(index) => {
+            let (a, b) = (&frames[index - 1], &frames[index]);
+            let span = (b.time - a.time) as f32;
+            let t = if span > 0.0 {
+                (time - a.time as f32) / span
+            } else {
+                1.0
+            };
+
+            a.position() + (b.position() - a.position()).scale(t)
+        }
+        None => frames
+            .last()
+            .map_or(Vector2::new(0.0, 0.0), |frame| frame.position()),
+    }
+}
+
+// returns the judgement, the half spins made and the spins needed to clear it
+fn judge_spinner(
+    object: &HitObject,
+    frames: &[Frame],
+    attributes: &DifficultyAttributes,
+) -> (HitResult, i64, i64) {
+    let centre = Vector2::new(SPINNER_CENTRE.0, SPINNER_CENTRE.1);
+    let duration = object.end_time - object.start_time;
+
+    let required = attributes.required_spins(duration as f64) as f32;
+
+    let mut rotation = 0.0;
+    let mut last: Option<(f32, f32)> = None;
+
+    for frame in frames {
+        let time = frame.time as f32;
+        if time < object.start_time || time > object.end_time {
+            continue;
+        }
+
+        let offset = frame.position() - centre;
+        let angle = offset.y.atan2(offset.x);
+
+        if frame.keys & 0b1111 != 0 {
+            if let Some((last_time, last_angle)) = last {
+                let mut delta = angle - last_angle;
+                if delta > PI {
+                    delta -= 2.0 * PI;
+                } else if delta < -PI {
+                    delta += 2.0 * PI;
+                }
+
+                let limit = (time - last_time) * MAX_SPIN_RATE;
+                rotation += delta.clamp(-limit, limit);
+            }
+
+            last = Some((time, angle));
+        } else {
+            last = None;
+        }
+    }
+
+    let spins = (rotation.abs() / (2.0 * PI)).floor();
+    let progress = if required > 0.0 {
+        spins / required
+    } else {
+        1.0
+    };
+
+    let result = if progress >= 1.0 {
+        HitResult::Hit300
+    } else if progress > 0.9 {
+        HitResult::Hit100
+    } else if progress > 0.75 {
+        HitResult::Hit50
+    } else {
+        HitResult::Miss
+    };
+
+    let half_spins = (rotation.abs() / PI).floor();
+    (result, half_spins as i64, required as i64)
+}
+
+// osu!stable scores spinners by half spins: 100 for every other one, 1000 instead once
+// a spinner has been spun a spin and a half past what it needs
+fn spin_score(half_spins: i64, required: i64) -> i64 {
+    let before_bonus = required + 3;
+
+    (1..=half_spins)
+        .map(|i| {
+            if i > before_bonus && (i - before_bonus) % 2 == 0 {
+                1000
+            } else if i > 1 && i % 2 == 0 {
+                100
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+// geki for a combo of only 300s, katu for one with 100s but nothing worse
+fn combo_bonuses(beatmap: &BeatmapFile, judgements: &[Judgement]) -> (i32, i32) {
+    let mut geki = 0;
+    let mut katu = 0;
+
+    for (position, judgement) in judgements.iter().enumerate() {
+        let combo = beatmap.hit_objects[judgement.index].combo_index;
+        let ends_combo = judgements
+            .get(position + 1)
+            .is_none_or(|next| beatmap.hit_objects[next.index].combo_index != combo);
+
+        if !ends_combo {
+            continue;
+        }
+
+        let results: Vec<HitResult> = judgements
+            .iter()
+            .filter(|j| beatmap.hit_objects[j.index].combo_index == combo)
+            .map(|j| j.result)
+            .collect();
+
+        if results.iter().all(|r| *r == HitResult::Hit300) {
+            geki += 1;
+        } else if results
+            .iter()
+            .all(|r| *r == HitResult::Hit300 || *r == HitResult::Hit100)
+        {
+            katu += 1;
+        }
+    }
+
+    (geki, katu)
+}
+
+// stable rounds the summed settings and object density to a 2 to 6 multiplier
+fn difficulty_multiplier(beatmap: &BeatmapFile) -> f32 {
+    let objects = &beatmap.hit_objects;
+    let (first, last) = match (objects.first(), objects.last()) {
+        (Some(first), Some(last)) => (first.start_time, last.end_time.max(last.start_time)),
+        _ => return 0.0,
+    };
+
+    let breaks: f32 = beatmap.breaks().iter().map(|b| b.duration()).sum();
+    let drain_seconds = ((last - first - breaks) / 1000.0).max(1.0);
+    let density = (objects.len() as f32 / drain_seconds * 8.0).clamp(0.0, 16.0);
+
+    let difficulty = &beatmap.difficulty;
+    ((difficulty.hp_drain + difficulty.circle_size + difficulty.overall_difficulty + density)
+        / 38.0
+        * 5.0)
+        .round()
+}
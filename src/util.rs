@@ -1,5 +1,7 @@
 use std::{
-    ops::{Add, Div, Sub},
+    cmp::Ordering,
+    ops::{Add, Sub},
+    str::FromStr,
 };
 
 #[derive(Debug)]
@@ -20,6 +22,7 @@ impl Vector2 {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn div(self, divisor: f32) -> Self {
         Vector2 {
             x: self.x / divisor,
@@ -31,10 +34,11 @@ impl Vector2 {
         self.x * other.x + self.y * other.y
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn mul(self, other: Vector2) -> Vector2 {
         Vector2 {
             x: self.x * other.x,
-            y: self.y * other.y
+            y: self.y * other.y,
         }
     }
 
@@ -53,10 +57,7 @@ impl Copy for Vector2 {}
 
 impl Clone for Vector2 {
     fn clone(&self) -> Self {
-        Vector2 {
-            x: self.x,
-            y: self.y,
-        }
+        *self
     }
 }
 
@@ -87,3 +88,198 @@ impl Add for Vector2 {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Colour {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Colour { r, g, b }
+    }
+}
+
+impl FromStr for Colour {
+    type Err = ();
+
+    // "r,g,b", as used throughout .osu and .osb files
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let values: Vec<&str> = input.split(',').map(|s| s.trim()).collect();
+
+        if values.len() < 3 {
+            return Err(());
+        }
+
+        let channel = |value: &str| value.parse::<u8>().map_err(|_| ());
+        Ok(Colour {
+            r: channel(values[0])?,
+            g: channel(values[1])?,
+            b: channel(values[2])?,
+        })
+    }
+}
+
+// below this many items the legacy sort falls back to an insertion sort
+const INTROSORT_SIZE_THRESHOLD: usize = 16;
+
+// the unstable introsort .NET Framework used, which osu! still sorts some things with.
+// things that compare equal end up in the same order as they would in the game, which
+// any other sort can't promise
+pub fn legacy_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if items.len() < 2 {
+        return;
+    }
+
+    let depth_limit = 2 * floor_log2(items.len());
+    intro_sort(items, 0, items.len() - 1, depth_limit, &mut compare);
+}
+
+// one more than the real floor of the logarithm, like .NET's
+fn floor_log2(mut n: usize) -> usize {
+    let mut result = 0;
+    while n >= 1 {
+        result += 1;
+        n /= 2;
+    }
+
+    result
+}
+
+fn intro_sort<T, F>(
+    items: &mut [T],
+    lo: usize,
+    mut hi: usize,
+    mut depth_limit: usize,
+    compare: &mut F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while hi > lo {
+        let size = hi - lo + 1;
+        if size <= INTROSORT_SIZE_THRESHOLD {
+            match size {
+                2 => swap_if_greater(items, lo, hi, compare),
+                3 => {
+                    swap_if_greater(items, lo, hi - 1, compare);
+                    swap_if_greater(items, lo, hi, compare);
+                    swap_if_greater(items, hi - 1, hi, compare);
+                }
+                _ => insertion_sort(items, lo, hi, compare),
+            }
+
+            return;
+        }
+
+        if depth_limit == 0 {
+            heap_sort(items, lo, hi, compare);
+            return;
+        }
+
+        depth_limit -= 1;
+        let pivot = pick_pivot_and_partition(items, lo, hi, compare);
+        intro_sort(items, pivot + 1, hi, depth_limit, compare);
+        hi = pivot - 1;
+    }
+}
+
+fn swap_if_greater<T, F>(items: &mut [T], a: usize, b: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if a != b && compare(&items[a], &items[b]) == Ordering::Greater {
+        items.swap(a, b);
+    }
+}
+
+// median of three, the pivot waits just before the end while the rest is partitioned
+fn pick_pivot_and_partition<T, F>(items: &mut [T], lo: usize, hi: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let middle = lo + (hi - lo) / 2;
+    swap_if_greater(items, lo, middle, compare);
+    swap_if_greater(items, lo, hi, compare);
+    swap_if_greater(items, middle, hi, compare);
+
+    items.swap(middle, hi - 1);
+    let pivot = hi - 1;
+    let mut left = lo;
+    let mut right = hi - 1;
+
+    while left < right {
+        left += 1;
+        while compare(&items[left], &items[pivot]) == Ordering::Less {
+            left += 1;
+        }
+
+        right -= 1;
+        while compare(&items[pivot], &items[right]) == Ordering::Less {
+            right -= 1;
+        }
+
+        if left >= right {
+            break;
+        }
+
+        items.swap(left, right);
+    }
+
+    items.swap(left, hi - 1);
+    left
+}
+
+fn heap_sort<T, F>(items: &mut [T], lo: usize, hi: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = hi - lo + 1;
+    for i in (1..=n / 2).rev() {
+        down_heap(items, i, n, lo, compare);
+    }
+
+    for i in (2..=n).rev() {
+        items.swap(lo, lo + i - 1);
+        down_heap(items, 1, i - 1, lo, compare);
+    }
+}
+
+// the heap is 1-based from `lo`. instead of holding on to the item being moved down
+// like .NET does, it's swapped along the way, which ends up in the same place
+fn down_heap<T, F>(items: &mut [T], mut i: usize, n: usize, lo: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while i <= n / 2 {
+        let mut child = 2 * i;
+        if child < n && compare(&items[lo + child - 1], &items[lo + child]) == Ordering::Less {
+            child += 1;
+        }
+
+        if compare(&items[lo + i - 1], &items[lo + child - 1]) != Ordering::Less {
+            break;
+        }
+
+        items.swap(lo + i - 1, lo + child - 1);
+        i = child;
+    }
+}
+
+// shifts each item back past everything greater than it
+fn insertion_sort<T, F>(items: &mut [T], lo: usize, hi: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in lo..hi {
+        let mut j = i + 1;
+        while j > lo && compare(&items[j], &items[j - 1]) == Ordering::Less {
+            items.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
@@ -1,6 +1,14 @@
 mod tests {
-    use sekkei::parser::beatmap::BeatmapFile;
-    use std::env;
+    use sekkei::game::{Gamemode, ModSet, Mods};
+    use sekkei::parser::beatmap::{
+        document::BeatmapDocument,
+        error::{ParseErrorKind, ParseWarningKind},
+        events::{BreakPeriod, Event},
+        objects::{Countdown, HitSound, OverlayPosition, SampleSet, SliderObjectType},
+        BeatmapFile,
+    };
+    use sekkei::util::{Colour, Vector2};
+    use std::{convert::TryFrom, env};
 
     #[test]
     fn test_parser() {
@@ -21,4 +29,741 @@ mod tests {
         // print bm to file
         std::fs::write("./test.osu_dec", format!("{:#?}", bm));
     }
+
+    #[test]
+    fn test_parser_errors() {
+        let map = "osu file format v14\n\n[Difficulty]\nHPDrainRate:5\nCircleSize:big\n";
+        let err = BeatmapFile::try_from_str(map).unwrap_err();
+
+        assert!(matches!(err.kind, ParseErrorKind::InvalidValue));
+        assert_eq!(err.line, 5);
+        assert_eq!(err.section, "Difficulty");
+        assert_eq!(err.field, "CircleSize");
+        assert_eq!(err.text, "big");
+
+        // truncated hit object
+        let map = "osu file format v14\n\n[HitObjects]\n256,192,1000,1\n";
+        let err = BeatmapFile::try_from_str(map).unwrap_err();
+
+        assert!(matches!(err.kind, ParseErrorKind::MissingValue));
+        assert_eq!(err.line, 4);
+        assert_eq!(err.section, "HitObjects");
+        assert_eq!(err.field, "hitSound");
+
+        // curve points that aren't finite numbers
+        for point in &["NaN", "inf"] {
+            let map = format!(
+                "osu file format v14\n\n[TimingPoints]\n0,500,4,2,0,100,1,0\n\n\
+                 [HitObjects]\n100,100,1000,2,0,B|{}:100|200:100,1,100\n",
+                point
+            );
+            let err = BeatmapFile::try_from_str(&map).unwrap_err();
+
+            assert!(matches!(err.kind, ParseErrorKind::InvalidValue));
+            assert_eq!(err.line, 7);
+            assert_eq!(err.field, "curvePoints");
+            assert_eq!(err.text, *point);
+        }
+
+        // the curve approximation still ends when it's handed one
+        let points = [
+            Vector2::new(f32::NAN, 100.0),
+            Vector2::new(150.0, 50.0),
+            Vector2::new(200.0, 100.0),
+        ];
+        assert!(!BeatmapFile::approximate_bezier(&points).is_empty());
+
+        // missing file
+        let err = BeatmapFile::try_from_file("./tests/files/missing.osu").unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn test_parser_lenient() {
+        let map = "osu file format v14\n\n[Difficulty]  \nCircleSize: 4 \nApproachRate:fast\n\n\
+                   [TimingPoints]\n1000,500\n1500\n\n\
+                   [HitObjects]\n256,192,1000,1,0,0:0\n256,192,oops,1,0\n";
+        let (bm, warnings) = BeatmapFile::from_str_lenient(map);
+
+        assert_eq!(bm.difficulty.circle_size, 4.0);
+        assert_eq!(bm.difficulty.approach_rate, 0.0);
+        assert_eq!(bm.timing_points.len(), 1);
+        assert_eq!(bm.uninherited_points.len(), 1);
+        assert_eq!(bm.hit_objects.len(), 1);
+
+        let summary: Vec<(ParseWarningKind, usize, &str)> = warnings
+            .iter()
+            .map(|w| (w.kind, w.line, w.field.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ParseWarningKind::DefaultedValue, 5, "ApproachRate"),
+                (ParseWarningKind::DefaultedValue, 8, "meter"),
+                (ParseWarningKind::DroppedLine, 9, "beatLength"),
+                (ParseWarningKind::DefaultedValue, 12, "hitSample"),
+                (ParseWarningKind::DroppedLine, 13, "time"),
+            ]
+        );
+
+        // the same map is rejected in strict mode
+        assert!(BeatmapFile::try_from_str(map).is_err());
+    }
+
+    #[test]
+    fn test_general_section() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osu";
+        let bm = BeatmapFile::from_file(&path);
+
+        assert_eq!(bm.general.countdown, Countdown::None);
+        assert_eq!(bm.audio.sample_set, SampleSet::Normal);
+        assert!(!bm.general.letterbox_in_breaks);
+        assert!(bm.general.widescreen_storyboard);
+
+        let map = "osu file format v14\n\n[General]\nSampleSet: Soft\nCountdown: 3\n\
+                   CountdownOffset: 2\nOverlayPosition: Above\nSkinPreference: my skin\n\
+                   EpilepsyWarning: 1\nSpecialStyle: 1\nUseSkinSprites: 1\n\
+                   SamplesMatchPlaybackRate: 1\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(bm.audio.sample_set, SampleSet::Soft);
+        assert_eq!(bm.general.countdown, Countdown::Double);
+        assert_eq!(bm.general.countdown_offset, 2);
+        assert_eq!(bm.general.overlay_position, OverlayPosition::Above);
+        assert_eq!(bm.general.skin_preference, "my skin");
+        assert!(bm.general.epilepsy_warning);
+        assert!(bm.general.special_style);
+        assert!(bm.general.use_skin_sprites);
+        assert!(bm.audio.samples_match_playback_rate);
+    }
+
+    #[test]
+    fn test_metadata_section() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osu";
+        let bm = BeatmapFile::from_file(&path);
+
+        assert_eq!(bm.creator, "Irisu");
+        assert_eq!(bm.source, "");
+        assert_eq!(bm.metadata.beatmap_id, Some(0));
+        assert_eq!(bm.metadata.beatmapset_id, None);
+
+        let map =
+            "osu file format v14\n\n[Metadata]\nSource:some game\nBeatmapID:75\nBeatmapSetID:1\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(bm.source, "some game");
+        assert_eq!(bm.metadata.beatmap_id, Some(75));
+        assert_eq!(bm.metadata.beatmapset_id, Some(1));
+    }
+
+    #[test]
+    fn test_editor_section() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/IMAGINARY LIKE THE JUSTICE.osu";
+        let bm = BeatmapFile::from_file(&path);
+
+        assert!(bm.editor.bookmarks.is_empty());
+        assert_eq!(bm.editor.distance_spacing, 0.6);
+        assert_eq!(bm.editor.beat_divisor, 4);
+        assert_eq!(bm.editor.grid_size, 16);
+        assert_eq!(bm.editor.timeline_zoom, 1.7);
+
+        let map = "osu file format v14\n\n[Editor]\nBookmarks: 1024,2129,78076\nBeatDivisor: 3\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(bm.editor.bookmarks, vec![1024, 2129, 78076]);
+        assert_eq!(bm.editor.beat_divisor, 3);
+
+        // older maps keep these in [General]
+        let map = "osu file format v5\n\n[General]\nEditorBookmarks: 500,1500\nEditorDistanceSpacing: 1.2\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(bm.editor.bookmarks, vec![500, 1500]);
+        assert_eq!(bm.editor.distance_spacing, 1.2);
+    }
+
+    #[test]
+    fn test_events_section() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/IMAGINARY LIKE THE JUSTICE.osu";
+        let bm = BeatmapFile::from_file(&path);
+
+        assert_eq!(bm.background(), Some("338689.jpg"));
+        assert_eq!(bm.video(), None);
+        assert!(bm.breaks().is_empty());
+
+        let map = "osu file format v14\n\n[Events]\n0,0,\"bg.png\",0,0\n\
+                   Video,-200,\"intro.avi\"\n2,12000,15500\n\
+                   Sprite,Foreground,Centre,\"sb/star.png\",320,240\n _F,0,1000,2000,0,1\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(bm.background(), Some("bg.png"));
+        assert_eq!(bm.video(), Some("intro.avi"));
+        assert_eq!(
+            bm.breaks(),
+            vec![BreakPeriod {
+                start_time: 12000.0,
+                end_time: 15500.0
+            }]
+        );
+        assert_eq!(bm.breaks()[0].duration(), 3500.0);
+        assert_eq!(
+            bm.events[1],
+            Event::Video {
+                start_time: -200.0,
+                filename: "intro.avi".to_string(),
+                x_offset: 0,
+                y_offset: 0
+            }
+        );
+        assert_eq!(bm.events[4], Event::Raw(" _F,0,1000,2000,0,1".to_string()));
+    }
+
+    #[test]
+    fn test_hit_sounds() {
+        // the hit sounds are the bits of a hit object's hit sound field
+        let map = "osu file format v14\n\n[HitObjects]\n100,100,1000,1,12,0:0:0:0:\n";
+        let bm = BeatmapFile::from_str(map);
+        let has = |sound: HitSound| bm.hit_objects[0].hit_sound & sound as i32 != 0;
+        assert!(has(HitSound::Finish) && has(HitSound::Clap));
+        assert!(!has(HitSound::Normal) && !has(HitSound::Whistle));
+
+        assert!(matches!(HitSound::try_from(4), Ok(HitSound::Finish)));
+        assert!(matches!(HitSound::try_from(1), Ok(HitSound::Normal)));
+        assert!(HitSound::try_from(0).is_err());
+        assert!(HitSound::try_from(12).is_err());
+    }
+
+    #[test]
+    fn test_spinner_combos() {
+        let map = |version: i32, spinner_type: i32| {
+            format!(
+                "osu file format v{}\n\n[Colours]\nCombo1 : 255,0,0\nCombo2 : 0,255,0\n\
+                 Combo3 : 0,0,255\nCombo4 : 255,255,255\n\n\
+                 [HitObjects]\n\
+                 100,100,1000,5,0,0:0:0:0:\n\
+                 256,192,1500,{},0,2500,0:0:0:0:\n\
+                 100,100,3000,1,0,0:0:0:0:\n",
+                version, spinner_type
+            )
+        };
+        let combos = |bm: &BeatmapFile| -> Vec<(i32, i32, Option<Colour>)> {
+            bm.hit_objects
+                .iter()
+                .map(|h| (h.combo_index, h.combo_number, h.colour))
+                .collect()
+        };
+        let red = Some(Colour::new(255, 0, 0));
+
+        // a spinner without a new combo carries on the combo in newer maps
+        let bm = BeatmapFile::from_str(&map(14, 8));
+        assert_eq!(combos(&bm), vec![(0, 1, red), (0, 2, red), (0, 3, red)]);
+
+        // before v9 it ends it regardless
+        let bm = BeatmapFile::from_str(&map(8, 8));
+        let green = Some(Colour::new(0, 255, 0));
+        assert_eq!(combos(&bm), vec![(0, 1, red), (0, 2, red), (1, 1, green)]);
+
+        // the colours a spinner skips are skipped by the object after it
+        let bm = BeatmapFile::from_str(&map(14, 12 | 32));
+        let white = Some(Colour::new(255, 255, 255));
+        assert_eq!(combos(&bm), vec![(0, 1, red), (0, 2, red), (1, 1, white)]);
+    }
+
+    #[test]
+    fn test_colours_section() {
+        let map = "osu file format v14\n\n[Colours]\nCombo2 : 0,255,0\nCombo1 : 255,0,0\n\
+                   Combo3 : 0,0,255\nSliderBorder : 10,20,30\n\n\
+                   [HitObjects]\n\
+                   100,100,1000,5,0,0:0:0:0:\n\
+                   200,100,1500,1,0,0:0:0:0:\n\
+                   300,100,2000,5,0,0:0:0:0:\n\
+                   256,192,2500,12,0,4000,0:0:0:0:\n\
+                   100,100,4500,1,0,0:0:0:0:\n\
+                   200,100,5000,21,0,0:0:0:0:\n";
+        let bm = BeatmapFile::from_str(map);
+
+        assert_eq!(
+            bm.colours.combo_colours,
+            vec![
+                Colour::new(255, 0, 0),
+                Colour::new(0, 255, 0),
+                Colour::new(0, 0, 255)
+            ]
+        );
+        assert_eq!(bm.colours.slider_border, Some(Colour::new(10, 20, 30)));
+        assert_eq!(bm.colours.slider_track_override, None);
+
+        // there's only room for 8 combo colours, anything numbered past that is refused
+        // rather than making room for it
+        let numbered = |number: &str| {
+            format!(
+                "osu file format v14\n\n[Colours]\nCombo1 : 255,0,0\nCombo{} : 1,2,3\n",
+                number
+            )
+        };
+        let err = BeatmapFile::try_from_str(&numbered("99999999999")).unwrap_err();
+        assert!(matches!(err.kind, ParseErrorKind::InvalidValue));
+        assert_eq!((err.line, err.field.as_str()), (5, "Combo99999999999"));
+        assert!(BeatmapFile::try_from_str(&numbered("0")).is_err());
+        assert_eq!(
+            BeatmapFile::try_from_str(&numbered("8"))
+                .unwrap()
+                .colours
+                .combo_colours
+                .len(),
+            8
+        );
+
+        let (lenient, warnings) = BeatmapFile::from_str_lenient(&numbered("9"));
+        assert_eq!(lenient.colours.combo_colours, vec![Colour::new(255, 0, 0)]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].field, "Combo9");
+
+        let combos: Vec<(i32, i32, Option<Colour>)> = bm
+            .hit_objects
+            .iter()
+            .map(|h| (h.combo_index, h.combo_number, h.colour))
+            .collect();
+        assert_eq!(
+            combos,
+            vec![
+                (0, 1, Some(Colour::new(255, 0, 0))),
+                (0, 2, Some(Colour::new(255, 0, 0))),
+                (1, 1, Some(Colour::new(0, 255, 0))),
+                // the spinner continues the combo, the object after it starts a new one
+                (1, 2, Some(Colour::new(0, 255, 0))),
+                (2, 1, Some(Colour::new(0, 0, 255))),
+                // skipping one colour wraps back around to the second
+                (3, 1, Some(Colour::new(0, 255, 0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slider_timing() {
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   SliderMultiplier:1.4\n\
+                   SliderTickRate:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   1000,-50,4,2,0,100,0,0\n\
+                   3000,400,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,100,1000,2,0,L|240:100,2,140\n";
+        let bm = BeatmapFile::from_str(map);
+
+        // points apply from their own time on, anything earlier uses the first one
+        assert_eq!(bm.get_timing_point(-100.0).time, 0.0);
+        assert_eq!(bm.get_timing_point(999.0).time, 0.0);
+        assert_eq!(bm.get_timing_point(1000.0).time, 1000.0);
+        assert_eq!(bm.get_uninherited_timing_point(2000.0).beat_length, 500.0);
+        assert_eq!(bm.get_uninherited_timing_point(3500.0).beat_length, 400.0);
+
+        // the green line doubles the velocity until the next red line resets it
+        assert_eq!(bm.get_speed_multiplier(500.0), 1.0);
+        assert_eq!(bm.get_speed_multiplier(2000.0), 2.0);
+        assert_eq!(bm.get_speed_multiplier(3500.0), 1.0);
+
+        // 280px per beat of 500ms, two spans of 140px
+        let slider = &bm.hit_objects[0];
+        assert_eq!(slider.end_time, 1500.0);
+        // an even number of slides ends back on the head
+        assert_eq!(slider.end_position, slider.position);
+
+        let slider_objects = slider.slider_objects.as_ref().unwrap();
+        let repeat = slider_objects
+            .iter()
+            .find(|o| matches!(o.slider_object_type, SliderObjectType::SliderRepeat))
+            .unwrap();
+        assert_eq!(repeat.start_time, 1250.0);
+        assert_eq!((repeat.span_index, repeat.span_start_time), (0, 1000.0));
+        assert!((repeat.position.x - 240.0).abs() < 0.01);
+
+        let tail = slider_objects
+            .iter()
+            .find(|o| matches!(o.slider_object_type, SliderObjectType::SliderEnd))
+            .unwrap();
+        assert_eq!((tail.span_index, tail.span_start_time), (1, 1250.0));
+        assert!(!slider_objects
+            .iter()
+            .any(|o| matches!(o.slider_object_type, SliderObjectType::SliderTick)));
+    }
+
+    #[test]
+    fn test_perfect_curve() {
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   SliderMultiplier:1.4\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,200,1000,2,0,P|200:100|300:200,1,157.0796\n";
+        let bm = BeatmapFile::from_str(map);
+        let slider = &bm.hit_objects[0];
+
+        // the arc goes around (200, 200), a quarter of the way from the head
+        let centre = Vector2::new(200.0, 200.0);
+        let body = &slider.slider_data.as_ref().unwrap().slider_body.body;
+        assert!(body.len() > 2);
+        for point in body {
+            assert!(((slider.position + *point).distance(centre) - 100.0).abs() < 0.5);
+        }
+
+        assert!(slider.end_position.distance(Vector2::new(200.0, 100.0)) < 0.5);
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        for name in &["kakushigoto.osu", "IMAGINARY LIKE THE JUSTICE.osu"] {
+            let path =
+                env::current_dir().unwrap().to_str().unwrap().to_string() + "/tests/files/" + name;
+            let bm = BeatmapFile::from_file(&path);
+
+            let written = bm.to_osu_string();
+            let reparsed = BeatmapFile::from_str(&written);
+
+            assert_eq!(format!("{:?}", reparsed), format!("{:?}", bm), "{}", name);
+            assert_eq!(reparsed.to_osu_string(), written, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_writer_output() {
+        let map = "osu file format v14\n\n\
+                   [General]\n\
+                   SampleSet: Soft\n\
+                   [Metadata]\n\
+                   Title:Test\n\
+                   BeatmapID:-1\n\
+                   [Difficulty]\n\
+                   SliderMultiplier:1.4\n\
+                   [Events]\n\
+                   0,0,\"bg.jpg\",0,0\n\
+                   2,1000,2000\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,1,60,1,0\n\
+                   1000,-50,4,3,0,80,0,1\n\
+                   [HitObjects]\n\
+                   100,100,0,5,2,1:2:0:70:hit.wav\n\
+                   100,100,500,6,0,P|150:150|200:100,2,140,2|0|8,1:0|0:0|2:3,0:0:0:0:\n\
+                   256,192,3000,12,0,4000\n";
+        let bm = BeatmapFile::from_str(map);
+
+        let written = bm.to_osu_string();
+        assert!(written.starts_with("osu file format v14\n"));
+        assert!(written.contains("SampleSet: Soft\n"));
+        assert!(written.contains("BeatmapID:-1\n"));
+        assert!(written.contains("0,0,\"bg.jpg\",0,0\n"));
+        assert!(written.contains("2,1000,2000\n"));
+        assert!(written.contains("0,500,4,2,1,60,1,0\n"));
+        assert!(written.contains("1000,-50,4,3,0,80,0,1\n"));
+        assert!(written.contains("100,100,0,5,2,1:2:0:70:hit.wav\n"));
+        assert!(written
+            .contains("100,100,500,6,0,P|150:150|200:100,2,140,2|0|8,1:0|0:0|2:3,0:0:0:0:\n"));
+        assert!(written.contains("256,192,3000,12,0,4000,0:0:0:0:\n"));
+
+        let reparsed = BeatmapFile::from_str(&written);
+        assert_eq!(format!("{:?}", reparsed), format!("{:?}", bm));
+
+        // older versions read timing 24ms late, the writer has to undo that
+        let mut old = BeatmapFile::from_str(map);
+        old.format_version = 4;
+        let written = old.to_osu_string();
+        assert!(written.contains("-24,500,4,2,1,60,1,0\n"));
+        assert_eq!(
+            BeatmapFile::from_str(&written).timing_points[0].time,
+            bm.timing_points[0].time
+        );
+    }
+
+    #[test]
+    fn test_document_round_trip() {
+        for name in &["kakushigoto.osu", "IMAGINARY LIKE THE JUSTICE.osu"] {
+            let path =
+                env::current_dir().unwrap().to_str().unwrap().to_string() + "/tests/files/" + name;
+            let original = std::fs::read_to_string(&path).unwrap();
+            let document = BeatmapDocument::from_file(&path);
+
+            assert_eq!(document.to_osu_string(), original, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_document_edits() {
+        let map = "\u{feff}osu file format v14\r\n\
+                   \r\n\
+                   // made by hand\r\n\
+                   [General]\r\n\
+                   AudioFilename:audio.mp3\r\n\
+                   SomethingNew: 1\r\n\
+                   \r\n\
+                   [Metadata]\r\n\
+                   Title  :  Test\r\n\
+                   \r\n\
+                   [Fancy]\r\n\
+                   whatever goes here\r\n\
+                   \r\n\
+                   [TimingPoints]\r\n\
+                   0,500,4,2,0,60,1,0\r\n\
+                   1000,500.00,4,2,0,60,1,0\r\n\
+                   \r\n\
+                   [HitObjects]\r\n\
+                   100,100,0,5,0,0:0:0:0:\r\n\
+                   // break\r\n\
+                   200,100,500,1,0,0:0:0:0:";
+
+        // nothing changed, nothing moves
+        let mut document = BeatmapDocument::from_str(map);
+        assert_eq!(document.to_osu_string(), map);
+
+        document.beatmap.timing_points[0].volume = 80;
+        document.beatmap.title = "Renamed".to_string();
+        document.beatmap.general.skin_preference = "Default".to_string();
+        let mut hit_object = document.beatmap.hit_objects[1].clone();
+        hit_object.start_time = 1000.0;
+        document.beatmap.hit_objects.push(hit_object);
+
+        let expected = map
+            .replace("0,500,4,2,0,60,1,0", "0,500,4,2,0,80,1,0")
+            .replace("Title  :  Test", "Title  :  Renamed")
+            .replace(
+                "SomethingNew: 1\r\n",
+                "SomethingNew: 1\r\nSkinPreference: Default\r\n",
+            )
+            + "\r\n200,100,1000,1,0,0:0:0:0:\r\n";
+        let written = document.to_osu_string();
+        assert_eq!(written, expected);
+
+        let reparsed = BeatmapFile::from_str(&written);
+        assert_eq!(reparsed.title, "Renamed");
+        assert_eq!(reparsed.timing_points[0].volume, 80);
+        assert_eq!(reparsed.hit_objects.len(), 3);
+    }
+
+    #[test]
+    fn test_with_mods() {
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   PreviewTime:3000\n\
+                   [Difficulty]\n\
+                   HPDrainRate:5\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:8\n\
+                   ApproachRate:9\n\
+                   SliderMultiplier:1.4\n\
+                   [Events]\n\
+                   2,3000,6000\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   1500,-50,4,2,0,100,0,0\n\
+                   [HitObjects]\n\
+                   256,100,1000,1,0,0:0:0:0:\n\
+                   256,100,1500,2,0,L|356:50,1,111.803\n\
+                   256,192,7000,12,0,8000,0:0:0:0:\n";
+        let bm = BeatmapFile::from_str(map);
+
+        let hr = bm.with_mods(ModSet::from(Mods::HardRock));
+        assert_eq!(hr.difficulty.circle_size, 5.2);
+        assert_eq!(hr.difficulty.approach_rate, 10.0);
+        assert_eq!(hr.difficulty.overall_difficulty, 10.0);
+        assert_eq!(hr.difficulty.hp_drain, 7.0);
+
+        // flipped upside down, slider paths included
+        let circle = &hr.hit_objects[0];
+        assert_eq!((circle.x, circle.y), (256.0, 284.0));
+        let slider = &hr.hit_objects[1];
+        assert_eq!(slider.position.y, 284.0);
+        assert!((slider.end_position.x - 356.0).abs() < 0.01);
+        assert!((slider.end_position.y - 334.0).abs() < 0.01);
+        let slider_data = slider.slider_data.as_ref().unwrap();
+        assert_eq!(slider_data.base_points[0].y, 334.0);
+        assert_eq!(slider_data.slider_points[1].y, 50.0);
+        let tail = slider.slider_objects.as_ref().unwrap().last().unwrap();
+        assert!((tail.position.y - 334.0).abs() < 0.01);
+
+        let ez = bm.with_mods(ModSet::from(Mods::Easy));
+        assert_eq!(ez.difficulty.circle_size, 2.0);
+        assert_eq!(ez.difficulty.approach_rate, 4.5);
+        assert_eq!(ez.hit_objects[0].y, 100.0);
+
+        // double time plays everything 1.5 times faster, velocities stay the same
+        let dt = bm.with_mods(ModSet::from(Mods::DoubleTime));
+        let times: Vec<f32> = dt.hit_objects.iter().map(|o| o.start_time).collect();
+        assert_eq!(times, vec![1000.0 / 1.5, 1000.0, 7000.0 / 1.5]);
+        assert_eq!(dt.hit_objects[2].end_time, 8000.0 / 1.5);
+        assert_eq!(dt.timing_points[0].beat_length, 500.0 / 1.5);
+        assert_eq!(dt.timing_points[1].beat_length, -50.0);
+        assert_eq!(dt.timing_points[1].time, 1000.0);
+        assert_eq!(dt.uninherited_points[0].beat_length, 500.0 / 1.5);
+        assert_eq!(
+            dt.breaks(),
+            vec![BreakPeriod {
+                start_time: 2000.0,
+                end_time: 4000.0
+            }]
+        );
+        assert_eq!(dt.metadata.preview_time, 2000);
+        assert_eq!(dt.difficulty.approach_rate, 9.0);
+        let slider = &bm.hit_objects[1];
+        let dt_slider = &dt.hit_objects[1];
+        assert!(
+            ((dt_slider.end_time - dt_slider.start_time) * 1.5
+                - (slider.end_time - slider.start_time))
+                .abs()
+                < 0.01
+        );
+
+        let ht = bm.with_mods(ModSet::from(Mods::HalfTime));
+        assert_eq!(ht.hit_objects[0].start_time, 1000.0 / 0.75);
+
+        // the original is left as it was
+        assert_eq!(bm.hit_objects[0].y, 100.0);
+        assert_eq!(bm.hit_objects[0].start_time, 1000.0);
+        assert_eq!(bm.difficulty.circle_size, 4.0);
+
+        // a mania map's key count stays put
+        let mania = BeatmapFile::from_str(
+            "osu file format v14\n[General]\nMode:3\n[Difficulty]\nCircleSize:4\n",
+        );
+        assert_eq!(
+            mania
+                .with_mods(ModSet::from(Mods::HardRock))
+                .difficulty
+                .circle_size,
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_difficulty_attributes() {
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   HPDrainRate:5\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:8\n\
+                   ApproachRate:9\n";
+        let bm = BeatmapFile::from_str(map);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+
+        let nomod = bm.attributes(Gamemode::Standard, ModSet::default());
+        assert!(close(nomod.approach_rate, 9.0));
+        assert!(close(nomod.overall_difficulty, 8.0));
+        assert_eq!((nomod.preempt, nomod.fade_in), (600.0, 400.0));
+        assert!(close(nomod.hit_window_300, 32.0));
+        assert!(close(nomod.hit_window_100, 76.0));
+        assert!(close(nomod.hit_window_50, 120.0));
+        assert!(close(nomod.circle_radius, 36.48));
+        assert!(close(nomod.spins_per_second, 6.5));
+        assert_eq!(nomod.required_spins(2000.0), 13);
+
+        // the windows stay in map time, ar and od are what the rate makes them feel like
+        let dt = bm.attributes(Gamemode::Standard, ModSet::from(Mods::DoubleTime));
+        assert_eq!(dt.preempt, 600.0);
+        assert!(close(dt.hit_window_300, 32.0));
+        assert!(close(dt.approach_rate, 31.0 / 3.0));
+        assert!(close(dt.overall_difficulty, 88.0 / 9.0));
+
+        let hr = bm.attributes(Gamemode::Standard, ModSet::from(Mods::HardRock));
+        assert!(close(hr.circle_size, 5.2));
+        assert!(close(hr.hp_drain, 7.0));
+        assert_eq!((hr.approach_rate, hr.overall_difficulty), (10.0, 10.0));
+        assert_eq!((hr.preempt, hr.hit_window_300), (450.0, 20.0));
+        let ez = bm.attributes(Gamemode::Standard, ModSet::from(Mods::Easy));
+        assert!(close(ez.preempt, 1260.0));
+        assert!(close(ez.hit_window_300, 56.0));
+
+        let taiko = bm.attributes(Gamemode::Taiko, ModSet::default());
+        assert!(close(taiko.hit_window_300, 26.0));
+        assert!(close(taiko.hit_window_100, 62.0));
+        assert_eq!(taiko.hit_window_50, 0.0);
+
+        let catch = bm.attributes(Gamemode::Catch, ModSet::default());
+        assert_eq!(catch.hit_window_300, 0.0);
+
+        // converts to mania have fixed windows, mania maps scale with od
+        let convert = bm.attributes(Gamemode::Mania, ModSet::default());
+        assert_eq!(convert.hit_window_300, 34.0);
+        assert_eq!(convert.hit_window_50, 121.0);
+        let mania = bm.difficulty.attributes(Gamemode::Mania, ModSet::default());
+        assert!(close(mania.hit_window_300, 40.0));
+        assert_eq!(mania.circle_size, 4.0);
+        let mania_hr = bm
+            .difficulty
+            .attributes(Gamemode::Mania, ModSet::from(Mods::HardRock));
+        assert!(close(mania_hr.hit_window_300, 40.0 / 1.4));
+    }
+
+    #[test]
+    fn test_stacking() {
+        let map = |version: i32| {
+            format!(
+                "osu file format v{}\n\
+                 [General]\n\
+                 StackLeniency:0.7\n\
+                 [Difficulty]\n\
+                 CircleSize:4\n\
+                 ApproachRate:5\n\
+                 SliderMultiplier:1.4\n\
+                 [TimingPoints]\n\
+                 0,500,4,2,0,100,1,0\n\
+                 [HitObjects]\n\
+                 100,100,1000,1,0\n\
+                 102,100,1100,1,0\n\
+                 104,100,1200,1,0\n\
+                 300,100,2000,2,0,L|400:100,1,100\n\
+                 400,100,2600,1,0\n\
+                 400,100,2700,1,0\n",
+                version
+            )
+        };
+        let heights = |bm: &BeatmapFile| -> Vec<i32> {
+            bm.hit_objects.iter().map(|o| o.stack_height).collect()
+        };
+
+        // circles under a slider's end stack down and right from it
+        let bm = BeatmapFile::from_str(&map(14));
+        assert_eq!(heights(&bm), vec![2, 1, 0, 0, -1, -2]);
+
+        // older maps only stack onto the first object's position, so the drifting
+        // stack is cut short
+        let old = BeatmapFile::from_str(&map(5));
+        assert_eq!(heights(&old), vec![1, 1, 0, 0, -1, -2]);
+
+        let radius = bm
+            .attributes(Gamemode::Standard, ModSet::default())
+            .circle_radius as f32;
+        let first = bm.hit_objects[0].stacked_position(radius);
+        assert!((first.x - (100.0 - 2.0 * radius / 10.0)).abs() < 0.001);
+        assert!((first.y - (100.0 - 2.0 * radius / 10.0)).abs() < 0.001);
+        let last = bm.hit_objects[5].stacked_position(radius);
+        assert!((last.x - (400.0 + 2.0 * radius / 10.0)).abs() < 0.001);
+        let slider_end = bm.hit_objects[3].stacked_end_position(radius);
+        assert!((slider_end.x - 400.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fixture_stacking() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/IMAGINARY LIKE THE JUSTICE.osu";
+        let text = std::fs::read_to_string(path).unwrap();
+        let stacked = |version: &str| -> Vec<(usize, i32)> {
+            let text = text.replacen("osu file format v14", version, 1);
+            BeatmapFile::from_str(&text)
+                .hit_objects
+                .iter()
+                .enumerate()
+                .filter(|(_, o)| o.stack_height != 0)
+                .map(|(i, o)| (i, o.stack_height))
+                .collect()
+        };
+
+        // checked against a separate transcription of lazer's OsuBeatmapProcessor, not
+        // against the game itself. both algorithms stack the same two circles here
+        assert_eq!(stacked("osu file format v14"), vec![(2, 1), (93, 1)]);
+        assert_eq!(stacked("osu file format v5"), vec![(2, 1), (93, 1)]);
+    }
 }
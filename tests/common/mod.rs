@@ -0,0 +1,31 @@
+// helpers shared by the test files, not every file uses all of them
+#![allow(dead_code)]
+
+use sekkei::{
+    game::{score::Score, ModSet},
+    parser::beatmap::BeatmapFile,
+};
+use std::env;
+
+pub fn fixture(name: &str) -> BeatmapFile {
+    let path = env::current_dir().unwrap().to_str().unwrap().to_string() + "/tests/files/" + name;
+    BeatmapFile::from_file(&path)
+}
+
+// a score with the given (300, 100, 50, miss) counts and nothing else filled in
+pub fn score(mods: ModSet, max_combo: i32, counts: (i32, i32, i32, i32)) -> Score {
+    Score {
+        score_id: 0,
+        user_id: 0,
+        beatmap_id: 0,
+        mods,
+        score: 0,
+        max_combo,
+        count_300: counts.0,
+        count_100: counts.1,
+        count_50: counts.2,
+        count_geki: 0,
+        count_katu: 0,
+        count_miss: counts.3,
+    }
+}
@@ -0,0 +1,739 @@
+mod common;
+
+mod tests {
+    use crate::common::fixture;
+    use sekkei::{
+        difficulty::{catch, mania, standard, taiko},
+        game::{
+            catch::{self as catch_objects, CatchObjectKind},
+            convert::{self, ConvertedObjects},
+            mania::{self as mania_objects, ManiaHold, ManiaNote, ManiaObject},
+            taiko::{self as taiko_objects, TaikoObjectKind},
+            Gamemode, ModSet, Mods,
+        },
+        parser::beatmap::BeatmapFile,
+        util,
+    };
+
+    #[test]
+    fn test_standard_difficulty() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let stars = |mods: ModSet| standard::calculate(&beatmap, mods).stars;
+
+        let difficulty = standard::calculate(&beatmap, ModSet::default());
+        assert_eq!(difficulty.max_combo, 135);
+        assert_eq!(
+            (
+                difficulty.circle_count,
+                difficulty.slider_count,
+                difficulty.spinner_count
+            ),
+            (71, 32, 0)
+        );
+        assert!(difficulty.slider_factor > 0.9 && difficulty.slider_factor <= 1.0);
+        assert!(difficulty.speed_note_count > 0.0);
+        assert!(difficulty.speed_note_count <= 102.0);
+
+        // no reference values from osu! can be pulled into the tests, these were
+        // recorded from this implementation to catch accidental changes
+        assert!((difficulty.stars - 5.593).abs() < 0.01);
+        assert!((difficulty.aim - 3.017).abs() < 0.01);
+        assert!((difficulty.speed - 2.187).abs() < 0.01);
+
+        // the same for the mods that change the strains, as (stars, aim, speed, flashlight)
+        let modded = |mods: &str| {
+            let difficulty = standard::calculate(&beatmap, mods.parse().unwrap());
+            [
+                difficulty.stars,
+                difficulty.aim,
+                difficulty.speed,
+                difficulty.flashlight,
+            ]
+        };
+        let close = |values: [f64; 4], expected: [f64; 4]| {
+            values
+                .iter()
+                .zip(&expected)
+                .all(|(a, b)| (a - b).abs() < 0.01)
+        };
+        assert!(close(modded("HR"), [5.990, 3.286, 2.216, 2.208]));
+        assert!(close(modded("DT"), [7.829, 4.175, 3.159, 2.663]));
+        assert!(close(modded("FL"), [6.372, 3.017, 2.187, 1.789]));
+
+        let nomod = stars(ModSet::default());
+        assert!(stars(ModSet::from(Mods::HalfTime)) < nomod);
+        assert!(stars(ModSet::from(Mods::Easy)) < nomod);
+        assert!(stars(ModSet::from(Mods::HardRock)) > nomod);
+        assert!(stars(ModSet::from(Mods::DoubleTime)) > stars(ModSet::from(Mods::HardRock)));
+        assert_eq!(
+            stars(ModSet::from(Mods::Nightcore)),
+            stars(ModSet::from(Mods::DoubleTime))
+        );
+
+        // hidden only changes flashlight, which only counts with the mod on
+        let hidden = standard::calculate(&beatmap, ModSet::from(Mods::Hidden));
+        assert_eq!(hidden.stars, nomod);
+        assert!(hidden.flashlight > difficulty.flashlight);
+        assert!(stars(ModSet::from(Mods::Flashlight)) > nomod);
+
+        let relax = standard::calculate(&beatmap, ModSet::from(Mods::Relax));
+        assert_eq!(relax.speed, 0.0);
+        assert!(relax.stars < nomod);
+    }
+
+    #[test]
+    fn test_standard_settings() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+
+        // AR9.3 OD8.9 HP5
+        let hard_rock = standard::calculate(&beatmap, ModSet::from(Mods::HardRock));
+        assert_eq!(hard_rock.approach_rate, 10.0);
+        assert_eq!(hard_rock.overall_difficulty, 10.0);
+        assert_eq!(hard_rock.drain_rate, 7.0);
+
+        let easy = standard::calculate(&beatmap, ModSet::from(Mods::Easy));
+        assert!((easy.approach_rate - 4.65).abs() < 1e-4);
+        assert!((easy.drain_rate - 2.5).abs() < 1e-4);
+
+        // 555ms of preempt played at 1.5x is 370ms, the 300 window goes from 26.6ms to 17.7ms
+        let double_time = standard::calculate(&beatmap, ModSet::from(Mods::DoubleTime));
+        assert!((double_time.approach_rate - 10.5333).abs() < 1e-3);
+        assert!((double_time.overall_difficulty - 10.3778).abs() < 1e-3);
+        assert_eq!(double_time.drain_rate, 5.0);
+    }
+
+    #[test]
+    fn test_standard_strains() {
+        // the second circle is the only one that gets rated: 1000ms apart and far
+        // enough to get the full spacing bonus, there's nothing to aim at yet
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:8\n\
+                   ApproachRate:9\n\
+                   SliderMultiplier:1\n\
+                   SliderTickRate:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   64,192,1000,5,0,0:0:0:0:\n\
+                   448,192,2000,1,0,0:0:0:0:\n";
+        let difficulty = standard::calculate(&BeatmapFile::from_str(map), ModSet::default());
+
+        // (1 + 1) / 1000 * 1375 for the strain, the top section is weighed down to
+        // 0.75 and scaled by 1.04
+        let speed = (2.0 / 1000.0 * 1375.0 * 0.75 * 1.04f64).sqrt() * 0.0675;
+        assert!((difficulty.speed - speed).abs() < 1e-9);
+        assert_eq!(difficulty.aim, 0.0);
+        assert!(difficulty.stars > 0.0);
+
+        // a lone slider has nothing before it to be rated against
+        let beatmap = fixture("kakushigoto.osu");
+        let difficulty = standard::calculate(&beatmap, ModSet::default());
+        assert_eq!(difficulty.stars, 0.0);
+        assert_eq!(difficulty.max_combo, 3);
+    }
+
+    #[test]
+    fn test_standard_hard_rock_stacking() {
+        // the pairs are 400ms apart: close enough to stack at approach rate 8 (525ms
+        // with the leniency), too far apart with hard rock's 10 (315ms)
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   StackLeniency:0.7\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:8\n\
+                   ApproachRate:8\n\
+                   SliderMultiplier:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,100,1000,1,0\n\
+                   100,100,1400,1,0\n\
+                   300,250,1800,1,0\n\
+                   300,250,2200,1,0\n\
+                   100,100,2600,1,0\n\
+                   100,100,3000,1,0\n";
+        let beatmap = BeatmapFile::from_str(map);
+        let heights = |beatmap: &BeatmapFile| -> Vec<i32> {
+            beatmap.hit_objects.iter().map(|o| o.stack_height).collect()
+        };
+        assert_eq!(heights(&beatmap), vec![1, 0, 1, 0, 1, 0]);
+
+        let hard_rock: ModSet = "HR".parse().unwrap();
+        assert_eq!(heights(&beatmap.with_mods(hard_rock)), vec![0; 6]);
+
+        // with nothing stacked the circles of a pair sit on top of each other, so the
+        // calculator has to see the same map as one that never stacked
+        let mut unstacked = beatmap.clone();
+        for object in &mut unstacked.hit_objects {
+            object.stack_height = 0;
+        }
+
+        let modded = standard::calculate(&beatmap, hard_rock);
+        let expected = standard::calculate(&unstacked, hard_rock);
+        assert_eq!(modded.aim, expected.aim);
+        assert_eq!(modded.speed, expected.speed);
+        assert_eq!(modded.stars, expected.stars);
+
+        let nomod = standard::calculate(&beatmap, ModSet::default());
+        let nomod_unstacked = standard::calculate(&unstacked, ModSet::default());
+        assert_ne!(nomod.aim, nomod_unstacked.aim);
+    }
+
+    // 125ms streams around a big drum roll and a swell, `colours` are the hit sounds
+    // of the notes in each stream
+    fn taiko_map(colours: &[i32]) -> BeatmapFile {
+        let stream = |start: i32| -> String {
+            colours
+                .iter()
+                .enumerate()
+                .map(|(i, hit_sound)| {
+                    format!(
+                        "256,192,{},1,{},0:0:0:0:\n",
+                        start + i as i32 * 125,
+                        hit_sound
+                    )
+                })
+                .collect()
+        };
+
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   Mode:1\n\
+                   [Difficulty]\n\
+                   CircleSize:5\n\
+                   OverallDifficulty:5\n\
+                   ApproachRate:5\n\
+                   SliderMultiplier:1.4\n\
+                   SliderTickRate:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n"
+            .to_string()
+            + &stream(1000)
+            + "256,192,4000,2,4,L|396:192,1,280\n"
+            + "256,192,5500,12,0,7500,0:0:0:0:\n"
+            + &stream(8000);
+
+        BeatmapFile::from_str(&map)
+    }
+
+    #[test]
+    fn test_taiko_conversion() {
+        let beatmap = taiko_map(&[0, 2, 8, 4, 6]);
+        let objects = taiko_objects::convert(&beatmap);
+
+        let kinds: Vec<TaikoObjectKind> = objects.iter().take(5).map(|o| o.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TaikoObjectKind::Don,
+                TaikoObjectKind::Kat,
+                TaikoObjectKind::Kat,
+                TaikoObjectKind::Don,
+                TaikoObjectKind::Kat
+            ]
+        );
+        let big: Vec<bool> = objects.iter().take(5).map(|o| o.big).collect();
+        assert_eq!(big, vec![false, false, false, true, true]);
+
+        // 280px at 140px a beat is two beats, a tick every quarter beat from 4000 to 5000
+        let drum_roll = objects[5];
+        assert_eq!(drum_roll.kind, TaikoObjectKind::DrumRoll);
+        assert!(drum_roll.big);
+        assert_eq!(drum_roll.duration(), 1000.0);
+        assert_eq!(drum_roll.ticks, 9);
+
+        // OD5 wants 5 * 1.65 hits a second
+        let swell = objects[6];
+        assert_eq!(swell.kind, TaikoObjectKind::Swell);
+        assert_eq!(swell.ticks, 16);
+
+        assert!(objects.iter().all(|o| o.is_hit() == (o.ticks == 0)));
+    }
+
+    #[test]
+    fn test_taiko_native_conversion() {
+        // maps made for taiko convert like standard maps do: the slider is too short to
+        // be a drum roll and the circles on top of each other are one big note
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   Mode:1\n\
+                   [Difficulty]\n\
+                   SliderMultiplier:1.4\n\
+                   SliderTickRate:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   256,192,1000,2,0,L|291:192,1,35\n\
+                   256,192,2000,1,0,0:0:0:0:\n\
+                   256,192,2000,1,0,0:0:0:0:\n";
+        let objects = taiko_objects::convert(&BeatmapFile::from_str(map));
+
+        // 49px at 196px a beat lasts 125ms, a note at each end
+        let notes: Vec<(TaikoObjectKind, f32, bool)> = objects
+            .iter()
+            .map(|o| (o.kind, o.start_time, o.big))
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (TaikoObjectKind::Don, 1000.0, false),
+                (TaikoObjectKind::Don, 1125.0, false),
+                (TaikoObjectKind::Don, 2000.0, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_taiko_difficulty() {
+        let beatmap = taiko_map(&[0, 0, 2, 0, 2, 2, 0, 2, 0, 0, 2, 2, 0, 2, 2, 0]);
+        let stars = |mods: ModSet| taiko::calculate(&beatmap, mods).stars;
+
+        let difficulty = taiko::calculate(&beatmap, ModSet::default());
+        assert_eq!(difficulty.max_combo, 32);
+        assert_eq!(difficulty.great_hit_window, 35.0);
+        assert!(difficulty.colour > 0.0);
+        assert!(difficulty.stamina > 0.0);
+        assert!(difficulty.stars > 0.0);
+
+        let nomod = stars(ModSet::default());
+        assert!(stars(ModSet::from(Mods::DoubleTime)) > nomod);
+        assert!(stars(ModSet::from(Mods::HalfTime)) < nomod);
+        // hard rock doesn't move notes, only the hit windows get tighter
+        assert_eq!(stars(ModSet::from(Mods::HardRock)), nomod);
+        let double_time = taiko::calculate(&beatmap, ModSet::from(Mods::DoubleTime));
+        assert!((double_time.great_hit_window - 35.0 / 1.5).abs() < 1e-9);
+
+        // a single colour has no changes to read and is easier to play
+        let mono = taiko::calculate(&taiko_map(&[0; 16]), ModSet::default());
+        assert_eq!(mono.colour, 0.0);
+        assert!(mono.stars < nomod);
+    }
+
+    // a juice stream with a repeat, a banana shower and a jump across the screen
+    fn catch_map() -> BeatmapFile {
+        BeatmapFile::from_str(
+            "osu file format v14\n\
+             [General]\n\
+             Mode:2\n\
+             [Difficulty]\n\
+             CircleSize:4\n\
+             OverallDifficulty:8\n\
+             ApproachRate:8\n\
+             SliderMultiplier:1\n\
+             SliderTickRate:1\n\
+             [TimingPoints]\n\
+             0,500,4,2,0,100,1,0\n\
+             [HitObjects]\n\
+             64,192,1000,6,0,L|264:192,2,200\n\
+             256,192,4000,12,0,5000,0:0:0:0:\n\
+             0,192,5500,5,0,0:0:0:0:\n\
+             512,192,5600,1,0,0:0:0:0:\n\
+             256,192,6500,1,0,0:0:0:0:\n",
+        )
+    }
+
+    #[test]
+    fn test_catch_conversion() {
+        let objects = catch_objects::convert(&catch_map(), ModSet::default());
+        let of_kind = |kind: CatchObjectKind| -> Vec<_> {
+            objects.iter().filter(|o| o.kind == kind).cloned().collect()
+        };
+
+        // head, repeat and tail, then the three circles
+        let fruits = of_kind(CatchObjectKind::Fruit);
+        let positions: Vec<(f32, f32)> = fruits.iter().map(|o| (o.start_time, o.x)).collect();
+        assert_eq!(
+            positions,
+            vec![
+                (1000.0, 64.0),
+                (2000.0, 264.0),
+                (3000.0, 64.0),
+                (5500.0, 0.0),
+                (5600.0, 512.0),
+                (6500.0, 256.0)
+            ]
+        );
+
+        // one tick a span, halfway along the path
+        let droplets = of_kind(CatchObjectKind::Droplet);
+        assert_eq!(droplets.len(), 2);
+        assert!(droplets.iter().all(|o| (o.x - 164.0).abs() < 0.01));
+
+        // seven in each 500ms gap between ticks, repeats and the legacy last tick
+        let tiny_droplets = of_kind(CatchObjectKind::TinyDroplet);
+        assert_eq!(tiny_droplets.len(), 28);
+        assert!(tiny_droplets
+            .iter()
+            .all(|o| o.start_time > 1000.0 && o.start_time < 3000.0));
+        assert!(tiny_droplets
+            .iter()
+            .all(|o| o.x >= 64.0 - 20.0 && o.x <= 264.0 + 20.0));
+
+        // 1000ms halved until it's under 100ms, both ends included
+        let bananas = of_kind(CatchObjectKind::Banana);
+        assert_eq!(bananas.len(), 17);
+        assert!(bananas.iter().all(|o| o.x >= 0.0 && o.x < 512.0));
+
+        // 512px in 100ms can't be walked, 256px in 900ms can
+        assert!(fruits[3].hyper_dash);
+        assert!(!fruits[4].hyper_dash);
+        assert!(fruits[4].distance_to_hyper_dash > 0.0);
+        assert!(objects.iter().all(|o| o.gives_combo() || !o.hyper_dash));
+
+        // the conversion is the same every time
+        assert_eq!(
+            objects,
+            catch_objects::convert(&catch_map(), ModSet::default())
+        );
+    }
+
+    #[test]
+    fn test_catch_hyper_dash_times() {
+        // 95.4px past the edge of the catcher in 99.1ms, but osu! cuts the fractions off
+        // the times and only leaves 100ms minus a quarter of a frame, 95.83ms
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   Mode:2\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   0,192,1000.9,1,0\n\
+                   156.25,192,1100,1,0\n";
+        let objects = catch_objects::convert(&BeatmapFile::from_str(map), ModSet::default());
+
+        assert_eq!(objects[0].start_time, 1000.9);
+        assert!(!objects[0].hyper_dash);
+        assert!((objects[0].distance_to_hyper_dash - 0.43).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_catch_hard_rock() {
+        // pairs of fruits on top of each other, each pair more than a second after the
+        // last so only the second fruit of a pair moves
+        let mut map = "osu file format v14\n\
+                       [General]\n\
+                       Mode:2\n\
+                       [Difficulty]\n\
+                       CircleSize:4\n\
+                       [TimingPoints]\n\
+                       0,500,4,2,0,100,1,0\n\
+                       [HitObjects]\n"
+            .to_string();
+        for pair in 0..8 {
+            let time = 1000 + pair * 1100;
+            map += &format!("256,192,{},1,0\n256,192,{},1,0\n", time, time + 100);
+        }
+
+        let hard_rock: ModSet = "HR".parse().unwrap();
+        let objects = catch_objects::convert(&BeatmapFile::from_str(&map), hard_rock);
+        let moved: Vec<f32> = objects.iter().skip(1).step_by(2).map(|o| o.x).collect();
+
+        // worked out by hand from the generator: one number gives the direction of 32
+        // fruits a bit at a time, set bits go right. the offset is up to a quarter of
+        // the 100ms gap, capped at 20
+        assert_eq!(
+            moved,
+            vec![251.0, 246.0, 236.0, 236.0, 269.0, 244.0, 259.0, 248.0]
+        );
+        assert!(objects.iter().step_by(2).all(|o| o.x == 256.0));
+    }
+
+    #[test]
+    fn test_catch_difficulty() {
+        let beatmap = catch_map();
+        let difficulty = catch::calculate(&beatmap, ModSet::default());
+        assert_eq!(difficulty.max_combo, 8);
+        assert_eq!(
+            (
+                difficulty.fruit_count,
+                difficulty.droplet_count,
+                difficulty.tiny_droplet_count,
+                difficulty.banana_count
+            ),
+            (6, 2, 28, 17)
+        );
+        assert!(difficulty.stars > 0.0);
+        assert_eq!(difficulty.approach_rate, 8.0);
+
+        // 1200ms of preempt at 1.5x is 800ms
+        let double_time = catch::calculate(&beatmap, ModSet::from(Mods::DoubleTime));
+        assert!((double_time.approach_rate - 9.0 - 2.0 / 3.0).abs() < 1e-9);
+
+        // standard maps convert too, long enough for the mods to make a difference
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let stars = |mods: ModSet| catch::calculate(&beatmap, mods).stars;
+        let converted = catch::calculate(&beatmap, ModSet::default());
+        assert!(converted.stars > 0.0);
+        // none of the sliders are long enough for a tick
+        assert_eq!(converted.max_combo, 135);
+        assert_eq!(converted.droplet_count, 0);
+
+        let nomod = converted.stars;
+        assert!(stars(ModSet::from(Mods::DoubleTime)) > nomod);
+        assert!(stars(ModSet::from(Mods::HalfTime)) < nomod);
+        assert!(stars(ModSet::from(Mods::HardRock)) > nomod);
+        assert!(stars(ModSet::from(Mods::Easy)) < nomod);
+    }
+
+    // 4K, a 150ms stream over all four columns with a chord and a hold every beat.
+    // `jacks` puts the whole stream in the first column instead
+    fn mania_map(jacks: bool) -> BeatmapFile {
+        let mut map = "osu file format v14\n\
+                       [General]\n\
+                       Mode:3\n\
+                       [Difficulty]\n\
+                       CircleSize:4\n\
+                       OverallDifficulty:8\n\
+                       [TimingPoints]\n\
+                       0,600,4,2,0,100,1,0\n\
+                       [HitObjects]\n"
+            .to_string();
+
+        for i in 0..64 {
+            let time = 1000 + i * 150;
+            let x = if jacks { 64 } else { 64 + (i % 4) * 128 };
+            map += &format!("{},192,{},1,0,0:0:0:0:\n", x, time);
+
+            if i % 4 == 0 {
+                map += &format!("448,192,{},128,0,{}:0:0:0:0:\n", time + 75, time + 375);
+            }
+        }
+
+        BeatmapFile::from_str(&map)
+    }
+
+    #[test]
+    fn test_mania_conversion() {
+        let beatmap = mania_map(false);
+        assert_eq!(mania_objects::key_count(&beatmap), 4);
+
+        let objects = mania_objects::convert(&beatmap);
+        assert_eq!(objects.len(), 80);
+        assert_eq!(
+            objects[..3].to_vec(),
+            vec![
+                ManiaObject::Note(ManiaNote {
+                    column: 0,
+                    start_time: 1000.0
+                }),
+                ManiaObject::Hold(ManiaHold {
+                    column: 3,
+                    start_time: 1075.0,
+                    end_time: 1375.0
+                }),
+                ManiaObject::Note(ManiaNote {
+                    column: 1,
+                    start_time: 1150.0
+                }),
+            ]
+        );
+        assert_eq!(objects[1].end_time() - objects[1].start_time(), 300.0);
+        assert_eq!(objects[0].end_time(), objects[0].start_time());
+
+        // columns split the playfield evenly, anything off the edges is clamped
+        assert_eq!(mania_objects::column(0.0, 7), 0);
+        assert_eq!(mania_objects::column(73.2, 7), 1);
+        assert_eq!(mania_objects::column(511.0, 7), 6);
+        assert_eq!(mania_objects::column(600.0, 7), 6);
+
+        assert_eq!(ModSet::from(Mods::Mania7K).key_count(), Some(7));
+        assert_eq!((Mods::Mania1K | Mods::Hidden).key_count(), Some(1));
+        assert_eq!(ModSet::from(Mods::HardRock).key_count(), None);
+        assert_eq!("9K".parse::<Mods>(), Ok(Mods::Mania9K));
+    }
+
+    #[test]
+    fn test_legacy_sort() {
+        let sorted = |keys: &dyn Fn(usize) -> usize| -> Vec<usize> {
+            let mut items: Vec<(usize, usize)> = (0..20).map(|i| (keys(i), i)).collect();
+            util::legacy_sort_by(&mut items, |a, b| a.0.cmp(&b.0));
+            assert!(items.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+            items.iter().map(|item| item.1).collect()
+        };
+
+        // the orders .NET Framework's sort leaves equal items in
+        assert_eq!(
+            sorted(&|i| i % 3),
+            vec![0, 15, 12, 18, 6, 9, 3, 4, 7, 10, 13, 1, 16, 19, 8, 11, 2, 14, 17, 5]
+        );
+        assert_eq!(
+            sorted(&|_| 0),
+            vec![0, 17, 16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 18, 19]
+        );
+
+        // small slices are insertion sorted, which keeps their order
+        let mut items: Vec<(usize, usize)> = (0..16).map(|i| (i % 2, i)).collect();
+        util::legacy_sort_by(&mut items, |a, b| a.0.cmp(&b.0));
+        let order: Vec<usize> = items.iter().map(|item| item.1).collect();
+        assert_eq!(
+            order,
+            vec![0, 2, 4, 6, 8, 10, 12, 14, 1, 3, 5, 7, 9, 11, 13, 15]
+        );
+    }
+
+    #[test]
+    fn test_mania_difficulty() {
+        let beatmap = mania_map(false);
+        let stars = |mods: ModSet| mania::calculate(&beatmap, mods).stars;
+
+        let difficulty = mania::calculate(&beatmap, ModSet::default());
+        assert_eq!((difficulty.note_count, difficulty.hold_count), (64, 16));
+        assert_eq!(difficulty.key_count, 4);
+        assert!(difficulty.stars > 0.0);
+
+        // 34ms + 3ms for every OD under 10
+        assert_eq!(difficulty.great_hit_window, 40.0);
+        assert_eq!(
+            mania::calculate(&beatmap, ModSet::from(Mods::HardRock)).great_hit_window,
+            29.0
+        );
+        assert_eq!(
+            mania::calculate(&beatmap, ModSet::from(Mods::DoubleTime)).great_hit_window,
+            27.0
+        );
+
+        let nomod = stars(ModSet::default());
+        assert!(stars(ModSet::from(Mods::DoubleTime)) > nomod);
+        assert!(stars(ModSet::from(Mods::HalfTime)) < nomod);
+        assert_eq!(stars(ModSet::from(Mods::HardRock)), nomod);
+
+        // the same stream in one column is all on one finger
+        assert!(mania::calculate(&mania_map(true), ModSet::default()).stars > nomod);
+    }
+
+    // a slider short enough to become notes, one that doubles its velocity, two
+    // circles at the same time and a spinner
+    fn standard_map() -> BeatmapFile {
+        BeatmapFile::from_str(
+            "osu file format v14\n\
+             [General]\n\
+             Mode:0\n\
+             [Difficulty]\n\
+             HPDrainRate:5\n\
+             CircleSize:4\n\
+             OverallDifficulty:5\n\
+             ApproachRate:5\n\
+             SliderMultiplier:1.4\n\
+             SliderTickRate:1\n\
+             [TimingPoints]\n\
+             0,500,4,2,0,100,1,0\n\
+             1500,-50,4,2,0,100,0,0\n\
+             [HitObjects]\n\
+             256,192,1000,2,0,L|326:192,1,70,0|8,0:0|0:0\n\
+             256,192,2000,2,4,L|456:192,1,560\n\
+             256,192,5000,1,0,0:0:0:0:\n\
+             256,192,5000,1,0,0:0:0:0:\n\
+             256,192,6000,12,0,7000,0:0:0:0:\n",
+        )
+    }
+
+    #[test]
+    fn test_taiko_convert() {
+        let beatmap = standard_map();
+        let objects = match convert::convert(&beatmap, Gamemode::Taiko, ModSet::default()) {
+            Some(ConvertedObjects::Taiko(objects)) => objects,
+            other => panic!("expected taiko objects, got {:?}", other),
+        };
+
+        let kinds: Vec<(TaikoObjectKind, f32, bool)> = objects
+            .iter()
+            .map(|o| (o.kind, o.start_time, o.big))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                // a tick apart with the slider's edge sounds
+                (TaikoObjectKind::Don, 1000.0, false),
+                (TaikoObjectKind::Kat, 1250.0, false),
+                (TaikoObjectKind::DrumRoll, 2000.0, true),
+                // both circles merge into one big note
+                (TaikoObjectKind::Don, 5000.0, true),
+                (TaikoObjectKind::Swell, 6000.0, false),
+            ]
+        );
+
+        // 560px at 2x velocity is a second at taiko's speed
+        assert_eq!(objects[2].duration(), 1000.0);
+        assert_eq!(
+            taiko::calculate(&beatmap, ModSet::default()),
+            taiko::calculate_objects(&beatmap, &objects, ModSet::default())
+        );
+
+        // standard has nothing to convert to and other modes only convert to themselves
+        assert_eq!(
+            convert::convert(&beatmap, Gamemode::Standard, ModSet::default()),
+            None
+        );
+        assert_eq!(
+            convert::convert(&taiko_map(&[0]), Gamemode::Mania, ModSet::default()),
+            None
+        );
+        assert_eq!(
+            convert::convert(&taiko_map(&[0]), Gamemode::Taiko, ModSet::default()),
+            Some(ConvertedObjects::Taiko(taiko_objects::convert(&taiko_map(
+                &[0]
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_mania_convert() {
+        let mania_convert = |beatmap: &BeatmapFile, mods: ModSet| match convert::convert(
+            beatmap,
+            Gamemode::Mania,
+            mods,
+        ) {
+            Some(ConvertedObjects::Mania { key_count, objects }) => (key_count, objects),
+            other => panic!("expected mania objects, got {:?}", other),
+        };
+
+        // sliders become holds as long as the slider
+        let (key_count, objects) = mania_convert(&standard_map(), ModSet::default());
+        assert_eq!(key_count, 6);
+        let times: Vec<(f32, f32)> = objects
+            .iter()
+            .map(|o| (o.start_time(), o.end_time()))
+            .collect();
+        assert_eq!(
+            times,
+            vec![
+                (1000.0, 1250.0),
+                (2000.0, 3000.0),
+                (5000.0, 5000.0),
+                (5000.0, 5000.0),
+                (6000.0, 7000.0)
+            ]
+        );
+
+        // few sliders and spinners make for 7 keys, and the layout is the same every time
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let (key_count, objects) = mania_convert(&beatmap, ModSet::default());
+        assert_eq!(key_count, 7);
+        assert_eq!(objects.len(), 170);
+        assert_eq!(mania_convert(&beatmap, ModSet::default()).1, objects);
+        assert!(objects.iter().all(|o| (0..7).contains(&o.column())));
+        assert!(objects
+            .windows(2)
+            .all(|pair| pair[0].start_time() <= pair[1].start_time()));
+
+        // key mods pick the key count, co-op doubles it
+        let (key_count, objects) = mania_convert(&beatmap, ModSet::from(Mods::Mania4K));
+        assert_eq!(key_count, 4);
+        assert!(objects.iter().all(|o| (0..4).contains(&o.column())));
+        let co_op = Mods::Mania4K | Mods::ManiaCoOp;
+        assert_eq!(mania_convert(&beatmap, co_op).0, 8);
+
+        // converts above OD4 have a 34ms window
+        let difficulty = mania::calculate(&beatmap, ModSet::default());
+        assert_eq!(difficulty.key_count, 7);
+        assert_eq!(difficulty.note_count + difficulty.hold_count, 170);
+        assert_eq!(difficulty.great_hit_window, 34.0);
+        assert!(difficulty.stars > 0.0);
+    }
+}
@@ -0,0 +1,56 @@
+mod tests {
+    use sekkei::game::{Gamemode, ModSet, Mods, UnknownMod};
+
+    #[test]
+    fn test_mod_set() {
+        let mods: ModSet = "HDDTHR".parse().unwrap();
+        assert_eq!(mods, Mods::Hidden | Mods::HardRock | Mods::DoubleTime);
+        assert_eq!(mods.to_string(), "HDHRDT");
+        assert_eq!(mods.bits(), 8 | 16 | 64);
+        assert_eq!(
+            "+hd, dt".parse::<ModSet>(),
+            Ok(Mods::Hidden | Mods::DoubleTime)
+        );
+        assert_eq!(
+            "+HD,+DT".parse::<ModSet>(),
+            Ok(Mods::Hidden | Mods::DoubleTime)
+        );
+        assert_eq!(
+            "+HD+DT".parse::<ModSet>(),
+            Ok(Mods::Hidden | Mods::DoubleTime)
+        );
+        assert_eq!("+HD +HR".parse::<ModSet>().unwrap().to_string(), "HDHR");
+        assert_eq!("".parse::<ModSet>().unwrap().to_string(), "NM");
+        assert_eq!("HDXX".parse::<ModSet>(), Err(UnknownMod("XX".to_string())));
+
+        // nightcore and perfect carry double time and sudden death with them
+        let nightcore = ModSet::from_bits(Mods::Nightcore as i64);
+        assert_eq!(nightcore.bits(), 576);
+        assert!(nightcore.contains(Mods::DoubleTime));
+        assert_eq!(nightcore.to_string(), "NC");
+        assert_eq!(nightcore.clock_rate(), 1.5);
+        assert!(ModSet::from(Mods::Perfect).contains(Mods::SuddenDeath));
+
+        let mut mods = nightcore | Mods::Hidden;
+        mods.remove(Mods::DoubleTime);
+        assert_eq!(mods, ModSet::from(Mods::Hidden));
+
+        assert_eq!(
+            (Mods::Easy | Mods::HardRock).incompatible(),
+            vec![(Mods::Easy, Mods::HardRock)]
+        );
+        assert_eq!(
+            (Mods::Nightcore | Mods::HalfTime).incompatible(),
+            vec![(Mods::DoubleTime, Mods::HalfTime)]
+        );
+        assert!(!(Mods::Mania4K | Mods::Mania7K).is_valid());
+        assert!("HDHRDT".parse::<ModSet>().unwrap().is_valid());
+
+        let multiplier = |mods: &str, mode| mods.parse::<ModSet>().unwrap().score_multiplier(mode);
+        assert!((multiplier("HDHR", Gamemode::Standard) - 1.1236).abs() < 1e-9);
+        assert_eq!(multiplier("NC", Gamemode::Standard), 1.12);
+        assert_eq!(multiplier("HR", Gamemode::Catch), 1.12);
+        assert_eq!(multiplier("HDHT", Gamemode::Mania), 0.5);
+        assert_eq!(multiplier("NM", Gamemode::Taiko), 1.0);
+    }
+}
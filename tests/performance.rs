@@ -0,0 +1,324 @@
+mod common;
+
+mod tests {
+    use crate::common::{fixture, score};
+    use sekkei::{
+        difficulty::{catch, mania, standard, taiko},
+        game::{
+            score::{Grade, PartialScore},
+            Gamemode, ModSet, Mods,
+        },
+        parser::beatmap::BeatmapFile,
+        performance::{
+            catch as catch_performance, mania as mania_performance, standard as performance,
+            taiko as taiko_performance,
+        },
+    };
+    #[test]
+    fn test_grades() {
+        let grade = |mods: &str, counts, mode| score(mods.parse().unwrap(), 0, counts).grade(mode);
+
+        // standard and taiko go by the share of 300s, 50s and misses hold back an S
+        assert_eq!(grade("", (100, 0, 0, 0), Gamemode::Standard), Grade::X);
+        assert_eq!(grade("HD", (100, 0, 0, 0), Gamemode::Standard), Grade::XH);
+        assert_eq!(grade("", (95, 4, 1, 0), Gamemode::Standard), Grade::S);
+        assert_eq!(grade("FL", (95, 4, 1, 0), Gamemode::Standard), Grade::SH);
+        assert_eq!(grade("", (95, 3, 2, 0), Gamemode::Standard), Grade::A);
+        assert_eq!(grade("", (95, 4, 0, 1), Gamemode::Taiko), Grade::A);
+        assert_eq!(grade("", (85, 14, 0, 1), Gamemode::Standard), Grade::B);
+        assert_eq!(grade("", (75, 25, 0, 0), Gamemode::Standard), Grade::B);
+        assert_eq!(grade("", (75, 24, 0, 1), Gamemode::Standard), Grade::C);
+        assert_eq!(grade("", (60, 40, 0, 0), Gamemode::Standard), Grade::D);
+
+        // catch counts missed droplets as katus
+        let mut catch = score(ModSet::default(), 0, (97, 1, 1, 0));
+        catch.count_katu = 1;
+        assert_eq!(catch.grade(Gamemode::Catch), Grade::S);
+        catch.count_miss = 2;
+        assert_eq!(catch.grade(Gamemode::Catch), Grade::A);
+
+        // mania weighs every judgement, rainbow 300s count the same as 300s
+        let mut mania = score(ModSet::default(), 0, (50, 0, 0, 0));
+        mania.count_geki = 50;
+        assert_eq!(mania.grade(Gamemode::Mania), Grade::X);
+        mania.count_katu = 10;
+        assert_eq!(mania.grade(Gamemode::Mania), Grade::S);
+        assert_eq!(Grade::XH.to_string(), "XH");
+    }
+
+    #[test]
+    fn test_partial_scores() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let difficulty = standard::calculate(&beatmap, ModSet::default());
+
+        // 103 objects, 100s are used before 50s
+        let counts = |partial: &PartialScore| {
+            let score = performance::score_for(&difficulty, partial);
+            (
+                score.count_300,
+                score.count_100,
+                score.count_50,
+                score.count_miss,
+                score.max_combo,
+            )
+        };
+
+        assert_eq!(
+            counts(&PartialScore::full_combo(ModSet::default(), 100.0)),
+            (103, 0, 0, 0, 135)
+        );
+        assert_eq!(
+            counts(&PartialScore::full_combo(ModSet::default(), 98.0)),
+            (100, 3, 0, 0, 135)
+        );
+        assert_eq!(
+            counts(&PartialScore {
+                mods: ModSet::default(),
+                max_combo: Some(60),
+                accuracy: 97.0,
+                count_miss: 2,
+            }),
+            (99, 2, 0, 2, 60)
+        );
+
+        // too low to reach with 100s alone
+        let (count_300, count_100, count_50, _, _) =
+            counts(&PartialScore::full_combo(ModSet::default(), 30.0));
+        assert_eq!(count_100, 0);
+        assert_eq!(count_300 + count_50, 103);
+
+        let score = performance::score_for(
+            &difficulty,
+            &PartialScore::full_combo(ModSet::default(), 98.0),
+        );
+        assert!((performance::accuracy(&score) - 0.98).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_standard_performance() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let difficulty = standard::calculate(&beatmap, ModSet::default());
+
+        let ss =
+            performance::calculate(&difficulty, &score(ModSet::default(), 135, (103, 0, 0, 0)));
+        assert_eq!(ss.effective_miss_count, 0.0);
+        assert_eq!(ss.flashlight, 0.0);
+
+        // OD8.9 with 71 circles, all of them 300s
+        let accuracy =
+            1.52163f64.powf(difficulty.overall_difficulty) * 2.83 * (71.0f64 / 1000.0).powf(0.3);
+        assert!((ss.accuracy - accuracy).abs() < 1e-9);
+
+        let total =
+            (ss.aim.powf(1.1) + ss.speed.powf(1.1) + ss.accuracy.powf(1.1)).powf(1.0 / 1.1) * 1.14;
+        assert!((ss.total - total).abs() < 1e-9);
+
+        // the pp of the SS as this port computes it today, not a value taken from
+        // osu-tools. it only pins the sum above to the current strains
+        assert!((ss.total - 205.8).abs() < 1.0);
+
+        let what_if = |partial: PartialScore| performance::calculate_partial(&difficulty, &partial);
+        assert_eq!(
+            what_if(PartialScore::full_combo(ModSet::default(), 100.0)),
+            ss
+        );
+        assert!(what_if(PartialScore::full_combo(ModSet::default(), 98.0)).total < ss.total);
+
+        // a miss and a broken combo cost more than the 100s do
+        let played = score(ModSet::default(), 60, (99, 2, 0, 2));
+        let broken = performance::calculate(&difficulty, &played);
+        assert!(broken.effective_miss_count > 2.0);
+        assert!(broken.total < what_if(PartialScore::full_combo(ModSet::default(), 97.0)).total);
+
+        // a full combo with the same misses turned into 100s is what the play could have been
+        let fixed =
+            performance::calculate(&difficulty, &score(ModSet::default(), 135, (99, 4, 0, 0)));
+        assert!(fixed.total > broken.total);
+    }
+
+    #[test]
+    fn test_standard_performance_mods() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let full_combo = |mods: ModSet| {
+            let difficulty = standard::calculate(&beatmap, mods);
+            performance::calculate_partial(&difficulty, &PartialScore::full_combo(mods, 99.0))
+        };
+
+        let nomod = full_combo(ModSet::default());
+        assert!(full_combo(ModSet::from(Mods::Hidden)).total > nomod.total);
+        assert!(full_combo(ModSet::from(Mods::HardRock)).total > nomod.total);
+        assert!(
+            full_combo(ModSet::from(Mods::DoubleTime)).total
+                > full_combo(ModSet::from(Mods::HardRock)).total
+        );
+
+        let flashlight = full_combo(ModSet::from(Mods::Flashlight));
+        assert!(flashlight.flashlight > 0.0);
+        assert!(flashlight.total > nomod.total);
+
+        // nothing to tap or time with relax
+        let relax = full_combo(ModSet::from(Mods::Relax));
+        assert_eq!(relax.speed, 0.0);
+        assert_eq!(relax.accuracy, 0.0);
+        assert!(relax.total < nomod.total);
+
+        // no fail only costs anything with misses
+        assert_eq!(full_combo(ModSet::from(Mods::NoFail)).total, nomod.total);
+    }
+
+    #[test]
+    fn test_taiko_performance() {
+        // 64 notes, don don kat 160ms apart
+        let mut map = "osu file format v14\n\
+                       [General]\n\
+                       Mode:1\n\
+                       [Difficulty]\n\
+                       OverallDifficulty:5\n\
+                       [TimingPoints]\n\
+                       0,500,4,2,0,100,1,0\n\
+                       [HitObjects]\n"
+            .to_string();
+        for i in 0..64 {
+            let hit_sound = if i % 3 == 2 { 2 } else { 0 };
+            map += &format!("256,192,{},1,{},0:0:0:0:\n", 1000 + i * 160, hit_sound);
+        }
+
+        let difficulty = taiko::calculate(&BeatmapFile::from_str(&map), ModSet::default());
+        assert_eq!(difficulty.max_combo, 64);
+
+        let score = taiko_performance::score_for(
+            &difficulty,
+            &PartialScore::full_combo(ModSet::default(), 95.0),
+        );
+        assert_eq!(
+            (score.count_300, score.count_100, score.count_miss),
+            (58, 6, 0)
+        );
+        assert!((taiko_performance::accuracy(&score) - 0.953125).abs() < 1e-9);
+
+        let pp =
+            |partial: PartialScore| taiko_performance::calculate_partial(&difficulty, &partial);
+        let ss = pp(PartialScore::full_combo(ModSet::default(), 100.0));
+        assert!(ss.strain > 0.0 && ss.accuracy > 0.0);
+        assert!(
+            (ss.total - (ss.strain.powf(1.1) + ss.accuracy.powf(1.1)).powf(1.0 / 1.1) * 1.1).abs()
+                < 1e-9
+        );
+
+        assert!(pp(PartialScore::full_combo(ModSet::default(), 95.0)).total < ss.total);
+        let missed = pp(PartialScore {
+            mods: ModSet::default(),
+            max_combo: None,
+            accuracy: 100.0,
+            count_miss: 2,
+        });
+        assert!(missed.strain < ss.strain);
+
+        // the difficulty doesn't change with either, only the multiplier does
+        let no_fail = pp(PartialScore::full_combo(ModSet::from(Mods::NoFail), 100.0));
+        assert!((no_fail.total - ss.total * 0.9).abs() < 1e-9);
+        let hidden = pp(PartialScore::full_combo(ModSet::from(Mods::Hidden), 100.0));
+        assert!(hidden.total > ss.total);
+    }
+
+    #[test]
+    fn test_catch_performance() {
+        let beatmap = fixture("IMAGINARY LIKE THE JUSTICE.osu");
+        let difficulty = catch::calculate(&beatmap, ModSet::default());
+
+        // 135 fruits and 32 tiny droplets, misses come out of fruits when there are
+        // no droplets to take them from
+        let partial = PartialScore {
+            mods: ModSet::default(),
+            max_combo: Some(100),
+            accuracy: 97.0,
+            count_miss: 2,
+        };
+        let score = catch_performance::score_for(&difficulty, &partial);
+        assert_eq!(
+            (
+                score.count_300,
+                score.count_100,
+                score.count_50,
+                score.count_katu,
+                score.count_miss
+            ),
+            (133, 0, 29, 3, 2)
+        );
+        assert!((catch_performance::accuracy(&score) - 162.0 / 167.0).abs() < 1e-9);
+
+        let pp =
+            |partial: PartialScore| catch_performance::calculate_partial(&difficulty, &partial);
+        let ss = pp(PartialScore::full_combo(ModSet::default(), 100.0)).total;
+        assert!(ss > 0.0);
+        assert!(pp(partial).total < ss);
+        assert!(pp(PartialScore::full_combo(ModSet::default(), 98.0)).total < ss);
+
+        let no_fail = pp(PartialScore::full_combo(ModSet::from(Mods::NoFail), 100.0)).total;
+        assert!((no_fail - ss * 0.9).abs() < 1e-9);
+
+        // AR9.3 gets 3% for being over 9, and hidden another 5% plus 7.5% per AR under 10
+        let hidden = pp(PartialScore::full_combo(ModSet::from(Mods::Hidden), 100.0)).total;
+        assert!((hidden / ss - (1.05 + 0.075 * (10.0 - difficulty.approach_rate))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mania_performance() {
+        // 4K, 100 notes across the columns
+        let mut map = "osu file format v14\n\
+                       [General]\n\
+                       Mode:3\n\
+                       [Difficulty]\n\
+                       CircleSize:4\n\
+                       OverallDifficulty:8\n\
+                       [TimingPoints]\n\
+                       0,600,4,2,0,100,1,0\n\
+                       [HitObjects]\n"
+            .to_string();
+        for i in 0..100 {
+            map += &format!(
+                "{},192,{},1,0,0:0:0:0:\n",
+                64 + (i % 4) * 128,
+                1000 + i * 120
+            );
+        }
+
+        let difficulty = mania::calculate(&BeatmapFile::from_str(&map), ModSet::default());
+        assert_eq!(difficulty.note_count, 100);
+
+        // 95% is 570 of 600, taken greedily: 94 MAXes then 6 50s
+        let score = mania_performance::score_for(
+            &difficulty,
+            &PartialScore::full_combo(ModSet::default(), 95.0),
+        );
+        assert_eq!(
+            (
+                score.count_geki,
+                score.count_300,
+                score.count_katu,
+                score.count_100,
+                score.count_50,
+                score.count_miss
+            ),
+            (94, 0, 0, 0, 6, 0)
+        );
+
+        let pp =
+            |partial: PartialScore| mania_performance::calculate_partial(&difficulty, &partial);
+        let ss = pp(PartialScore::full_combo(ModSet::default(), 100.0));
+        let length_bonus = 1.0 + 0.1 * 100.0 / 1500.0;
+        assert!((ss.total - (difficulty.stars - 0.15).powf(2.2) * length_bonus * 8.0).abs() < 1e-9);
+        assert!(pp(PartialScore::full_combo(ModSet::default(), 95.0)).total < ss.total);
+
+        // nothing under 80%
+        assert_eq!(
+            pp(PartialScore::full_combo(ModSet::default(), 70.0)).total,
+            0.0
+        );
+
+        let easy = pp(PartialScore::full_combo(ModSet::from(Mods::Easy), 100.0));
+        assert!((easy.total - ss.total * 0.5).abs() < 1e-9);
+        let no_fail = pp(PartialScore::full_combo(ModSet::from(Mods::NoFail), 100.0));
+        assert!((no_fail.total - ss.total * 0.75).abs() < 1e-9);
+    }
+}
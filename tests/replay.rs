@@ -0,0 +1,498 @@
+mod tests {
+    use sekkei::{
+        game::{Gamemode, ModSet, Mods},
+        parser::beatmap::BeatmapFile,
+        parser::replays::{error::ReplayErrorKind, objects::LifeBarPoint, Replay},
+        replay::{
+            analysis::{self, KeyAction},
+            autoplay,
+            simulator::{self, HitResult},
+            Frame, Keys,
+        },
+        util::Vector2,
+    };
+    use std::env;
+
+    #[test]
+    fn test_replay_parser() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let replay = Replay::from_file(&path);
+
+        assert_eq!(replay.gamemode, Gamemode::Standard);
+        assert_eq!(replay.game_version, 20210520);
+        assert_eq!(replay.beatmap_hash, "6cabf3843118d39ca0096be64ce51b17");
+        assert_eq!(replay.player_name, "sekkei");
+        assert_eq!(replay.replay_hash.len(), 32);
+
+        assert_eq!(replay.count_300, 1);
+        assert_eq!(replay.count_miss, 0);
+        assert_eq!(replay.score, 342);
+        assert_eq!(replay.max_combo, 3);
+        assert!(replay.perfect);
+        assert_eq!(replay.mods, ModSet::default());
+
+        assert_eq!(
+            replay.life_bar,
+            vec![
+                LifeBarPoint {
+                    time: 1000,
+                    life: 1.0
+                },
+                LifeBarPoint {
+                    time: 6548,
+                    life: 1.0
+                },
+            ]
+        );
+        assert_eq!(replay.timestamp, 637570656000000000);
+        assert_eq!(replay.online_score_id, 0);
+        assert_eq!(replay.target_accuracy, None);
+
+        // the seed frame is pulled out of the frame list
+        assert_eq!(replay.seed, Some(7364));
+        assert_eq!(replay.frames.len(), 41);
+        assert_eq!(
+            replay.frames[0],
+            Frame {
+                time: 0,
+                x: 256.0,
+                y: -500.0,
+                keys: 0
+            }
+        );
+        assert_eq!(replay.frames[1].time, -1);
+
+        // deltas are summed into absolute times
+        let head = replay.frames.iter().find(|f| f.keys != 0).unwrap();
+        assert_eq!(head.time, 6173);
+        assert_eq!((head.x, head.y), (256.0, 226.0));
+        assert_eq!(replay.frames.last().unwrap().time, 6700);
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let data = std::fs::read(&path).unwrap();
+
+        let err = Replay::try_from_bytes(&data[..20]).unwrap_err();
+        assert!(matches!(err.kind, ReplayErrorKind::UnexpectedEof));
+        assert_eq!(err.field, "beatmap hash");
+
+        let mut bad_mode = data.clone();
+        bad_mode[0] = 7;
+        let err = Replay::try_from_bytes(&bad_mode).unwrap_err();
+        assert!(matches!(err.kind, ReplayErrorKind::InvalidValue));
+        assert_eq!(err.field, "mode");
+    }
+
+    #[test]
+    fn test_replay_round_trip() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let replay = Replay::from_file(&path);
+
+        let encoded = replay.to_bytes();
+        let decoded = Replay::from_bytes(&encoded);
+
+        assert_eq!(decoded, replay);
+        assert_eq!(decoded.to_bytes(), encoded);
+    }
+
+    #[test]
+    fn test_replay_writer() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let mut replay = Replay::from_file(&path);
+
+        // anonymised and trimmed down to the first few frames
+        replay.player_name = "".to_string();
+        replay.replay_hash = "".to_string();
+        replay.frames.truncate(5);
+        replay.life_bar.push(LifeBarPoint {
+            time: 7000,
+            life: 0.5,
+        });
+        assert_eq!(replay.life_bar_string(), "1000|1,6548|1,7000|0.5,");
+
+        let decoded = Replay::from_bytes(&replay.to_bytes());
+        assert_eq!(decoded.player_name, "");
+        assert_eq!(decoded.replay_hash, replay.replay_checksum());
+        assert_eq!(decoded.replay_hash.len(), 32);
+
+        // the md5 of "1p0o0o0t0a6cabf3843118d39ca0096be64ce51b17r3eTrueyo342uX0True",
+        // the grade and mods follow the player name and score
+        assert_eq!(replay.replay_checksum(), "b50b581cffb86ca1b45f6847475e30b6");
+        let mut hidden = replay.clone();
+        hidden.mods = ModSet::from(Mods::Hidden);
+        assert_eq!(hidden.replay_checksum(), "c3fdc8981fd069e6af526219c426178e");
+        assert_eq!(decoded.frames, replay.frames);
+        assert_eq!(decoded.life_bar, replay.life_bar);
+        assert_eq!(decoded.seed, Some(7364));
+
+        // older clients stop after the replay data, target practice adds the accuracy
+        replay.game_version = 20110101;
+        replay.mods = ModSet::from(Mods::Target);
+        replay.target_accuracy = Some(0.75);
+        replay.online_score_id = 0;
+
+        let decoded = Replay::from_bytes(&replay.to_bytes());
+        assert_eq!(decoded.game_version, 20110101);
+        assert_eq!(decoded.target_accuracy, Some(0.75));
+        assert_eq!(decoded.frames, replay.frames);
+    }
+
+    fn frame(time: i32, x: f32, y: f32, keys: i32) -> Frame {
+        Frame { time, x, y, keys }
+    }
+
+    #[test]
+    fn test_key_events() {
+        let frames = vec![
+            // skip frames and a rewind at the start
+            frame(0, 256.0, -500.0, 0),
+            frame(-1, 256.0, -500.0, 0),
+            frame(100, 0.0, 0.0, 0),
+            frame(90, 0.0, 0.0, 5),
+            // K1 comes with M1 set, then M2 on its own joins in
+            frame(200, 10.0, 0.0, 5),
+            frame(250, 20.0, 0.0, 7),
+            frame(300, 30.0, 0.0, 2),
+            frame(350, 40.0, 0.0, 10),
+        ];
+
+        let playable = analysis::playable_frames(&frames);
+        assert_eq!(playable.len(), 5);
+        assert_eq!(playable[0].time, 100);
+
+        // only the frame that steps back is dropped, the ones after it stay
+        let rewound = vec![
+            frame(100, 0.0, 0.0, 0),
+            frame(50, 0.0, 0.0, 0),
+            frame(60, 0.0, 0.0, 0),
+            frame(120, 0.0, 0.0, 0),
+        ];
+        let times: Vec<i32> = analysis::playable_frames(&rewound)
+            .iter()
+            .map(|f| f.time)
+            .collect();
+        assert_eq!(times, vec![100, 60, 120]);
+
+        let events: Vec<(Keys, KeyAction, i32)> = analysis::key_events(&frames)
+            .iter()
+            .map(|e| (e.key, e.action, e.time))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (Keys::K1, KeyAction::Press, 200),
+                (Keys::M2, KeyAction::Press, 250),
+                (Keys::K1, KeyAction::Release, 300),
+                (Keys::M2, KeyAction::Release, 350),
+                (Keys::K2, KeyAction::Press, 350),
+            ]
+        );
+
+        let presses = analysis::key_presses(&frames);
+        assert_eq!(presses.len(), 3);
+        assert_eq!((presses[0].key, presses[0].duration()), (Keys::K1, 100));
+        assert_eq!(presses[0].position, Vector2::new(10.0, 0.0));
+        assert_eq!((presses[1].key, presses[1].duration()), (Keys::M2, 100));
+        // still held when the replay ends
+        assert_eq!((presses[2].key, presses[2].duration()), (Keys::K2, 0));
+    }
+
+    #[test]
+    fn test_cursor_motion() {
+        let frames = vec![
+            frame(0, 0.0, 0.0, 0),
+            frame(10, 10.0, 0.0, 0),
+            frame(20, 30.0, 0.0, 0),
+            frame(20, 35.0, 0.0, 0),
+        ];
+
+        let motion = analysis::cursor_motion(&frames);
+        assert_eq!(motion.len(), 4);
+        assert_eq!(motion[0].velocity, Vector2::new(0.0, 0.0));
+        assert_eq!(motion[1].velocity, Vector2::new(1.0, 0.0));
+        assert_eq!(motion[1].acceleration, Vector2::new(0.1, 0.0));
+        assert_eq!(motion[2].speed(), 2.0);
+        assert_eq!(motion[2].acceleration, Vector2::new(0.1, 0.0));
+        assert_eq!(motion[3].velocity, motion[2].velocity);
+
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let replay = Replay::from_file(&path);
+        let motion = analysis::cursor_motion(&replay.frames);
+
+        assert_eq!(motion.len(), replay.frames.len() - 2);
+        assert!(motion.iter().all(|m| m.speed().is_finite()));
+
+        // one press on the slider head, held through the slider
+        let presses = analysis::key_presses(&replay.frames);
+        assert_eq!(presses.len(), 1);
+        assert_eq!(presses[0].key, Keys::K1);
+        assert_eq!(presses[0].start_time, 6173);
+    }
+
+    fn replay_with(frames: Vec<Frame>) -> Replay {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/kakushigoto.osr";
+        let mut replay = Replay::from_file(&path);
+
+        replay.frames = frames;
+        replay
+    }
+
+    #[test]
+    fn test_simulator_fixture() {
+        let dir = env::current_dir().unwrap().to_str().unwrap().to_string() + "/tests/files/";
+        let beatmap = BeatmapFile::from_file(&(dir.clone() + "kakushigoto.osu"));
+        let replay = Replay::from_file(&(dir + "kakushigoto.osr"));
+
+        let simulation = simulator::simulate(&beatmap, &replay);
+
+        assert_eq!(simulation.judgements.len(), 1);
+        assert_eq!(simulation.judgements[0].result, HitResult::Hit300);
+        assert_eq!(simulation.judgements[0].hit_error, Some(0.0));
+        assert_eq!(simulation.slider_breaks(), 0);
+
+        // head, one tick and the end
+        assert_eq!(simulation.score.max_combo, replay.max_combo);
+        assert_eq!(simulation.score.count_300, replay.count_300);
+        assert_eq!(simulation.score.count_miss, 0);
+        assert_eq!(simulation.score.count_geki, 1);
+    }
+
+    #[test]
+    fn test_simulator_judgements() {
+        // OD5: 50ms for a 300, 100ms for a 100, 150ms for a 50
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:5\n\
+                   SliderMultiplier:1\n\
+                   SliderTickRate:1\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,100,1000,5,0,0:0:0:0:\n\
+                   200,100,2000,1,0,0:0:0:0:\n\
+                   300,100,3000,1,0,0:0:0:0:\n\
+                   400,100,4000,1,0,0:0:0:0:\n\
+                   100,300,5000,2,0,L|300:300,1,200\n";
+        let beatmap = BeatmapFile::from_str(map);
+
+        let replay = replay_with(vec![
+            frame(980, 100.0, 100.0, 0),
+            frame(980, 100.0, 100.0, 5),
+            frame(1000, 100.0, 100.0, 0),
+            frame(2070, 200.0, 100.0, 10),
+            frame(2100, 200.0, 100.0, 0),
+            // nothing for the third circle, and the fourth is clicked way off
+            frame(3990, 0.0, 0.0, 5),
+            frame(4010, 0.0, 0.0, 0),
+            // the slider is let go of halfway through
+            frame(5000, 100.0, 300.0, 5),
+            frame(5250, 150.0, 300.0, 5),
+            frame(5500, 200.0, 300.0, 5),
+            frame(5510, 200.0, 300.0, 0),
+            frame(6000, 300.0, 300.0, 0),
+        ]);
+
+        let simulation = simulator::simulate(&beatmap, &replay);
+        let results: Vec<HitResult> = simulation.judgements.iter().map(|j| j.result).collect();
+        assert_eq!(
+            results,
+            vec![
+                HitResult::Hit300,
+                HitResult::Hit100,
+                HitResult::Miss,
+                HitResult::Miss,
+                HitResult::Hit100,
+            ]
+        );
+
+        assert_eq!(simulation.hit_errors(), vec![-20.0, 70.0, 0.0]);
+        assert!((simulation.unstable_rate() - 385.9).abs() < 0.1);
+
+        // the tick at the middle was held, the end wasn't
+        assert!(!simulation.judgements[4].slider_break);
+
+        let score = &simulation.score;
+        assert_eq!(
+            (
+                score.count_300,
+                score.count_100,
+                score.count_50,
+                score.count_miss
+            ),
+            (1, 2, 0, 2)
+        );
+        assert_eq!(score.max_combo, 2);
+        assert!(score.score > 0);
+    }
+
+    #[test]
+    fn test_simulator_hard_rock_stacking() {
+        // stacked at approach rate 8, too far apart for hard rock's 10
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   StackLeniency:0.7\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:5\n\
+                   ApproachRate:8\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,100,1000,5,0,0:0:0:0:\n\
+                   100,100,1400,1,0,0:0:0:0:\n";
+        let beatmap = BeatmapFile::from_str(map);
+        assert_eq!(beatmap.hit_objects[0].stack_height, 1);
+
+        let mods = ModSet::from(Mods::HardRock);
+        let radius = beatmap.attributes(Gamemode::Standard, mods).circle_radius as f32;
+
+        // just inside the unstacked circle on the flipped map, on the side away from
+        // where the stack would have moved it
+        let edge = (radius - 2.0) / 2f32.sqrt();
+        let (x, y) = (100.0 + edge, 284.0 + edge);
+        let mut replay = replay_with(vec![
+            frame(1000, x, y, 1),
+            frame(1010, x, y, 0),
+            frame(1400, x, y, 2),
+            frame(1410, x, y, 0),
+        ]);
+        replay.mods = mods;
+
+        let simulation = simulator::simulate(&beatmap, &replay);
+        let results: Vec<HitResult> = simulation.judgements.iter().map(|j| j.result).collect();
+        assert_eq!(results, vec![HitResult::Hit300, HitResult::Hit300]);
+    }
+
+    #[test]
+    fn test_simulator_spinner_score() {
+        // OD5 needs 5 spins a second, 15 for this spinner
+        let map = "osu file format v14\n\
+                   [Difficulty]\n\
+                   OverallDifficulty:5\n\
+                   [TimingPoints]\n\
+                   0,500,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   256,192,1000,12,0,4000,0:0:0:0:\n";
+        let beatmap = BeatmapFile::from_str(map);
+
+        // a quarter turn every 40ms, 37 and a half half spins
+        let frames = (0..=75)
+            .map(|step| {
+                let angle = step as f32 * std::f32::consts::FRAC_PI_2;
+                let x = 256.0 + 100.0 * angle.cos();
+                let y = 192.0 + 100.0 * angle.sin();
+                frame(1000 + step * 40, x, y, 1)
+            })
+            .collect();
+
+        let simulation = simulator::simulate(&beatmap, &replay_with(frames));
+        assert_eq!(simulation.judgements[0].result, HitResult::Hit300);
+
+        // 100 for every other half spin up to 18, then 1000 for every other one after
+        // that. the 300 itself has no combo to be multiplied by
+        assert_eq!(simulation.score.score, 9 * 100 + 9 * 1000 + 300);
+    }
+
+    // every judgement a combo can come from: circles, slider parts and spinners
+    fn full_combo(beatmap: &BeatmapFile) -> i32 {
+        beatmap
+            .hit_objects
+            .iter()
+            .map(|object| match &object.slider_objects {
+                Some(slider_objects) => slider_objects.len() as i32,
+                None => 1,
+            })
+            .sum()
+    }
+
+    fn assert_perfect(beatmap: &BeatmapFile) {
+        let replay = replay_with(autoplay::generate(beatmap));
+        let simulation = simulator::simulate(beatmap, &replay);
+
+        assert_eq!(simulation.judgements.len(), beatmap.hit_objects.len());
+        for judgement in &simulation.judgements {
+            assert_eq!(
+                judgement.result,
+                HitResult::Hit300,
+                "object {} at {}",
+                judgement.index,
+                judgement.time
+            );
+            assert!(judgement.hit_error.is_none_or(|error| error.abs() < 1.0));
+        }
+
+        assert_eq!(simulation.slider_breaks(), 0);
+        assert_eq!(simulation.score.max_combo, full_combo(beatmap));
+    }
+
+    #[test]
+    fn test_autoplay_fixtures() {
+        for file in &["kakushigoto.osu", "IMAGINARY LIKE THE JUSTICE.osu"] {
+            let path =
+                env::current_dir().unwrap().to_str().unwrap().to_string() + "/tests/files/" + file;
+            assert_perfect(&BeatmapFile::from_file(&path));
+        }
+    }
+
+    #[test]
+    fn test_autoplay() {
+        let map = "osu file format v14\n\
+                   [General]\n\
+                   StackLeniency:0.7\n\
+                   [Difficulty]\n\
+                   CircleSize:4\n\
+                   OverallDifficulty:8\n\
+                   ApproachRate:9\n\
+                   SliderMultiplier:1.4\n\
+                   SliderTickRate:2\n\
+                   [TimingPoints]\n\
+                   0,300,4,2,0,100,1,0\n\
+                   [HitObjects]\n\
+                   100,100,1000,5,0,0:0:0:0:\n\
+                   100,100,1150,1,0,0:0:0:0:\n\
+                   100,100,1300,1,0,0:0:0:0:\n\
+                   300,200,2000,6,0,P|350:250|300:300,3,140\n\
+                   50,300,4000,2,0,B|100:200|200:350|250:250,2,210\n\
+                   256,192,6000,12,0,8000,0:0:0:0:\n\
+                   400,100,9000,5,0,0:0:0:0:\n";
+        let beatmap = BeatmapFile::from_str(map);
+        assert_perfect(&beatmap);
+
+        let frames = autoplay::generate(&beatmap);
+        let presses = analysis::key_presses(&frames);
+        let keys: Vec<Keys> = presses.iter().map(|press| press.key).collect();
+
+        // the stream alternates, everything after a pause starts on K1 again
+        assert_eq!(
+            keys,
+            vec![
+                Keys::K1,
+                Keys::K2,
+                Keys::K1,
+                Keys::K1,
+                Keys::K1,
+                Keys::K1,
+                Keys::K1
+            ]
+        );
+
+        for (press, object) in presses.iter().zip(&beatmap.hit_objects) {
+            assert_eq!(press.start_time, object.start_time as i32);
+            assert!(press.end_time >= object.end_time.max(object.start_time) as i32);
+        }
+
+        // the cursor stays on time order and ends up on the last circle
+        assert!(frames.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        let last_press = presses.last().unwrap();
+        assert_eq!(last_press.position, Vector2::new(400.0, 100.0));
+    }
+}
@@ -0,0 +1,118 @@
+mod tests {
+    use sekkei::{
+        parser::storyboard::{
+            objects::{CommandKind, Easing, Element, Layer, LoopType, Origin, Parameter},
+            Storyboard,
+        },
+        util::{Colour, Vector2},
+    };
+    use std::env;
+
+    #[test]
+    fn test_storyboard_parser() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/storyboard.osb";
+        let sb = Storyboard::from_file(&path);
+
+        assert_eq!(sb.elements.len(), 4);
+
+        let sprites = sb.sprites();
+        assert_eq!(sprites.len(), 3);
+
+        // background sprite
+        assert_eq!(sprites[0].layer, Layer::Background);
+        assert_eq!(sprites[0].origin, Origin::TopLeft);
+        assert_eq!(sprites[0].commands.len(), 2);
+        assert_eq!(sprites[0].commands[1].easing, Easing::EasingOut);
+        assert_eq!(
+            sprites[0].commands[1].kind,
+            CommandKind::Move {
+                start: Vector2::new(320.0, 240.0),
+                end: Vector2::new(100.0, 100.0)
+            }
+        );
+
+        // variables are substituted before parsing
+        let star = sprites[1];
+        assert_eq!(star.layer, Layer::Foreground);
+        assert_eq!(star.origin, Origin::Centre);
+        assert_eq!(star.path, "sb/star.png");
+
+        // chained scale values become two commands
+        assert_eq!(star.commands.len(), 4);
+        assert_eq!(star.commands[1].start_time, 1000.0);
+        assert_eq!(star.commands[1].end_time, 1500.0);
+        assert_eq!(
+            star.commands[1].kind,
+            CommandKind::Scale {
+                start: 1.0,
+                end: 0.5
+            }
+        );
+
+        // empty end times fall back to the start time
+        assert_eq!(star.commands[2].end_time, 500.0);
+        assert_eq!(
+            star.commands[2].kind,
+            CommandKind::Colour {
+                start: Colour::new(255, 128, 0),
+                end: Colour::new(255, 128, 0)
+            }
+        );
+        assert_eq!(
+            star.commands[3].kind,
+            CommandKind::Parameter(Parameter::AdditiveBlend)
+        );
+
+        assert_eq!(star.loops.len(), 1);
+        assert_eq!(star.loops[0].loop_count, 4);
+        assert_eq!(star.loops[0].commands.len(), 1);
+
+        assert_eq!(star.triggers.len(), 1);
+        assert_eq!(star.triggers[0].trigger_name, "HitSoundClap");
+        assert_eq!(star.triggers[0].commands[0].easing, Easing::EasingIn);
+
+        match &sb.elements[2] {
+            Element::Animation(animation) => {
+                assert_eq!(animation.frame_count, 3);
+                assert_eq!(animation.loop_type, LoopType::LoopOnce);
+                assert_eq!(animation.sprite.commands.len(), 1);
+            }
+            element => panic!("expected an animation, got {:?}", element),
+        }
+
+        match &sb.elements[3] {
+            Element::Sample(sample) => {
+                assert_eq!(sample.time, 1500.0);
+                assert_eq!(sample.volume, 70);
+            }
+            element => panic!("expected a sample, got {:?}", element),
+        }
+
+        assert_eq!(
+            sb.files(),
+            vec![
+                "sb/bg.jpg",
+                "sb/blink0.png",
+                "sb/blink1.png",
+                "sb/blink2.png",
+                "sb/star.png",
+                "sb/whoosh.wav"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_storyboard_from_beatmap() {
+        let path = env::current_dir().unwrap().to_str().unwrap().to_string()
+            + "/tests/files/IMAGINARY LIKE THE JUSTICE.osu";
+        let sb = Storyboard::from_file(&path);
+
+        // only a background, which stays on the beatmap
+        assert!(sb.elements.is_empty());
+
+        let err = Storyboard::try_from_str("[Events]\n F,0,0,1000,0,1\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.field, "sprite");
+    }
+}